@@ -323,6 +323,20 @@ function Dashboard({ onCompareClick }) {
   const topEntities = data.entities.slice(0, 10)
   const maxPct = topEntities[0]?.percentage || 100
 
+  // Evidence of one side's total: the first of its facts (line items and
+  // months of a year come from the same published artifact)
+  async function openSideEvidence(row, year) {
+    try {
+      const url = `${API}/facts?metric_id=${selectedMetric}&entity_id=${row.entity_id}&from=${year}-01-01&to=${year}-12-31&limit=1`
+      const res = await fetch(url)
+      if (!res.ok) throw new Error('Hechos no encontrados')
+      const { facts } = await res.json()
+      await openEvidence(facts[0]?.fact_id)
+    } catch (e) {
+      alert('Error: ' + e.message)
+    }
+  }
+
   return (
     <div>
       {/* Year Selector */}
@@ -633,13 +647,13 @@ function Comparador() {
                         {formatPct(row.pct_change)}
                       </td>
                       <td style={{ ...styles.td, textAlign: 'center' }}>
-                        {row.fact_id_a && (
-                          <button onClick={() => openEvidence(row.fact_id_a)} style={{ ...styles.button(false), padding: '4px 8px', fontSize: '12px', marginRight: '4px' }}>
+                        {row.fact_count_a > 0 && (
+                          <button onClick={() => openSideEvidence(row, result.year_a)} title={`${row.fact_count_a} hechos`} style={{ ...styles.button(false), padding: '4px 8px', fontSize: '12px', marginRight: '4px' }}>
                             A
                           </button>
                         )}
-                        {row.fact_id_b && (
-                          <button onClick={() => openEvidence(row.fact_id_b)} style={{ ...styles.button(false), padding: '4px 8px', fontSize: '12px' }}>
+                        {row.fact_count_b > 0 && (
+                          <button onClick={() => openSideEvidence(row, result.year_b)} title={`${row.fact_count_b} hechos`} style={{ ...styles.button(false), padding: '4px 8px', fontSize: '12px' }}>
                             B
                           </button>
                         )}
@@ -2,11 +2,29 @@
 //!
 //! Endpoints:
 //! - GET /health - Health check
+//! - GET /dashboard - Budget overview (consolidated or gross totals)
 //! - GET /metrics - List all metrics
 //! - GET /entities - Search/list entities
+//! - GET /entities/children - Direct children of an entity
+//! - GET /entities/ancestors - Parent chain of an entity (root first)
+//! - GET /entities/rollup - Totals of an entity and its children, including descendants
+//! - GET /entities/aliases - Source names resolved to each canonical entity
+//! - GET /entities/review-queue - Source entity names no crosswalk rule matched
 //! - GET /facts - Query facts with filters
 //! - GET /compare - Compare facts between years
 //! - GET /evidence - Get evidence for a fact
+//! - GET /snapshots - List parser snapshots and their status
+//! - GET /classifier - Economic classifier catalog (subtítulo/ítem) for a year
+//! - GET /facts/by-classifier - Totals grouped by subtítulo or ítem
+//! - GET /partidas - Official Partida names in force for a year
+//! - GET /schema-drift - Structural fingerprint of each artifact and drift vs the previous fiscal year
+//! - GET /execution-rate - Budget lifecycle stages (ley, vigente, devengado, pagado) and execution rates per entity
+//!
+//! Fact queries only read the currently published snapshot of each artifact,
+//! unless a snapshot is requested explicitly (`snapshot_id`, `snapshot_a`, `snapshot_b`).
+//!
+//! Monetary amounts are exact decimals (NUMERIC in Postgres) and are serialized
+//! as JSON strings so no client-side float parsing can drop digits.
 
 use anyhow::Context;
 use axum::{
@@ -17,6 +35,8 @@ use axum::{
     Json, Router,
 };
 use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 use sqlx::{postgres::PgPoolOptions, PgPool};
 use std::sync::Arc;
@@ -57,6 +77,84 @@ struct EntityResponse {
     entity_key: String,
     display_name: String,
     entity_type: String,
+    parent_entity_id: Option<Uuid>,
+    level: Option<i16>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct RollupChild {
+    entity_id: Uuid,
+    entity_key: String,
+    display_name: String,
+    entity_type: String,
+    level: Option<i16>,
+    total: Option<Decimal>,
+    fact_count: i64,
+}
+
+#[derive(Serialize)]
+struct RollupResponse {
+    entity: EntityResponse,
+    metric_id: Uuid,
+    year: i32,
+    /// Facts attached directly to the entity (not to its descendants)
+    own_total: Option<Decimal>,
+    /// own_total + every child's subtree total
+    total: Decimal,
+    children: Vec<RollupChild>,
+}
+
+/// One metric of the budget lifecycle, as synced from config/ciclo_presupuestario.json
+#[derive(sqlx::FromRow)]
+struct LifecycleMetric {
+    metric_key: String,
+    stage: String,
+    stage_name: String,
+    period_kind: String,
+}
+
+/// Published total of one lifecycle metric under one entity
+#[derive(sqlx::FromRow)]
+struct LifecycleTotal {
+    root_id: Uuid,
+    metric_key: String,
+    total: Option<Decimal>,
+    fact_count: i64,
+}
+
+#[derive(Serialize)]
+struct LifecycleStageAmount {
+    stage: String,
+    stage_name: String,
+    /// year: annual amount; month: sum of the months through `through_month`
+    period_kind: String,
+    /// Metric the amount comes from; null when no source reported the stage
+    metric_key: Option<String>,
+    amount: Option<Decimal>,
+    fact_count: i64,
+}
+
+#[derive(Serialize)]
+struct ExecutionRateRow {
+    entity_id: Uuid,
+    entity_key: String,
+    display_name: String,
+    entity_type: String,
+    stages: Vec<LifecycleStageAmount>,
+    /// devengado / ley inicial * 100
+    devengado_vs_ley_pct: Option<f64>,
+    /// devengado / vigente * 100
+    devengado_vs_vigente_pct: Option<f64>,
+    /// pagado / devengado * 100
+    pagado_vs_devengado_pct: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct ExecutionRateResponse {
+    year: i32,
+    /// Last month included in monthly stages; null = every reported month
+    through_month: Option<i32>,
+    rows: Vec<ExecutionRateRow>,
 }
 
 #[derive(Serialize)]
@@ -68,7 +166,7 @@ struct FactResponse {
     metric_name: String,
     period_start: NaiveDate,
     period_end: NaiveDate,
-    value_num: f64,
+    value_num: Decimal,
     unit: String,
     dims: serde_json::Value,
 }
@@ -80,13 +178,14 @@ struct CompareRow {
     metric_id: Uuid,
     metric_name: String,
     year_a: i32,
-    value_a: Option<f64>,
+    value_a: Option<Decimal>,
     year_b: i32,
-    value_b: Option<f64>,
-    delta: Option<f64>,
+    value_b: Option<Decimal>,
+    delta: Option<Decimal>,
     pct_change: Option<f64>,
-    fact_id_a: Option<Uuid>,
-    fact_id_b: Option<Uuid>,
+    /// Facts summed into each value (one per line item or month)
+    fact_count_a: i64,
+    fact_count_b: i64,
 }
 
 #[derive(Serialize)]
@@ -116,6 +215,89 @@ struct ArtifactInfo {
     download_path: String,
 }
 
+#[derive(Serialize, sqlx::FromRow)]
+struct SnapshotResponse {
+    snapshot_id: Uuid,
+    artifact_id: Option<Uuid>,
+    parser: Option<String>,
+    status: String,
+    created_at: DateTime<Utc>,
+    published_at: Option<DateTime<Utc>>,
+    superseded_by: Option<Uuid>,
+    note: Option<String>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct SchemaDriftResponse {
+    artifact_id: Uuid,
+    source_id: String,
+    source_family: String,
+    fiscal_year: i32,
+    compared_to: Option<Uuid>,
+    has_drift: bool,
+    drift: Option<serde_json::Value>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct EntityAliasResponse {
+    source_id: String,
+    source_name: String,
+    normalized_name: String,
+    entity_key: String,
+    match_rule: String,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct ReviewQueueEntry {
+    source_id: String,
+    source_name: String,
+    normalized_name: String,
+    local_entity_key: String,
+    rows: i32,
+    status: String,
+    resolved_entity_key: Option<String>,
+    first_seen_at: DateTime<Utc>,
+    last_seen_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct PartidaNameResponse {
+    code: String,
+    name: String,
+    valid_from_year: i32,
+    valid_to_year: Option<i32>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct ClassifierEntryResponse {
+    code: String,
+    level: String,
+    name: String,
+    kind: String,
+    valid_from_year: i32,
+    valid_to_year: Option<i32>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct ClassifierGroup {
+    code: String,
+    name: Option<String>,
+    kind: Option<String>,
+    total: Option<Decimal>,
+    fact_count: i64,
+}
+
+#[derive(Serialize)]
+struct ClassifierBreakdownResponse {
+    metric_id: Uuid,
+    year: i32,
+    entity_id: Option<Uuid>,
+    group_by: String,
+    groups: Vec<ClassifierGroup>,
+}
+
 #[derive(Serialize)]
 struct ErrorResponse {
     error: String,
@@ -125,10 +307,18 @@ struct ErrorResponse {
 #[derive(Serialize)]
 struct DashboardResponse {
     year: i32,
-    total_budget: i64,
+    /// "consolidated" (default) | "gross"; applies to every total and budget below
+    basis: String,
+    total_budget: Decimal,
     total_formatted: String,
+    total_gross: Decimal,
+    /// Intra-Gobierno Central transfers removed from the gross total
+    total_transfers: Decimal,
+    /// Gasto budgeted in dollars (Monto Dolar), reported apart from every CLP figure
+    total_usd: Decimal,
+    netting_rules: Vec<NettingRule>,
     previous_year: Option<i32>,
-    previous_total: Option<i64>,
+    previous_total: Option<Decimal>,
     yoy_change_pct: Option<f64>,
     entities: Vec<DashboardEntity>,
     available_years: Vec<i32>,
@@ -139,11 +329,31 @@ struct DashboardEntity {
     entity_id: Uuid,
     entity_key: String,
     display_name: String,
-    budget: i64,
+    budget: Decimal,
     budget_formatted: String,
+    budget_gross: Decimal,
+    transfers: Decimal,
+    budget_usd: Decimal,
     percentage: f64,
 }
 
+#[derive(sqlx::FromRow)]
+struct DashboardRow {
+    entity_id: Uuid,
+    entity_key: String,
+    display_name: String,
+    gross: Decimal,
+    transfers: Decimal,
+    usd: Decimal,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct NettingRule {
+    rule_id: String,
+    classifier_prefix: String,
+    description: String,
+}
+
 // ============================================================================
 // Query params
 // ============================================================================
@@ -154,26 +364,108 @@ struct EntitiesQuery {
     limit: Option<i64>,
 }
 
+#[derive(Deserialize)]
+struct EntityAliasesQuery {
+    entity_key: Option<String>,
+    source_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ReviewQueueQuery {
+    /// pending (default) | resolved
+    status: Option<String>,
+    source_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EntityQuery {
+    entity_id: Uuid,
+}
+
+#[derive(Deserialize)]
+struct RollupQuery {
+    entity_id: Uuid,
+    metric_id: Uuid,
+    year: i32,
+}
+
+#[derive(Deserialize)]
+struct ExecutionRateQuery {
+    year: i32,
+    /// One entity (with its descendants); default: every entity of `entity_type`
+    entity_key: Option<String>,
+    /// Default: partida
+    entity_type: Option<String>,
+    through_month: Option<i32>,
+}
+
 #[derive(Deserialize)]
 struct FactsQuery {
     metric_id: Option<Uuid>,
     entity_id: Option<Uuid>,
     from: Option<NaiveDate>,
     to: Option<NaiveDate>,
+    snapshot_id: Option<Uuid>,
+    /// Economic classifier filters (dims.subtitulo_code / dims.item_code);
+    /// item is only accepted together with subtitulo
+    subtitulo: Option<String>,
+    item: Option<String>,
     limit: Option<i64>,
 }
 
+#[derive(Deserialize)]
+struct ClassifierQuery {
+    year: i32,
+    level: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PartidasQuery {
+    year: i32,
+}
+
+#[derive(Deserialize)]
+struct ClassifierBreakdownQuery {
+    metric_id: Uuid,
+    year: i32,
+    /// Includes the entity's descendants
+    entity_id: Option<Uuid>,
+    /// "subtitulo" (default) | "item"
+    group_by: Option<String>,
+    subtitulo: Option<String>,
+}
+
 #[derive(Deserialize)]
 struct CompareQuery {
     metric_id: Uuid,
     entity_id: Option<Uuid>,
     year_a: i32,
     year_b: i32,
+    snapshot_a: Option<Uuid>,
+    snapshot_b: Option<Uuid>,
 }
 
 #[derive(Deserialize)]
 struct DashboardQuery {
     year: Option<i32>,
+    snapshot_id: Option<Uuid>,
+    /// "consolidated" (default) | "gross"
+    basis: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SnapshotsQuery {
+    artifact_id: Option<Uuid>,
+    status: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct SchemaDriftQuery {
+    source_family: Option<String>,
+    /// Only artifacts whose layout or codes changed vs the previous year
+    has_drift: Option<bool>,
+    limit: Option<i64>,
 }
 
 #[derive(Deserialize)]
@@ -196,11 +488,25 @@ async fn dashboard_handler(
     State(state): State<Arc<AppState>>,
     Query(params): Query<DashboardQuery>,
 ) -> impl IntoResponse {
+    let basis = params.basis.clone().unwrap_or_else(|| "consolidated".to_string());
+    if basis != "consolidated" && basis != "gross" {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "basis must be 'consolidated' or 'gross'".to_string(),
+            }),
+        )
+            .into_response();
+    }
+    let consolidated = basis == "consolidated";
+
     // Get available years
     let years_result: Result<Vec<(i32,)>, _> = sqlx::query_as(
         r#"
-        SELECT DISTINCT EXTRACT(YEAR FROM period_start)::int as year
-        FROM facts
+        SELECT DISTINCT EXTRACT(YEAR FROM f.period_start)::int as year
+        FROM facts f
+        JOIN snapshots s ON f.snapshot_id = s.snapshot_id
+        WHERE s.status = 'published'
         ORDER BY year DESC
         "#,
     )
@@ -230,27 +536,38 @@ async fn dashboard_handler(
         None
     };
 
-    // Get entities with budget for selected year
-    let entities_result: Result<Vec<_>, _> = sqlx::query(
+    // Get entities with gross budget, intra-Gobierno Central transfers and the
+    // separate USD budget for selected year (currencies are never added together)
+    let entities_result: Result<Vec<DashboardRow>, _> = sqlx::query_as(
         r#"
         SELECT
             e.entity_id,
             e.entity_key,
-            e.display_name,
-            f.value_num as budget
+            COALESCE(pc.name, e.display_name) as display_name,
+            SUM(f.value_num) FILTER (WHERE m.metric_key = 'presupuesto_ley_gasto') as gross,
+            COALESCE(SUM(f.value_num) FILTER (WHERE m.metric_key = 'presupuesto_ley_gasto_transferencias'), 0) as transfers,
+            COALESCE(SUM(f.value_num) FILTER (WHERE m.metric_key = 'presupuesto_ley_gasto_usd'), 0) as usd
         FROM facts f
         JOIN entities e ON f.entity_id = e.entity_id
         JOIN metrics m ON f.metric_id = m.metric_id
-        WHERE m.metric_key = 'presupuesto_ley'
+        JOIN snapshots s ON f.snapshot_id = s.snapshot_id
+        -- Partida name in force for the requested year
+        LEFT JOIN partida_catalog pc
+          ON e.entity_key = 'partida_' || pc.code
+         AND $1 BETWEEN pc.valid_from_year AND COALESCE(pc.valid_to_year, 9999)
+        WHERE m.metric_key IN ('presupuesto_ley_gasto', 'presupuesto_ley_gasto_transferencias', 'presupuesto_ley_gasto_usd')
           AND EXTRACT(YEAR FROM f.period_start) = $1
-        ORDER BY f.value_num DESC
+          AND (($2::uuid IS NULL AND s.status = 'published') OR f.snapshot_id = $2)
+        GROUP BY e.entity_id, e.entity_key, e.display_name, pc.name
+        HAVING SUM(f.value_num) FILTER (WHERE m.metric_key = 'presupuesto_ley_gasto') IS NOT NULL
         "#,
     )
     .bind(year)
+    .bind(params.snapshot_id)
     .fetch_all(&state.pool)
     .await;
 
-    let entities = match entities_result {
+    let mut entities = match entities_result {
         Ok(rows) => rows,
         Err(e) => {
             return (
@@ -263,39 +580,59 @@ async fn dashboard_handler(
         }
     };
 
-    use sqlx::Row;
+    // Sorted by budget on the requested basis
+    let budget_on_basis = |gross: Decimal, transfers: Decimal| {
+        if consolidated {
+            gross - transfers
+        } else {
+            gross
+        }
+    };
+    entities.sort_by_key(|r| std::cmp::Reverse(budget_on_basis(r.gross, r.transfers)));
 
-    // Calculate total (value_num is stored as FLOAT8 in PostgreSQL)
-    let total_budget: i64 = entities
-        .iter()
-        .map(|r| r.get::<f64, _>("budget") as i64)
-        .sum();
+    let total_gross: Decimal = entities.iter().map(|r| r.gross).sum();
+    let total_transfers: Decimal = entities.iter().map(|r| r.transfers).sum();
+    let total_budget = budget_on_basis(total_gross, total_transfers);
+    let total_usd: Decimal = entities.iter().map(|r| r.usd).sum();
 
-    // Get previous year total if available
-    let previous_total: Option<i64> = if let Some(prev_year) = previous_year {
-        let prev_result: Result<Option<(i64,)>, _> = sqlx::query_as(
+    // Get previous year total (same basis) if available
+    let previous_total: Option<Decimal> = if let Some(prev_year) = previous_year {
+        let prev_result: Result<Option<(Option<Decimal>, Decimal)>, _> = sqlx::query_as(
             r#"
-            SELECT SUM(f.value_num)::bigint as total
+            SELECT
+                SUM(f.value_num) FILTER (WHERE m.metric_key = 'presupuesto_ley_gasto') as gross,
+                COALESCE(SUM(f.value_num) FILTER (WHERE m.metric_key = 'presupuesto_ley_gasto_transferencias'), 0) as transfers
             FROM facts f
             JOIN metrics m ON f.metric_id = m.metric_id
-            WHERE m.metric_key = 'presupuesto_ley'
+            JOIN snapshots s ON f.snapshot_id = s.snapshot_id
+            WHERE m.metric_key IN ('presupuesto_ley_gasto', 'presupuesto_ley_gasto_transferencias')
               AND EXTRACT(YEAR FROM f.period_start) = $1
+              AND s.status = 'published'
             "#,
         )
         .bind(prev_year)
         .fetch_optional(&state.pool)
         .await;
 
-        prev_result.ok().flatten().map(|(t,)| t)
+        match prev_result.ok().flatten() {
+            Some((Some(gross), transfers)) => Some(budget_on_basis(gross, transfers)),
+            _ => None,
+        }
     } else {
         None
     };
 
+    // Netting rules used to build the consolidated figures
+    let netting_rules: Vec<NettingRule> = sqlx::query_as(
+        "SELECT rule_id, classifier_prefix, description FROM consolidation_rules ORDER BY rule_id",
+    )
+    .fetch_all(&state.pool)
+    .await
+    .unwrap_or_default();
+
     // Calculate YoY change
-    let yoy_change_pct = match (previous_total, total_budget) {
-        (Some(prev), total) if prev > 0 => {
-            Some(((total - prev) as f64 / prev as f64) * 100.0)
-        }
+    let yoy_change_pct = match previous_total {
+        Some(prev) if prev > Decimal::ZERO => percent_of(total_budget - prev, prev),
         _ => None,
     };
 
@@ -304,21 +641,24 @@ async fn dashboard_handler(
 
     // Build entity list with percentages
     let dashboard_entities: Vec<DashboardEntity> = entities
-        .iter()
+        .into_iter()
         .map(|r| {
-            let budget: i64 = r.get::<f64, _>("budget") as i64;
-            let percentage = if total_budget > 0 {
-                (budget as f64 / total_budget as f64) * 100.0
+            let budget = budget_on_basis(r.gross, r.transfers);
+            let percentage = if total_budget > Decimal::ZERO {
+                percent_of(budget, total_budget).unwrap_or(0.0)
             } else {
                 0.0
             };
 
             DashboardEntity {
-                entity_id: r.get("entity_id"),
-                entity_key: r.get("entity_key"),
-                display_name: r.get("display_name"),
+                entity_id: r.entity_id,
+                entity_key: r.entity_key,
+                display_name: r.display_name,
                 budget,
                 budget_formatted: format_clp(budget),
+                budget_gross: r.gross,
+                transfers: r.transfers,
+                budget_usd: r.usd,
                 percentage,
             }
         })
@@ -326,8 +666,13 @@ async fn dashboard_handler(
 
     Json(DashboardResponse {
         year,
+        basis,
         total_budget,
         total_formatted,
+        total_gross,
+        total_transfers,
+        total_usd,
+        netting_rules,
         previous_year,
         previous_total,
         yoy_change_pct,
@@ -338,16 +683,24 @@ async fn dashboard_handler(
 }
 
 /// Format number as Chilean pesos
-fn format_clp(amount: i64) -> String {
-    let billions = amount as f64 / 1_000_000_000_000.0;
-    if billions >= 1.0 {
-        format!("${:.2} billones", billions)
+fn format_clp(amount: Decimal) -> String {
+    let round = |d: Decimal, dp: u32| d.round_dp_with_strategy(dp, RoundingStrategy::MidpointAwayFromZero);
+    let billions = amount / Decimal::from(1_000_000_000_000i64);
+    if billions >= Decimal::ONE {
+        format!("${:.2} billones", round(billions, 2))
     } else {
-        let millions = amount as f64 / 1_000_000_000.0;
-        format!("${:.1} mil millones", millions)
+        let millions = amount / Decimal::from(1_000_000_000i64);
+        format!("${:.1} mil millones", round(millions, 1))
     }
 }
 
+/// part / whole * 100 as a display percentage (percentages stay f64, amounts never do)
+fn percent_of(part: Decimal, whole: Decimal) -> Option<f64> {
+    (part * Decimal::ONE_HUNDRED)
+        .checked_div(whole)
+        .and_then(|p| p.to_f64())
+}
+
 async fn metrics_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let metrics: Result<Vec<MetricResponse>, _> = sqlx::query_as(
         "SELECT metric_id, metric_key, display_name, unit, description FROM metrics ORDER BY display_name",
@@ -377,7 +730,7 @@ async fn entities_handler(
         let pattern = format!("%{}%", q.to_lowercase());
         sqlx::query_as(
             r#"
-            SELECT entity_id, entity_key, display_name, entity_type
+            SELECT entity_id, entity_key, display_name, entity_type, parent_entity_id, level
             FROM entities
             WHERE LOWER(display_name) LIKE $1 OR LOWER(entity_key) LIKE $1
             ORDER BY display_name
@@ -390,7 +743,7 @@ async fn entities_handler(
         .await
     } else {
         sqlx::query_as(
-            "SELECT entity_id, entity_key, display_name, entity_type FROM entities ORDER BY display_name LIMIT $1",
+            "SELECT entity_id, entity_key, display_name, entity_type, parent_entity_id, level FROM entities ORDER BY display_name LIMIT $1",
         )
         .bind(limit)
         .fetch_all(&state.pool)
@@ -409,10 +762,203 @@ async fn entities_handler(
     }
 }
 
+async fn entity_children_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<EntityQuery>,
+) -> impl IntoResponse {
+    let children: Result<Vec<EntityResponse>, _> = sqlx::query_as(
+        r#"
+        SELECT entity_id, entity_key, display_name, entity_type, parent_entity_id, level
+        FROM entities
+        WHERE parent_entity_id = $1
+        ORDER BY entity_key
+        "#,
+    )
+    .bind(params.entity_id)
+    .fetch_all(&state.pool)
+    .await;
+
+    match children {
+        Ok(e) => {
+            Json(serde_json::json!({ "entity_id": params.entity_id, "children": e })).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+async fn entity_ancestors_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<EntityQuery>,
+) -> impl IntoResponse {
+    // Walk parent links up to the root; returned root first
+    let ancestors: Result<Vec<EntityResponse>, _> = sqlx::query_as(
+        r#"
+        WITH RECURSIVE chain AS (
+            SELECT entity_id, parent_entity_id, 0 AS depth
+            FROM entities
+            WHERE entity_id = $1
+            UNION ALL
+            SELECT e.entity_id, e.parent_entity_id, c.depth + 1
+            FROM entities e
+            JOIN chain c ON e.entity_id = c.parent_entity_id
+        )
+        SELECT e.entity_id, e.entity_key, e.display_name, e.entity_type, e.parent_entity_id, e.level
+        FROM chain c
+        JOIN entities e ON e.entity_id = c.entity_id
+        WHERE c.depth > 0
+        ORDER BY c.depth DESC
+        "#,
+    )
+    .bind(params.entity_id)
+    .fetch_all(&state.pool)
+    .await;
+
+    match ancestors {
+        Ok(e) => {
+            Json(serde_json::json!({ "entity_id": params.entity_id, "ancestors": e })).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+async fn entity_rollup_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RollupQuery>,
+) -> impl IntoResponse {
+    let entity: Result<Option<EntityResponse>, _> = sqlx::query_as(
+        "SELECT entity_id, entity_key, display_name, entity_type, parent_entity_id, level FROM entities WHERE entity_id = $1",
+    )
+    .bind(params.entity_id)
+    .fetch_optional(&state.pool)
+    .await;
+
+    let entity = match entity {
+        Ok(Some(e)) => e,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: "Entity not found".to_string(),
+                }),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    // Facts attached directly to the entity
+    let own_total: Result<(Option<Decimal>,), _> = sqlx::query_as(
+        r#"
+        SELECT SUM(f.value_num)
+        FROM facts f
+        JOIN snapshots s ON f.snapshot_id = s.snapshot_id
+        WHERE f.entity_id = $1 AND f.metric_id = $2
+          AND EXTRACT(YEAR FROM f.period_start) = $3
+          AND s.status = 'published'
+        "#,
+    )
+    .bind(params.entity_id)
+    .bind(params.metric_id)
+    .bind(params.year)
+    .fetch_one(&state.pool)
+    .await;
+
+    // Each child with the facts of its whole subtree
+    let children: Result<Vec<RollupChild>, _> = sqlx::query_as(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT entity_id, entity_id AS branch_id
+            FROM entities
+            WHERE parent_entity_id = $1
+            UNION ALL
+            SELECT e.entity_id, st.branch_id
+            FROM entities e
+            JOIN subtree st ON e.parent_entity_id = st.entity_id
+        ),
+        published AS (
+            SELECT f.entity_id, f.fact_id, f.value_num
+            FROM facts f
+            JOIN snapshots s ON f.snapshot_id = s.snapshot_id
+            WHERE f.metric_id = $2
+              AND EXTRACT(YEAR FROM f.period_start) = $3
+              AND s.status = 'published'
+        )
+        SELECT b.entity_id, b.entity_key, b.display_name, b.entity_type, b.level,
+               SUM(p.value_num) AS total,
+               COUNT(p.fact_id) AS fact_count
+        FROM subtree st
+        JOIN entities b ON b.entity_id = st.branch_id
+        LEFT JOIN published p ON p.entity_id = st.entity_id
+        GROUP BY b.entity_id, b.entity_key, b.display_name, b.entity_type, b.level
+        ORDER BY b.entity_key
+        "#,
+    )
+    .bind(params.entity_id)
+    .bind(params.metric_id)
+    .bind(params.year)
+    .fetch_all(&state.pool)
+    .await;
+
+    match (own_total, children) {
+        (Ok((own_total,)), Ok(children)) => {
+            let total = own_total.unwrap_or(Decimal::ZERO)
+                + children.iter().filter_map(|c| c.total).sum::<Decimal>();
+
+            Json(RollupResponse {
+                entity,
+                metric_id: params.metric_id,
+                year: params.year,
+                own_total,
+                total,
+                children,
+            })
+            .into_response()
+        }
+        (Err(e), _) | (_, Err(e)) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
 async fn facts_handler(
     State(state): State<Arc<AppState>>,
     Query(params): Query<FactsQuery>,
 ) -> impl IntoResponse {
+    // Item codes repeat across subtítulos; an item alone is not a classifier line
+    if params.item.is_some() && params.subtitulo.is_none() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "item requires subtitulo".to_string(),
+            }),
+        )
+            .into_response();
+    }
+
     let limit = params.limit.unwrap_or(100).min(1000);
 
     // Build dynamic query
@@ -424,11 +970,11 @@ async fn facts_handler(
         FROM facts f
         JOIN entities e ON f.entity_id = e.entity_id
         JOIN metrics m ON f.metric_id = m.metric_id
+        JOIN snapshots s ON f.snapshot_id = s.snapshot_id
         WHERE 1=1
         "#,
     );
 
-    let mut bindings: Vec<String> = Vec::new();
     let mut idx = 1;
 
     if params.metric_id.is_some() {
@@ -447,6 +993,20 @@ async fn facts_handler(
         query.push_str(&format!(" AND f.period_end <= ${}", idx));
         idx += 1;
     }
+    if params.subtitulo.is_some() {
+        query.push_str(&format!(" AND f.dims->>'subtitulo_code' = ${}", idx));
+        idx += 1;
+    }
+    if params.item.is_some() {
+        query.push_str(&format!(" AND f.dims->>'item_code' = ${}", idx));
+        idx += 1;
+    }
+    if params.snapshot_id.is_some() {
+        query.push_str(&format!(" AND f.snapshot_id = ${}", idx));
+        idx += 1;
+    } else {
+        query.push_str(" AND s.status = 'published'");
+    }
 
     query.push_str(&format!(" ORDER BY f.period_start DESC LIMIT ${}", idx));
 
@@ -465,6 +1025,15 @@ async fn facts_handler(
     if let Some(to) = params.to {
         q = q.bind(to);
     }
+    if let Some(subtitulo) = &params.subtitulo {
+        q = q.bind(subtitulo);
+    }
+    if let Some(item) = &params.item {
+        q = q.bind(item);
+    }
+    if let Some(sid) = params.snapshot_id {
+        q = q.bind(sid);
+    }
     q = q.bind(limit);
 
     let rows = q.fetch_all(&state.pool).await;
@@ -501,135 +1070,492 @@ async fn facts_handler(
     }
 }
 
-async fn compare_handler(
+async fn classifier_handler(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<CompareQuery>,
+    Query(params): Query<ClassifierQuery>,
 ) -> impl IntoResponse {
-    // Get facts for year A
-    let year_a_start = NaiveDate::from_ymd_opt(params.year_a, 1, 1).unwrap();
-    let year_a_end = NaiveDate::from_ymd_opt(params.year_a, 12, 31).unwrap();
-    let year_b_start = NaiveDate::from_ymd_opt(params.year_b, 1, 1).unwrap();
-    let year_b_end = NaiveDate::from_ymd_opt(params.year_b, 12, 31).unwrap();
-
-    let query = if params.entity_id.is_some() {
+    let entries: Result<Vec<ClassifierEntryResponse>, _> = sqlx::query_as(
         r#"
-        WITH year_a AS (
-            SELECT f.fact_id, f.entity_id, e.display_name as entity_name, f.value_num
-            FROM facts f
-            JOIN entities e ON f.entity_id = e.entity_id
-            WHERE f.metric_id = $1 AND f.entity_id = $5
-              AND f.period_start >= $2 AND f.period_end <= $3
-        ),
-        year_b AS (
-            SELECT f.fact_id, f.entity_id, f.value_num
-            FROM facts f
-            WHERE f.metric_id = $1 AND f.entity_id = $5
-              AND f.period_start >= $4 AND f.period_end <= $6
-        )
-        SELECT
-            COALESCE(a.entity_id, b.entity_id) as entity_id,
-            COALESCE(a.entity_name, e.display_name) as entity_name,
-            a.value_num as value_a,
-            b.value_num as value_b,
-            a.fact_id as fact_id_a,
-            b.fact_id as fact_id_b
-        FROM year_a a
-        FULL OUTER JOIN year_b b ON a.entity_id = b.entity_id
-        LEFT JOIN entities e ON b.entity_id = e.entity_id
-        ORDER BY entity_name
-        "#
-    } else {
+        SELECT code, level, name, kind, valid_from_year, valid_to_year
+        FROM economic_classifier
+        WHERE $1 BETWEEN valid_from_year AND COALESCE(valid_to_year, 9999)
+          AND ($2::text IS NULL OR level = $2)
+        ORDER BY code
+        "#,
+    )
+    .bind(params.year)
+    .bind(params.level)
+    .fetch_all(&state.pool)
+    .await;
+
+    match entries {
+        Ok(e) => Json(serde_json::json!({ "year": params.year, "entries": e })).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+async fn entity_aliases_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<EntityAliasesQuery>,
+) -> impl IntoResponse {
+    let aliases: Result<Vec<EntityAliasResponse>, _> = sqlx::query_as(
         r#"
-        WITH year_a AS (
-            SELECT f.fact_id, f.entity_id, e.display_name as entity_name, f.value_num
+        SELECT source_id, source_name, normalized_name, entity_key, match_rule, updated_at
+        FROM entity_aliases
+        WHERE ($1::text IS NULL OR entity_key = $1)
+          AND ($2::text IS NULL OR source_id = $2)
+        ORDER BY entity_key, source_id, normalized_name
+        "#,
+    )
+    .bind(params.entity_key)
+    .bind(params.source_id)
+    .fetch_all(&state.pool)
+    .await;
+
+    match aliases {
+        Ok(a) => Json(serde_json::json!({ "aliases": a })).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+async fn review_queue_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ReviewQueueQuery>,
+) -> impl IntoResponse {
+    let status = params.status.unwrap_or_else(|| "pending".to_string());
+    if status != "pending" && status != "resolved" {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "status must be 'pending' or 'resolved'".to_string(),
+            }),
+        )
+            .into_response();
+    }
+
+    let entries: Result<Vec<ReviewQueueEntry>, _> = sqlx::query_as(
+        r#"
+        SELECT source_id, source_name, normalized_name, local_entity_key, rows, status,
+               resolved_entity_key, first_seen_at, last_seen_at
+        FROM entity_review_queue
+        WHERE status = $1
+          AND ($2::text IS NULL OR source_id = $2)
+        ORDER BY rows DESC, source_id, normalized_name
+        "#,
+    )
+    .bind(&status)
+    .bind(params.source_id)
+    .fetch_all(&state.pool)
+    .await;
+
+    match entries {
+        Ok(e) => Json(serde_json::json!({ "status": status, "entries": e })).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+async fn partidas_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PartidasQuery>,
+) -> impl IntoResponse {
+    let partidas: Result<Vec<PartidaNameResponse>, _> = sqlx::query_as(
+        r#"
+        SELECT code, name, valid_from_year, valid_to_year
+        FROM partida_catalog
+        WHERE $1 BETWEEN valid_from_year AND COALESCE(valid_to_year, 9999)
+        ORDER BY code
+        "#,
+    )
+    .bind(params.year)
+    .fetch_all(&state.pool)
+    .await;
+
+    match partidas {
+        Ok(p) => Json(serde_json::json!({ "year": params.year, "partidas": p })).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// Lifecycle stages side by side per entity. Each stage is its own metric and
+/// is never summed with another; within a stage the first metric (by priority)
+/// with published facts under the entity is used, so the Ley is not counted
+/// both as a Partida total and as programa lines.
+async fn execution_rate_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ExecutionRateQuery>,
+) -> impl IntoResponse {
+    let bad_request = |error: &str| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: error.to_string(),
+            }),
+        )
+            .into_response()
+    };
+    if params.through_month.is_some_and(|m| !(1..=12).contains(&m)) {
+        return bad_request("through_month must be between 1 and 12");
+    }
+    let entity_type = params.entity_type.unwrap_or_else(|| "partida".to_string());
+
+    let lifecycle: Result<Vec<LifecycleMetric>, _> = sqlx::query_as(
+        r#"
+        SELECT metric_key, stage, stage_name, period_kind
+        FROM budget_lifecycle
+        ORDER BY stage_order, metric_priority
+        "#,
+    )
+    .fetch_all(&state.pool)
+    .await;
+
+    let entities: Result<Vec<EntityResponse>, _> = sqlx::query_as(
+        r#"
+        SELECT entity_id, entity_key, display_name, entity_type, parent_entity_id, level
+        FROM entities
+        WHERE ($1::text IS NOT NULL AND entity_key = $1)
+           OR ($1::text IS NULL AND entity_type = $2)
+        ORDER BY entity_key
+        "#,
+    )
+    .bind(&params.entity_key)
+    .bind(&entity_type)
+    .fetch_all(&state.pool)
+    .await;
+
+    // Subtree totals of every lifecycle metric, per requested entity
+    let totals: Result<Vec<LifecycleTotal>, _> = sqlx::query_as(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT entity_id, entity_id AS root_id
+            FROM entities
+            WHERE ($1::text IS NOT NULL AND entity_key = $1)
+               OR ($1::text IS NULL AND entity_type = $2)
+            UNION ALL
+            SELECT e.entity_id, st.root_id
+            FROM entities e
+            JOIN subtree st ON e.parent_entity_id = st.entity_id
+        )
+        SELECT st.root_id, bl.metric_key,
+               SUM(f.value_num) AS total,
+               COUNT(f.fact_id) AS fact_count
+        FROM subtree st
+        JOIN facts f ON f.entity_id = st.entity_id
+        JOIN snapshots s ON f.snapshot_id = s.snapshot_id
+        JOIN metrics m ON f.metric_id = m.metric_id
+        JOIN budget_lifecycle bl ON bl.metric_key = m.metric_key
+        WHERE s.status = 'published'
+          AND EXTRACT(YEAR FROM f.period_start) = $3
+          AND (bl.period_kind = 'year' OR $4::int IS NULL OR EXTRACT(MONTH FROM f.period_end) <= $4)
+        GROUP BY st.root_id, bl.metric_key
+        "#,
+    )
+    .bind(&params.entity_key)
+    .bind(&entity_type)
+    .bind(params.year)
+    .bind(params.through_month)
+    .fetch_all(&state.pool)
+    .await;
+
+    let (lifecycle, entities, totals) = match (lifecycle, entities, totals) {
+        (Ok(l), Ok(e), Ok(t)) => (l, e, t),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+                .into_response();
+        }
+    };
+    if entities.is_empty() && params.entity_key.is_some() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: "Entity not found".to_string(),
+            }),
+        )
+            .into_response();
+    }
+
+    let rows: Vec<ExecutionRateRow> = entities
+        .into_iter()
+        .map(|entity| {
+            let mut stages: Vec<LifecycleStageAmount> = Vec::new();
+            for metric in &lifecycle {
+                let total = totals
+                    .iter()
+                    .find(|t| t.root_id == entity.entity_id && t.metric_key == metric.metric_key)
+                    .filter(|t| t.fact_count > 0);
+                match stages.iter_mut().find(|s| s.stage == metric.stage) {
+                    // Lower-priority metric of a stage already measured: ignored
+                    Some(stage) if stage.metric_key.is_some() => {}
+                    Some(stage) => {
+                        if let Some(t) = total {
+                            stage.metric_key = Some(t.metric_key.clone());
+                            stage.amount = t.total;
+                            stage.fact_count = t.fact_count;
+                        }
+                    }
+                    None => stages.push(LifecycleStageAmount {
+                        stage: metric.stage.clone(),
+                        stage_name: metric.stage_name.clone(),
+                        period_kind: metric.period_kind.clone(),
+                        metric_key: total.map(|t| t.metric_key.clone()),
+                        amount: total.and_then(|t| t.total),
+                        fact_count: total.map_or(0, |t| t.fact_count),
+                    }),
+                }
+            }
+
+            let amount = |stage: &str| {
+                stages
+                    .iter()
+                    .find(|s| s.stage == stage)
+                    .and_then(|s| s.amount)
+            };
+            let rate = |part: &str, whole: &str| match (amount(part), amount(whole)) {
+                (Some(p), Some(w)) if !w.is_zero() => percent_of(p, w),
+                _ => None,
+            };
+            let devengado_vs_ley_pct = rate("devengado", "ley");
+            let devengado_vs_vigente_pct = rate("devengado", "vigente");
+            let pagado_vs_devengado_pct = rate("pagado", "devengado");
+
+            ExecutionRateRow {
+                entity_id: entity.entity_id,
+                entity_key: entity.entity_key,
+                display_name: entity.display_name,
+                entity_type: entity.entity_type,
+                stages,
+                devengado_vs_ley_pct,
+                devengado_vs_vigente_pct,
+                pagado_vs_devengado_pct,
+            }
+        })
+        .collect();
+
+    Json(ExecutionRateResponse {
+        year: params.year,
+        through_month: params.through_month,
+        rows,
+    })
+    .into_response()
+}
+
+async fn classifier_breakdown_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ClassifierBreakdownQuery>,
+) -> impl IntoResponse {
+    let group_by = params.group_by.unwrap_or_else(|| "subtitulo".to_string());
+    if group_by != "subtitulo" && group_by != "item" {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "group_by must be 'subtitulo' or 'item'".to_string(),
+            }),
+        )
+            .into_response();
+    }
+
+    let groups: Result<Vec<ClassifierGroup>, _> = sqlx::query_as(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT entity_id FROM entities WHERE entity_id = $3
+            UNION ALL
+            SELECT e.entity_id
+            FROM entities e
+            JOIN subtree st ON e.parent_entity_id = st.entity_id
+        ),
+        tagged AS (
+            SELECT f.fact_id, f.value_num,
+                   CASE
+                       WHEN $4 = 'item' AND f.dims->>'item_code' IS NOT NULL
+                           THEN (f.dims->>'subtitulo_code') || '.' || (f.dims->>'item_code')
+                       ELSE f.dims->>'subtitulo_code'
+                   END AS code
             FROM facts f
-            JOIN entities e ON f.entity_id = e.entity_id
+            JOIN snapshots s ON f.snapshot_id = s.snapshot_id
             WHERE f.metric_id = $1
+              AND EXTRACT(YEAR FROM f.period_start) = $2
+              AND s.status = 'published'
+              AND f.dims->>'subtitulo_code' IS NOT NULL
+              AND ($3::uuid IS NULL OR f.entity_id IN (SELECT entity_id FROM subtree))
+              AND ($5::text IS NULL OR f.dims->>'subtitulo_code' = $5)
+        )
+        SELECT t.code, c.name, c.kind, SUM(t.value_num) AS total, COUNT(t.fact_id) AS fact_count
+        FROM tagged t
+        LEFT JOIN economic_classifier c
+          ON c.code = t.code
+         AND $2 BETWEEN c.valid_from_year AND COALESCE(c.valid_to_year, 9999)
+        GROUP BY t.code, c.name, c.kind
+        ORDER BY t.code
+        "#,
+    )
+    .bind(params.metric_id)
+    .bind(params.year)
+    .bind(params.entity_id)
+    .bind(&group_by)
+    .bind(params.subtitulo)
+    .fetch_all(&state.pool)
+    .await;
+
+    match groups {
+        Ok(groups) => Json(ClassifierBreakdownResponse {
+            metric_id: params.metric_id,
+            year: params.year,
+            entity_id: params.entity_id,
+            group_by,
+            groups,
+        })
+        .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// Per-entity totals of both years, as summed by the database
+#[derive(sqlx::FromRow)]
+struct CompareTotals {
+    entity_id: Uuid,
+    entity_name: String,
+    value_a: Option<Decimal>,
+    value_b: Option<Decimal>,
+    fact_count_a: i64,
+    fact_count_b: i64,
+}
+
+/// Total of a metric per entity in each year, side by side
+/// Each side is summed before the join: line-item and monthly metrics have
+/// many facts per entity and year, and pairing them fact by fact is meaningless
+async fn compare_rows(pool: &PgPool, params: &CompareQuery) -> Result<Vec<CompareRow>, sqlx::Error> {
+    let year_a_start = NaiveDate::from_ymd_opt(params.year_a, 1, 1).unwrap();
+    let year_a_end = NaiveDate::from_ymd_opt(params.year_a, 12, 31).unwrap();
+    let year_b_start = NaiveDate::from_ymd_opt(params.year_b, 1, 1).unwrap();
+    let year_b_end = NaiveDate::from_ymd_opt(params.year_b, 12, 31).unwrap();
+
+    let rows: Vec<CompareTotals> = sqlx::query_as(
+        r#"
+        WITH year_a AS (
+            SELECT f.entity_id, SUM(f.value_num) AS total, COUNT(*) AS fact_count
+            FROM facts f
+            JOIN snapshots s ON f.snapshot_id = s.snapshot_id
+            WHERE f.metric_id = $1 AND ($4::uuid IS NULL OR f.entity_id = $4)
               AND f.period_start >= $2 AND f.period_end <= $3
+              AND (($5::uuid IS NULL AND s.status = 'published') OR f.snapshot_id = $5)
+            GROUP BY f.entity_id
         ),
         year_b AS (
-            SELECT f.fact_id, f.entity_id, f.value_num
+            SELECT f.entity_id, SUM(f.value_num) AS total, COUNT(*) AS fact_count
             FROM facts f
-            WHERE f.metric_id = $1
-              AND f.period_start >= $4 AND f.period_end <= $5
+            JOIN snapshots s ON f.snapshot_id = s.snapshot_id
+            WHERE f.metric_id = $1 AND ($4::uuid IS NULL OR f.entity_id = $4)
+              AND f.period_start >= $6 AND f.period_end <= $7
+              AND (($8::uuid IS NULL AND s.status = 'published') OR f.snapshot_id = $8)
+            GROUP BY f.entity_id
         )
         SELECT
-            COALESCE(a.entity_id, b.entity_id) as entity_id,
-            COALESCE(a.entity_name, e.display_name) as entity_name,
-            a.value_num as value_a,
-            b.value_num as value_b,
-            a.fact_id as fact_id_a,
-            b.fact_id as fact_id_b
+            e.entity_id,
+            e.display_name AS entity_name,
+            a.total AS value_a,
+            b.total AS value_b,
+            COALESCE(a.fact_count, 0) AS fact_count_a,
+            COALESCE(b.fact_count, 0) AS fact_count_b
         FROM year_a a
         FULL OUTER JOIN year_b b ON a.entity_id = b.entity_id
-        LEFT JOIN entities e ON b.entity_id = e.entity_id
-        ORDER BY entity_name
-        "#
-    };
-
-    let rows = if let Some(eid) = params.entity_id {
-        sqlx::query(query)
-            .bind(params.metric_id)
-            .bind(year_a_start)
-            .bind(year_a_end)
-            .bind(year_b_start)
-            .bind(eid)
-            .bind(year_b_end)
-            .fetch_all(&state.pool)
-            .await
-    } else {
-        sqlx::query(query)
-            .bind(params.metric_id)
-            .bind(year_a_start)
-            .bind(year_a_end)
-            .bind(year_b_start)
-            .bind(year_b_end)
-            .fetch_all(&state.pool)
-            .await
-    };
-
-    match rows {
-        Ok(rows) => {
-            use sqlx::Row;
-            let compare_rows: Vec<CompareRow> = rows
-                .iter()
-                .map(|row| {
-                    let value_a: Option<f64> = row.get("value_a");
-                    let value_b: Option<f64> = row.get("value_b");
-                    let delta = match (value_a, value_b) {
-                        (Some(a), Some(b)) => Some(b - a),
-                        _ => None,
-                    };
-                    let pct_change = match (value_a, value_b) {
-                        (Some(a), Some(b)) if a != 0.0 => Some(((b - a) / a) * 100.0),
-                        _ => None,
-                    };
-
-                    CompareRow {
-                        entity_id: row.get("entity_id"),
-                        entity_name: row.get("entity_name"),
-                        metric_id: params.metric_id,
-                        metric_name: String::new(), // Will be filled by frontend
-                        year_a: params.year_a,
-                        value_a,
-                        year_b: params.year_b,
-                        value_b,
-                        delta,
-                        pct_change,
-                        fact_id_a: row.get("fact_id_a"),
-                        fact_id_b: row.get("fact_id_b"),
-                    }
-                })
-                .collect();
-
-            Json(CompareResponse {
+        JOIN entities e ON e.entity_id = COALESCE(a.entity_id, b.entity_id)
+        ORDER BY e.display_name
+        "#,
+    )
+    .bind(params.metric_id)
+    .bind(year_a_start)
+    .bind(year_a_end)
+    .bind(params.entity_id)
+    .bind(params.snapshot_a)
+    .bind(year_b_start)
+    .bind(year_b_end)
+    .bind(params.snapshot_b)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|totals| {
+            let CompareTotals {
+                entity_id,
+                entity_name,
+                value_a,
+                value_b,
+                fact_count_a,
+                fact_count_b,
+            } = totals;
+            let delta = match (value_a, value_b) {
+                (Some(a), Some(b)) => Some(b - a),
+                _ => None,
+            };
+            let pct_change = match (value_a, value_b) {
+                (Some(a), Some(b)) if !a.is_zero() => percent_of(b - a, a),
+                _ => None,
+            };
+            CompareRow {
+                entity_id,
+                entity_name,
+                metric_id: params.metric_id,
+                metric_name: String::new(), // Will be filled by frontend
                 year_a: params.year_a,
+                value_a,
                 year_b: params.year_b,
-                metric_id: params.metric_id,
-                rows: compare_rows,
-            })
-            .into_response()
-        }
+                value_b,
+                delta,
+                pct_change,
+                fact_count_a,
+                fact_count_b,
+            }
+        })
+        .collect())
+}
+
+async fn compare_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<CompareQuery>,
+) -> impl IntoResponse {
+    match compare_rows(&state.pool, &params).await {
+        Ok(compare_rows) => Json(CompareResponse {
+            year_a: params.year_a,
+            year_b: params.year_b,
+            metric_id: params.metric_id,
+            rows: compare_rows,
+        })
+        .into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -655,8 +1581,7 @@ async fn evidence_handler(
             a.captured_at,
             a.content_hash,
             a.mime_type,
-            a.size_bytes,
-            a.storage_path
+            a.size_bytes
         FROM provenance p
         JOIN artifacts a ON p.artifact_id = a.artifact_id
         WHERE p.fact_id = $1
@@ -669,7 +1594,6 @@ async fn evidence_handler(
     match result {
         Ok(Some(row)) => {
             use sqlx::Row;
-            let storage_path: String = row.get("storage_path");
             let artifact_id: Uuid = row.get("artifact_id");
 
             Json(EvidenceResponse {
@@ -705,6 +1629,74 @@ async fn evidence_handler(
     }
 }
 
+async fn snapshots_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SnapshotsQuery>,
+) -> impl IntoResponse {
+    let limit = params.limit.unwrap_or(100).min(1000);
+
+    let snapshots: Result<Vec<SnapshotResponse>, _> = sqlx::query_as(
+        r#"
+        SELECT snapshot_id, artifact_id, parser, status, created_at, published_at, superseded_by, note
+        FROM snapshots
+        WHERE ($1::uuid IS NULL OR artifact_id = $1)
+          AND ($2::text IS NULL OR status = $2)
+        ORDER BY created_at DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(params.artifact_id)
+    .bind(params.status)
+    .bind(limit)
+    .fetch_all(&state.pool)
+    .await;
+
+    match snapshots {
+        Ok(s) => Json(serde_json::json!({ "snapshots": s })).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+async fn schema_drift_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SchemaDriftQuery>,
+) -> impl IntoResponse {
+    let limit = params.limit.unwrap_or(100).min(1000);
+
+    let rows: Result<Vec<SchemaDriftResponse>, _> = sqlx::query_as(
+        r#"
+        SELECT artifact_id, source_id, source_family, fiscal_year, compared_to, has_drift, drift, created_at
+        FROM schema_fingerprints
+        WHERE ($1::text IS NULL OR source_family = $1)
+          AND ($2::bool IS NULL OR has_drift = $2)
+        ORDER BY source_family, fiscal_year DESC, created_at DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(params.source_family)
+    .bind(params.has_drift)
+    .bind(limit)
+    .fetch_all(&state.pool)
+    .await;
+
+    match rows {
+        Ok(r) => Json(serde_json::json!({ "fingerprints": r })).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
 // ============================================================================
 // Main
 // ============================================================================
@@ -740,23 +1732,123 @@ async fn main() -> anyhow::Result<()> {
         .route("/dashboard", get(dashboard_handler))
         .route("/metrics", get(metrics_handler))
         .route("/entities", get(entities_handler))
+        .route("/entities/children", get(entity_children_handler))
+        .route("/entities/ancestors", get(entity_ancestors_handler))
+        .route("/entities/rollup", get(entity_rollup_handler))
+        .route("/entities/aliases", get(entity_aliases_handler))
+        .route("/entities/review-queue", get(review_queue_handler))
         .route("/facts", get(facts_handler))
+        .route("/facts/by-classifier", get(classifier_breakdown_handler))
+        .route("/classifier", get(classifier_handler))
+        .route("/partidas", get(partidas_handler))
         .route("/compare", get(compare_handler))
         .route("/evidence", get(evidence_handler))
+        .route("/snapshots", get(snapshots_handler))
+        .route("/schema-drift", get(schema_drift_handler))
+        .route("/execution-rate", get(execution_rate_handler))
         .layer(cors)
         .with_state(state);
 
     println!("API listening on http://{}", bind);
     println!("\nEndpoints:");
     println!("  GET /health");
+    println!("  GET /dashboard?year=&snapshot_id=&basis=consolidated|gross");
     println!("  GET /metrics");
     println!("  GET /entities?query=&limit=");
-    println!("  GET /facts?metric_id=&entity_id=&from=&to=&limit=");
-    println!("  GET /compare?metric_id=&year_a=&year_b=&entity_id=");
+    println!("  GET /entities/children?entity_id=");
+    println!("  GET /entities/ancestors?entity_id=");
+    println!("  GET /entities/rollup?entity_id=&metric_id=&year=");
+    println!("  GET /entities/aliases?entity_key=&source_id=");
+    println!("  GET /entities/review-queue?status=pending|resolved&source_id=");
+    println!("  GET /facts?metric_id=&entity_id=&from=&to=&snapshot_id=&subtitulo=&item=&limit=");
+    println!("  GET /facts/by-classifier?metric_id=&year=&entity_id=&group_by=&subtitulo=");
+    println!("  GET /classifier?year=&level=");
+    println!("  GET /partidas?year=");
+    println!("  GET /compare?metric_id=&year_a=&year_b=&entity_id=&snapshot_a=&snapshot_b=");
     println!("  GET /evidence?fact_id=");
+    println!("  GET /snapshots?artifact_id=&status=&limit=");
+    println!("  GET /schema-drift?source_family=&has_drift=&limit=");
+    println!("  GET /execution-rate?year=&entity_key=&entity_type=&through_month=");
 
     let listener = tokio::net::TcpListener::bind(&bind).await?;
     axum::serve(listener, app).await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pool for tests that need the database; None (test skipped) without DB_URL
+    async fn test_pool() -> Option<PgPool> {
+        let db_url = std::env::var("DB_URL").ok()?;
+        Some(PgPoolOptions::new().max_connections(1).connect(&db_url).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_compare_sums_line_items_per_entity() {
+        let Some(pool) = test_pool().await else {
+            eprintln!("DB_URL not set, skipping");
+            return;
+        };
+        let tag = Uuid::new_v4().simple().to_string();
+        let metric_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO metrics (metric_key, display_name, unit) VALUES ($1, 'Test', 'CLP') RETURNING metric_id",
+        )
+        .bind(format!("test_compare_{}", tag))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let entity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO entities (entity_key, display_name, entity_type) VALUES ($1, 'Programa de prueba', 'programa') RETURNING entity_id",
+        )
+        .bind(format!("test_compare_{}", tag))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let snapshot_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO snapshots (status, note) VALUES ('published', 'compare test') RETURNING snapshot_id",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        // Line items: three in 2025, two in 2026, all under one programa
+        for (year, amount) in [(2025, 100), (2025, 200), (2025, 300), (2026, 250), (2026, 450)] {
+            let day = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+            sqlx::query(
+                "INSERT INTO facts (snapshot_id, entity_id, metric_id, period_start, period_end, value_num, unit) VALUES ($1, $2, $3, $4, $4, $5, 'CLP')",
+            )
+            .bind(snapshot_id)
+            .bind(entity_id)
+            .bind(metric_id)
+            .bind(day)
+            .bind(Decimal::from(amount))
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let params = CompareQuery {
+            metric_id,
+            entity_id: None,
+            year_a: 2025,
+            year_b: 2026,
+            snapshot_a: None,
+            snapshot_b: None,
+        };
+        let rows = compare_rows(&pool, &params).await;
+
+        sqlx::query("DELETE FROM snapshots WHERE snapshot_id = $1").bind(snapshot_id).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM entities WHERE entity_id = $1").bind(entity_id).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM metrics WHERE metric_id = $1").bind(metric_id).execute(&pool).await.unwrap();
+
+        let rows = rows.unwrap();
+        assert_eq!(rows.len(), 1, "one row per entity, not one per pair of facts");
+        assert_eq!(rows[0].value_a, Some(Decimal::from(600)));
+        assert_eq!(rows[0].value_b, Some(Decimal::from(700)));
+        assert_eq!(rows[0].delta, Some(Decimal::from(100)));
+        assert_eq!((rows[0].fact_count_a, rows[0].fact_count_b), (3, 2));
+    }
+}
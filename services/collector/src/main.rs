@@ -25,7 +25,6 @@ use serde::Deserialize;
 use sha2::{Digest, Sha256};
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
-use std::collections::HashMap;
 use std::path::PathBuf;
 use std::time::Duration;
 use tokio::fs;
@@ -68,16 +67,12 @@ struct Args {
 struct SourcesConfig {
     version: String,
     sources: Vec<Source>,
-    #[serde(default)]
-    parsers: HashMap<String, ParserConfig>,
 }
 
 #[derive(Debug, Deserialize)]
 struct Source {
     id: String,
     name: String,
-    #[serde(default)]
-    description: String,
     provider: String,
     #[serde(default)]
     category: String,
@@ -85,11 +80,7 @@ struct Source {
     #[serde(default)]
     urls: Vec<SourceUrl>,
     #[serde(default)]
-    api_url: Option<String>,
-    #[serde(default)]
     requires_api_key: bool,
-    #[serde(default)]
-    parser: String,
     #[serde(default = "default_true")]
     enabled: bool,
 }
@@ -102,32 +93,11 @@ fn default_true() -> bool {
 struct SourceUrl {
     #[serde(default)]
     year: Option<i32>,
-    #[serde(default)]
-    month: Option<i32>,
-    #[serde(default)]
-    quarter: Option<i32>,
     url: String,
     #[serde(default)]
     description: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct ParserConfig {
-    #[serde(rename = "type")]
-    parser_type: String,
-    #[serde(default)]
-    columns: HashMap<String, Vec<String>>,
-    #[serde(default)]
-    metric: Option<MetricConfig>,
-}
-
-#[derive(Debug, Deserialize)]
-struct MetricConfig {
-    key: String,
-    name: String,
-    unit: String,
-}
-
 #[derive(Debug)]
 struct ArtifactMeta {
     artifact_id: Uuid,
@@ -0,0 +1,145 @@
+//! Economic classifier catalog (subtítulo / ítem / asignación)
+//!
+//! The catalog is the seed file config/clasificador_economico.json, embedded
+//! at compile time so the parser output never depends on database state
+//! (PRINCIPLES.md #1). The same entries are synced into the
+//! `economic_classifier` table so the API can resolve names per year.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// Subtítulo / ítem codes of the economic classifier and the years each is in force
+const CATALOG_JSON: &str = include_str!("../../../config/clasificador_economico.json");
+
+/// One classifier entry as declared in the seed file
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ClassifierEntry {
+    /// Dotted code: "21", "21.01" or "21.01.001"
+    pub code: String,
+    pub name: String,
+    /// "ingreso" | "gasto"
+    pub kind: String,
+    pub valid_from: i32,
+    pub valid_to: Option<i32>,
+}
+
+impl ClassifierEntry {
+    /// "subtitulo" | "item" | "asignacion", from the number of code segments
+    pub fn level(&self) -> &'static str {
+        match self.code.split('.').count() {
+            1 => "subtitulo",
+            2 => "item",
+            _ => "asignacion",
+        }
+    }
+
+    fn is_valid_in(&self, year: i32) -> bool {
+        year >= self.valid_from && self.valid_to.is_none_or(|to| year <= to)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CatalogFile {
+    version: String,
+    entries: Vec<ClassifierEntry>,
+}
+
+/// The economic classifier catalog
+#[derive(Debug)]
+pub struct ClassifierCatalog {
+    pub version: String,
+    pub entries: Vec<ClassifierEntry>,
+}
+
+impl ClassifierCatalog {
+    /// Load the catalog embedded in the binary
+    pub fn embedded() -> Result<Self> {
+        Self::from_json(CATALOG_JSON)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: CatalogFile =
+            serde_json::from_str(json).context("Failed to parse economic classifier catalog")?;
+        Ok(Self {
+            version: file.version,
+            entries: file.entries,
+        })
+    }
+
+    /// Find the entry for a dotted code in force during `year`
+    pub fn lookup(&self, year: i32, code: &str) -> Option<&ClassifierEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.code == code && e.is_valid_in(year))
+    }
+}
+
+/// Normalize a classifier segment: empty or all-zero codes mean "not present"
+/// (DIPRES writes "00" / "000" on rows that stop at a higher level)
+pub fn segment(code: &str) -> Option<&str> {
+    let code = code.trim();
+    if code.is_empty() || code.chars().all(|c| c == '0') {
+        None
+    } else {
+        Some(code)
+    }
+}
+
+/// Build the dotted code of the most specific level present in a row
+/// e.g. ("24", "01", "001") -> "24.01.001", ("21", "00", "000") -> "21"
+pub fn dotted_code(subtitulo: &str, item: &str, asignacion: &str) -> Option<String> {
+    let subtitulo = segment(subtitulo)?;
+    let mut code = format!("{:0>2}", subtitulo);
+    if let Some(item) = segment(item) {
+        code.push_str(&format!(".{:0>2}", item));
+        if let Some(asignacion) = segment(asignacion) {
+            code.push_str(&format!(".{:0>3}", asignacion));
+        }
+    }
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_catalog_loads() {
+        let catalog = ClassifierCatalog::embedded().unwrap();
+        assert!(!catalog.entries.is_empty());
+        assert_eq!(catalog.lookup(2026, "21").unwrap().name, "Gastos en Personal");
+        assert_eq!(catalog.lookup(2026, "22.11").unwrap().level(), "item");
+    }
+
+    #[test]
+    fn test_embedded_catalog_codes_unique_per_period() {
+        let catalog = ClassifierCatalog::embedded().unwrap();
+        let mut seen = std::collections::BTreeSet::new();
+        for entry in &catalog.entries {
+            assert!(seen.insert((entry.code.clone(), entry.valid_from)), "duplicate {}", entry.code);
+            assert!(entry.kind == "ingreso" || entry.kind == "gasto");
+        }
+    }
+
+    #[test]
+    fn test_lookup_respects_validity() {
+        let catalog = ClassifierCatalog::from_json(
+            r#"{"version": "t", "entries": [
+                {"code": "21", "name": "Viejo", "kind": "gasto", "valid_from": 2000, "valid_to": 2019},
+                {"code": "21", "name": "Nuevo", "kind": "gasto", "valid_from": 2020, "valid_to": null}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(catalog.lookup(2019, "21").unwrap().name, "Viejo");
+        assert_eq!(catalog.lookup(2020, "21").unwrap().name, "Nuevo");
+        assert!(catalog.lookup(1999, "21").is_none());
+    }
+
+    #[test]
+    fn test_dotted_code() {
+        assert_eq!(dotted_code("24", "01", "001").as_deref(), Some("24.01.001"));
+        assert_eq!(dotted_code("21", "00", "000").as_deref(), Some("21"));
+        assert_eq!(dotted_code("22", "4", "").as_deref(), Some("22.04"));
+        assert_eq!(dotted_code("", "", ""), None);
+    }
+}
@@ -0,0 +1,88 @@
+//! Consolidation rules for Gobierno Central budget totals
+//!
+//! Transfers between Gobierno Central institutions appear twice in the Ley de
+//! Presupuestos: once as a transfer in the paying Partida and again as spending
+//! in the receiving one. The rules in config/consolidacion.json tag those
+//! transfer lines so the API can net them out of the gross total.
+//! Embedded at compile time, like the economic classifier.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// Classifier prefixes of transfers within the Gobierno Central, eliminated on consolidation
+const RULES_JSON: &str = include_str!("../../../config/consolidacion.json");
+
+/// One netting rule
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ConsolidationRule {
+    pub id: String,
+    /// Dotted classifier code; matches itself and every code below it
+    pub classifier_prefix: String,
+    pub description: String,
+}
+
+impl ConsolidationRule {
+    fn matches(&self, classifier_code: &str) -> bool {
+        classifier_code == self.classifier_prefix
+            || classifier_code
+                .strip_prefix(self.classifier_prefix.as_str())
+                .is_some_and(|rest| rest.starts_with('.'))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    version: String,
+    rules: Vec<ConsolidationRule>,
+}
+
+/// The set of netting rules applied to gasto lines
+#[derive(Debug)]
+pub struct ConsolidationRules {
+    pub version: String,
+    pub rules: Vec<ConsolidationRule>,
+}
+
+impl ConsolidationRules {
+    /// Load the rules embedded in the binary
+    pub fn embedded() -> Result<Self> {
+        Self::from_json(RULES_JSON)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: RulesFile =
+            serde_json::from_str(json).context("Failed to parse consolidation rules")?;
+        Ok(Self {
+            version: file.version,
+            rules: file.rules,
+        })
+    }
+
+    /// First rule that tags a gasto line with this classifier code as an
+    /// intra-Gobierno Central transfer
+    pub fn matching(&self, classifier_code: &str) -> Option<&ConsolidationRule> {
+        self.rules.iter().find(|r| r.matches(classifier_code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_rules_load() {
+        let rules = ConsolidationRules::embedded().unwrap();
+        assert!(!rules.rules.is_empty());
+    }
+
+    #[test]
+    fn test_prefix_matches_by_segment() {
+        let rules = ConsolidationRules::embedded().unwrap();
+        assert!(rules.matching("24.02").is_some());
+        assert!(rules.matching("24.02.001").is_some());
+        assert!(rules.matching("33.02.010").is_some());
+        assert!(rules.matching("24").is_none());
+        assert!(rules.matching("24.01.001").is_none());
+        assert!(rules.matching("24.021").is_none());
+    }
+}
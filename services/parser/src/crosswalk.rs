@@ -0,0 +1,276 @@
+//! Entity crosswalk: source names → canonical entities
+//!
+//! Each source names institutions its own way ("Ministerio de Educación",
+//! "MINEDUC", "09"). Canonical entities are the Partidas of the institutional
+//! classifier (`partida_NN`). A source name is matched by deterministic rules,
+//! in order:
+//!
+//! 1. `code`: the name is a Partida code ("09", "09 - Educación")
+//! 2. `alias`: the name is listed in config/entity_aliases.json (source-specific first)
+//! 3. `official_name`: the name is the official Partida name in force that year
+//!
+//! Names are compared in `normalize::fold` form. Names that match no rule
+//! keep their source-local key and go to the review queue; resolving one
+//! means adding an alias to the seed file, so parser output never depends on
+//! database state (PRINCIPLES.md #1).
+
+use crate::normalize;
+use crate::partidas::PartidaCatalog;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Known spellings of each Partida, per source or for all sources
+const ALIASES_JSON: &str = include_str!("../../../config/entity_aliases.json");
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct EntityAlias {
+    pub alias: String,
+    /// Two-digit Partida code the alias stands for
+    pub partida: String,
+    /// Source id the alias applies to; None = every source
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AliasesFile {
+    version: String,
+    aliases: Vec<EntityAlias>,
+}
+
+/// Rule that matched a source name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchRule {
+    Code,
+    Alias,
+    OfficialName,
+}
+
+impl MatchRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchRule::Code => "code",
+            MatchRule::Alias => "alias",
+            MatchRule::OfficialName => "official_name",
+        }
+    }
+}
+
+/// Canonical entity for a source name
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityMatch {
+    pub entity_key: String,
+    pub entity_name: String,
+    pub entity_type: &'static str,
+    pub rule: MatchRule,
+}
+
+#[derive(Debug)]
+pub struct EntityCrosswalk {
+    pub version: String,
+    aliases: Vec<EntityAlias>,
+    partidas: PartidaCatalog,
+}
+
+impl EntityCrosswalk {
+    /// Load the aliases and Partida catalog embedded in the binary
+    pub fn embedded() -> Result<Self> {
+        Self::from_json(ALIASES_JSON, PartidaCatalog::embedded()?)
+    }
+
+    pub fn from_json(json: &str, partidas: PartidaCatalog) -> Result<Self> {
+        let file: AliasesFile =
+            serde_json::from_str(json).context("Failed to parse entity aliases")?;
+        Ok(Self {
+            version: file.version,
+            aliases: file.aliases,
+            partidas,
+        })
+    }
+
+    /// Resolve a source name for a fiscal year; None means unmatched
+    pub fn resolve(&self, source_id: &str, year: i32, name: &str) -> Option<EntityMatch> {
+        let (code, rule) = if let Some(code) = partida_code_prefix(name) {
+            (code, MatchRule::Code)
+        } else if let Some(code) = self.alias_for(source_id, name) {
+            (code, MatchRule::Alias)
+        } else {
+            let normalized = normalize::fold(name);
+            let entry = self
+                .partidas
+                .entries
+                .iter()
+                .filter(|e| e.is_valid_in(year))
+                .find(|e| normalize::fold(&e.name) == normalized)?;
+            (entry.code.clone(), MatchRule::OfficialName)
+        };
+
+        Some(EntityMatch {
+            entity_key: format!("partida_{}", code),
+            entity_name: self.partidas.name(year, &code),
+            entity_type: "partida",
+            rule,
+        })
+    }
+
+    fn alias_for(&self, source_id: &str, name: &str) -> Option<String> {
+        let normalized = normalize::fold(name);
+        let matching = |a: &&EntityAlias| normalize::fold(&a.alias) == normalized;
+        self.aliases
+            .iter()
+            .filter(|a| a.source.as_deref() == Some(source_id))
+            .find(matching)
+            .or_else(|| self.aliases.iter().filter(|a| a.source.is_none()).find(matching))
+            .map(|a| format!("{:0>2}", a.partida))
+    }
+}
+
+/// How one source name was resolved during a run; feeds `entity_aliases`
+/// (matched) and `entity_review_queue` (unmatched)
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityResolution {
+    pub source_name: String,
+    pub normalized_name: String,
+    /// Canonical key, or the source-local key when unmatched
+    pub entity_key: String,
+    /// None = unmatched, queued for review
+    pub rule: Option<MatchRule>,
+    pub rows: usize,
+}
+
+/// Distinct source names seen in a run, in deterministic order
+#[derive(Debug, Default)]
+pub struct ResolutionLog(BTreeMap<String, EntityResolution>);
+
+impl ResolutionLog {
+    pub fn record(&mut self, source_name: &str, entity_key: &str, rule: Option<MatchRule>) {
+        let normalized_name = normalize::fold(source_name);
+        self.0
+            .entry(normalized_name.clone())
+            .or_insert_with(|| EntityResolution {
+                source_name: source_name.to_string(),
+                normalized_name,
+                entity_key: entity_key.to_string(),
+                rule,
+                rows: 0,
+            })
+            .rows += 1;
+    }
+
+    pub fn into_vec(self) -> Vec<EntityResolution> {
+        self.0.into_values().collect()
+    }
+}
+
+/// Canonical (or source-local) entity of a fact
+pub struct ResolvedEntity {
+    pub key: String,
+    pub name: String,
+    pub entity_type: String,
+}
+
+/// Resolve a source entity name through the crosswalk
+/// Matched names take the canonical entity and keep the original name in
+/// `dims.source_entity_name`; unmatched names keep a source-local key
+/// (`normalize::slug`) and are logged for the review queue. None when the
+/// name yields no key at all (no ASCII letter or digit)
+pub fn crosswalk_entity(
+    crosswalk: &EntityCrosswalk,
+    log: &mut ResolutionLog,
+    source_id: &str,
+    year: i32,
+    source_name: &str,
+    dims: &mut serde_json::Value,
+) -> Option<ResolvedEntity> {
+    match crosswalk.resolve(source_id, year, source_name) {
+        Some(m) => {
+            log.record(source_name, &m.entity_key, Some(m.rule));
+            dims["source_entity_name"] = serde_json::json!(source_name);
+            dims["entity_match_rule"] = serde_json::json!(m.rule.as_str());
+            dims["entity_aliases_version"] = serde_json::json!(crosswalk.version);
+            Some(ResolvedEntity {
+                key: m.entity_key,
+                name: m.entity_name,
+                entity_type: m.entity_type.to_string(),
+            })
+        }
+        None => {
+            // Source-local key: stable ASCII slug of the name
+            let key = normalize::slug(source_name);
+            if key.is_empty() {
+                return None;
+            }
+            log.record(source_name, &key, None);
+            Some(ResolvedEntity {
+                key,
+                name: source_name.to_string(),
+                entity_type: "organismo".to_string(),
+            })
+        }
+    }
+}
+
+/// "09", "9" or "09 - Ministerio de Educación" -> "09"
+fn partida_code_prefix(name: &str) -> Option<String> {
+    let name = name.trim();
+    let digits: String = name.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.is_empty() || digits.len() > 2 {
+        return None;
+    }
+    let rest = name[digits.len()..].trim_start();
+    if rest.is_empty() || rest.starts_with(['-', '–', ':', '.']) {
+        Some(format!("{:0>2}", digits))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_rules_in_order() {
+        let crosswalk = EntityCrosswalk::embedded().unwrap();
+
+        let by_code = crosswalk.resolve("any", 2024, "09 - Educación").unwrap();
+        assert_eq!(by_code.entity_key, "partida_09");
+        assert_eq!(by_code.entity_name, "Ministerio de Educación");
+        assert_eq!(by_code.rule, MatchRule::Code);
+
+        let by_alias = crosswalk.resolve("any", 2024, "Mineduc").unwrap();
+        assert_eq!(by_alias.entity_key, "partida_09");
+        assert_eq!(by_alias.rule, MatchRule::Alias);
+
+        let by_name = crosswalk.resolve("any", 2024, "  MINISTERIO DE SALUD ").unwrap();
+        assert_eq!(by_name.entity_key, "partida_16");
+        assert_eq!(by_name.rule, MatchRule::OfficialName);
+
+        assert!(crosswalk.resolve("any", 2024, "Servicio de Salud Aconcagua").is_none());
+        assert!(crosswalk.resolve("any", 2024, "2024").is_none());
+    }
+
+    #[test]
+    fn test_official_name_respects_year() {
+        let crosswalk = EntityCrosswalk::embedded().unwrap();
+        assert!(crosswalk.resolve("any", 2025, "Ministerio de Seguridad Pública").is_none());
+        assert_eq!(
+            crosswalk.resolve("any", 2026, "Ministerio de Seguridad Pública").unwrap().entity_key,
+            "partida_31"
+        );
+    }
+
+    #[test]
+    fn test_source_specific_alias_wins() {
+        let crosswalk = EntityCrosswalk::from_json(
+            r#"{"version": "t", "aliases": [
+                {"alias": "Hacienda", "partida": "08", "source": null},
+                {"alias": "Hacienda", "partida": "50", "source": "tesoro-csv"}
+            ]}"#,
+            PartidaCatalog::embedded().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(crosswalk.resolve("tesoro-csv", 2024, "hacienda").unwrap().entity_key, "partida_50");
+        assert_eq!(crosswalk.resolve("otra", 2024, "hacienda").unwrap().entity_key, "partida_08");
+    }
+}
@@ -0,0 +1,1577 @@
+//! DIPRES Ley de Presupuestos CSV (semicolon delimiter)
+//!
+//! Source: https://www.dipres.gob.cl/597/articles-397499_doc_csv.csv
+//! Documentation: docs/SOURCES.md
+//!
+//! Expected columns (normalized, in order):
+//!   Partida;Capitulo;Programa;Subtitulo;Item;Asignacion;Denominacion;Monto Pesos;Monto Dolar
+//!
+//! This parser:
+//! - Uses semicolon delimiter
+//! - Handles multiple encodings (UTF-8, UTF-8 BOM, Latin-1/Windows-1252)
+//! - Normalizes header names (removes accents for consistent matching)
+//! - Validates column structure
+//! - Aggregates by Partida (ministerio/servicio)
+//! - Fails explicitly on ambiguity (PRINCIPLES.md #3)
+
+use crate::classifier::{self, ClassifierCatalog};
+use crate::consolidation::{self, ConsolidationRules};
+use crate::encoding::{self, Decoded};
+use crate::fingerprint::SchemaFingerprint;
+use crate::normalize;
+use crate::numeric;
+use crate::partidas::PartidaCatalog;
+use crate::registry::{ArtifactInput, Parser};
+use crate::report::ParseReport;
+use crate::{decode_artifact, fiscal_year_from_source_id, with_encoding, ParseOutput, ParsedEntity, ParsedFact};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Parser identifiers for DIPRES Ley CSV (match `parsers` in config/sources.json)
+pub const PARSER_ID: &str = "dipres_ley_csv_v1";
+pub const LINES_PARSER_ID: &str = "dipres_ley_csv_lines_v1";
+
+/// Output granularity of the DIPRES Ley CSV parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DipresLeyMode {
+    /// One fact per Partida (sum of all its rows)
+    ByPartida,
+    /// One fact per row, attached to its Programa, with the full classifier as dims
+    LineItems,
+}
+
+impl DipresLeyMode {
+    pub fn parser_id(self) -> &'static str {
+        match self {
+            DipresLeyMode::ByPartida => PARSER_ID,
+            DipresLeyMode::LineItems => LINES_PARSER_ID,
+        }
+    }
+}
+
+/// Side of the budget a DIPRES Ley row belongs to, by the economic classifier
+/// Ingresos and gastos are never summed together (PRINCIPLES.md #4)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum BudgetSide {
+    Ingreso,
+    Gasto,
+}
+
+impl BudgetSide {
+    fn from_kind(kind: &str) -> Option<Self> {
+        match kind {
+            "ingreso" => Some(BudgetSide::Ingreso),
+            "gasto" => Some(BudgetSide::Gasto),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BudgetSide::Ingreso => "ingreso",
+            BudgetSide::Gasto => "gasto",
+        }
+    }
+
+    /// (metric_key, metric_name) for per-Partida totals
+    fn partida_metric(self) -> (&'static str, &'static str) {
+        match self {
+            BudgetSide::Ingreso => ("presupuesto_ley_ingreso", "Presupuesto de Ley - Ingresos"),
+            BudgetSide::Gasto => ("presupuesto_ley_gasto", "Presupuesto de Ley - Gastos"),
+        }
+    }
+
+    /// (metric_key, metric_name) for per-Partida totals in dollars (Monto Dolar)
+    fn partida_usd_metric(self) -> (&'static str, &'static str) {
+        match self {
+            BudgetSide::Ingreso => ("presupuesto_ley_ingreso_usd", "Presupuesto de Ley - Ingresos (USD)"),
+            BudgetSide::Gasto => ("presupuesto_ley_gasto_usd", "Presupuesto de Ley - Gastos (USD)"),
+        }
+    }
+
+    /// (metric_key, metric_name) for line items in dollars (Monto Dolar)
+    fn line_usd_metric(self) -> (&'static str, &'static str) {
+        match self {
+            BudgetSide::Ingreso => (
+                "presupuesto_ley_ingreso_linea_usd",
+                "Presupuesto de Ley - Ingresos (por línea, USD)",
+            ),
+            BudgetSide::Gasto => (
+                "presupuesto_ley_gasto_linea_usd",
+                "Presupuesto de Ley - Gastos (por línea, USD)",
+            ),
+        }
+    }
+
+    /// (metric_key, metric_name) for line items
+    fn line_metric(self) -> (&'static str, &'static str) {
+        match self {
+            BudgetSide::Ingreso => (
+                "presupuesto_ley_ingreso_linea",
+                "Presupuesto de Ley - Ingresos (por línea)",
+            ),
+            BudgetSide::Gasto => (
+                "presupuesto_ley_gasto_linea",
+                "Presupuesto de Ley - Gastos (por línea)",
+            ),
+        }
+    }
+}
+
+/// Classify a row as ingreso or gasto from its Subtitulo
+/// Halts if the row has no Subtitulo or the Subtitulo is not in the catalog
+fn classify_dipres_ley_row(
+    catalog: &ClassifierCatalog,
+    year: i32,
+    row: &DipresLeyRow,
+) -> Result<BudgetSide> {
+    let subtitulo = match classifier::segment(&row.subtitulo) {
+        Some(s) => format!("{:0>2}", s),
+        None => anyhow::bail!(
+            "AMBIGUITY: Line {} has no Subtitulo, cannot classify it as ingreso or gasto",
+            row.line_num
+        ),
+    };
+    let entry = catalog.lookup(year, &subtitulo).with_context(|| {
+        format!(
+            "AMBIGUITY: Line {} has Subtitulo '{}', not in the economic classifier for {}",
+            row.line_num, subtitulo, year
+        )
+    })?;
+    BudgetSide::from_kind(&entry.kind).with_context(|| {
+        format!(
+            "AMBIGUITY: Subtitulo '{}' has unknown kind '{}' in the economic classifier",
+            subtitulo, entry.kind
+        )
+    })
+}
+
+/// Consolidation rule tagging a row as an intra-Gobierno Central transfer, if any
+fn consolidation_rule_for<'a>(
+    rules: &'a ConsolidationRules,
+    row: &DipresLeyRow,
+) -> Option<&'a consolidation::ConsolidationRule> {
+    let code = classifier::dotted_code(&row.subtitulo, &row.item, &row.asignacion)?;
+    rules.matching(&code)
+}
+
+/// Expected headers for DIPRES Ley CSV (normalized without accents)
+const DIPRES_LEY_EXPECTED_HEADERS: &[&str] = &[
+    "Partida",
+    "Capitulo",
+    "Programa",
+    "Subtitulo",
+    "Item",
+    "Asignacion",
+    "Denominacion",
+    "Monto Pesos",
+    "Monto Dolar",
+];
+
+/// Row from DIPRES Ley CSV
+#[derive(Debug)]
+struct DipresLeyRow {
+    partida: String,
+    capitulo: String,
+    programa: String,
+    subtitulo: String,
+    item: String,
+    asignacion: String,
+    denominacion: String,
+    monto_pesos: i64,
+    monto_dolar: i64,
+    line_num: usize,
+}
+
+/// Aggregated fact by Partida
+#[derive(Debug)]
+struct PartidaAggregate {
+    partida_code: String,
+    side: BudgetSide,
+    partida_name: String,
+    total_monto: i64,
+    row_count: usize,
+    /// Sum of Monto Dolar, kept apart from the pesos total
+    total_dolar: i64,
+    dolar_rows: usize,
+    /// Part of total_monto tagged by a consolidation rule (gasto only)
+    transfer_monto: i64,
+    transfer_rows: usize,
+    first_line: usize,
+    last_line: usize,
+}
+
+/// Parse a decoded DIPRES Ley de Presupuestos CSV
+/// This function is DETERMINISTIC: same bytes = same output
+///
+/// Follows PRINCIPLES.md:
+/// - #1 Determinism: Same input = same output
+/// - #2 Evidence: Full provenance tracking
+/// - #3 Halt on ambiguity: Fails on unexpected structure
+/// - #4 Domain separation: Only parses Ley de Presupuestos format
+pub fn parse_dipres_ley_decoded(
+    decoded: &Decoded,
+    source_id: &str,
+    mode: DipresLeyMode,
+) -> Result<ParseOutput> {
+    let output = match mode {
+        DipresLeyMode::ByPartida => parse_dipres_ley_csv(&decoded.text, source_id),
+        DipresLeyMode::LineItems => parse_dipres_ley_csv_lines(&decoded.text, source_id),
+    }?;
+    Ok(with_encoding(output, decoded))
+}
+
+/// Parse DIPRES Ley de Presupuestos CSV
+/// This function is DETERMINISTIC: same CSV = same output
+///
+/// Follows PRINCIPLES.md:
+/// - #1 Determinism: Same input = same output
+/// - #2 Evidence: Full provenance tracking
+/// - #3 Halt on ambiguity: Fails on unexpected structure
+/// - #4 Domain separation: Only parses Ley de Presupuestos format
+pub fn parse_dipres_ley_csv(content: &str, source_id: &str) -> Result<ParseOutput> {
+    let mut report = ParseReport::default();
+    let (year, rows) = read_dipres_ley_rows(content, source_id, &mut report)?;
+    let catalog = ClassifierCatalog::embedded()?;
+    let rows = split_dipres_ley_control_rows(&rows, &catalog, year, &mut report);
+    let consolidation = ConsolidationRules::embedded()?;
+    let partida_names = PartidaCatalog::embedded()?;
+
+    // Aggregate by Partida and budget side (ingreso / gasto)
+    // Using BTreeMap for deterministic ordering
+    let mut aggregates: BTreeMap<(String, BudgetSide), PartidaAggregate> = BTreeMap::new();
+
+    for &row in &rows {
+        // Skip rows with empty partida
+        if row.partida.is_empty() {
+            report.skip();
+            continue;
+        }
+
+        let side = classify_dipres_ley_row(&catalog, year, row)?;
+        report.accept();
+
+        let entry = aggregates.entry((row.partida.clone(), side)).or_insert_with(|| {
+            // Official partida name in force for the fiscal year
+            PartidaAggregate {
+                partida_code: row.partida.clone(),
+                side,
+                partida_name: partida_names.name(year, &row.partida),
+                total_monto: 0,
+                row_count: 0,
+                total_dolar: 0,
+                dolar_rows: 0,
+                transfer_monto: 0,
+                transfer_rows: 0,
+                first_line: row.line_num,
+                last_line: row.line_num,
+            }
+        });
+
+        entry.total_monto += row.monto_pesos;
+        entry.row_count += 1;
+        entry.last_line = row.line_num;
+
+        if row.monto_dolar != 0 {
+            entry.total_dolar += row.monto_dolar;
+            entry.dolar_rows += 1;
+        }
+
+        if side == BudgetSide::Gasto && consolidation_rule_for(&consolidation, row).is_some() {
+            entry.transfer_monto += row.monto_pesos;
+            entry.transfer_rows += 1;
+        }
+    }
+
+    println!("Aggregated into {} partida totals (ingreso/gasto)", aggregates.len());
+
+    if aggregates.is_empty() {
+        anyhow::bail!("AMBIGUITY: No partidas found after aggregation");
+    }
+
+    // Create period dates
+    let period_start = NaiveDate::from_ymd_opt(year, 1, 1)
+        .context("Invalid year for period_start")?;
+    let period_end = NaiveDate::from_ymd_opt(year, 12, 31)
+        .context("Invalid year for period_end")?;
+
+    // Convert aggregates to facts
+    let mut facts: Vec<ParsedFact> = Vec::new();
+
+    for agg in aggregates.values() {
+        let partida_code = &agg.partida_code;
+        // Normalize entity key: partida code padded to 2 digits
+        let entity_key = format!("partida_{:0>2}", partida_code);
+
+        let entity_name = agg.partida_name.clone();
+        let (metric_key, metric_name) = agg.side.partida_metric();
+
+        facts.push(ParsedFact {
+            entity_key: entity_key.clone(),
+            entity_name: entity_name.clone(),
+            entity_type: "partida".to_string(),
+            entity_parents: Vec::new(),
+            metric_key: metric_key.to_string(),
+            metric_name: metric_name.to_string(),
+            metric_unit: "CLP".to_string(),
+            period_start,
+            period_end,
+            value_num: Decimal::from(agg.total_monto) * Decimal::ONE_THOUSAND, // CSV is in thousands of pesos
+            location: format!(
+                "dipres_ley_csv:partida={}:kind={}:lines={}-{}:rows={}",
+                partida_code,
+                agg.side.as_str(),
+                agg.first_line,
+                agg.last_line,
+                agg.row_count
+            ),
+            dims: serde_json::json!({
+                "partida_code": partida_code,
+                "kind": agg.side.as_str(),
+                "aggregated_rows": agg.row_count,
+                "source_file": "articles-397499_doc_csv.csv"
+            }),
+        });
+
+        // Dollar amounts are a separate series, never converted or added to CLP
+        if agg.dolar_rows > 0 {
+            let (usd_key, usd_name) = agg.side.partida_usd_metric();
+            facts.push(ParsedFact {
+                entity_key: entity_key.clone(),
+                entity_name: entity_name.clone(),
+                entity_type: "partida".to_string(),
+                entity_parents: Vec::new(),
+                metric_key: usd_key.to_string(),
+                metric_name: usd_name.to_string(),
+                metric_unit: "USD".to_string(),
+                period_start,
+                period_end,
+                value_num: Decimal::from(agg.total_dolar) * Decimal::ONE_THOUSAND, // CSV is in thousands of dollars
+                location: format!(
+                    "dipres_ley_csv:partida={}:kind={}:currency=usd:lines={}-{}:rows={}",
+                    partida_code,
+                    agg.side.as_str(),
+                    agg.first_line,
+                    agg.last_line,
+                    agg.dolar_rows
+                ),
+                dims: serde_json::json!({
+                    "partida_code": partida_code,
+                    "kind": agg.side.as_str(),
+                    "aggregated_rows": agg.dolar_rows,
+                    "source_file": "articles-397499_doc_csv.csv"
+                }),
+            });
+        }
+
+        // Intra-Gobierno Central transfers inside the gasto total, netted out
+        // by the API for consolidated figures
+        if agg.transfer_rows > 0 {
+            facts.push(ParsedFact {
+                entity_key,
+                entity_name,
+                entity_type: "partida".to_string(),
+                entity_parents: Vec::new(),
+                metric_key: "presupuesto_ley_gasto_transferencias".to_string(),
+                metric_name: "Presupuesto de Ley - Transferencias al Gobierno Central".to_string(),
+                metric_unit: "CLP".to_string(),
+                period_start,
+                period_end,
+                value_num: Decimal::from(agg.transfer_monto) * Decimal::ONE_THOUSAND, // CSV is in thousands of pesos
+                location: format!(
+                    "dipres_ley_csv:partida={}:kind=transferencias:lines={}-{}:rows={}",
+                    partida_code, agg.first_line, agg.last_line, agg.transfer_rows
+                ),
+                dims: serde_json::json!({
+                    "partida_code": partida_code,
+                    "kind": "gasto",
+                    "aggregated_rows": agg.transfer_rows,
+                    "consolidation_rules_version": consolidation.version,
+                    "source_file": "articles-397499_doc_csv.csv"
+                }),
+            });
+        }
+    }
+
+    // Sort by entity_key, then metric, for deterministic output
+    facts.sort_by(|a, b| {
+        a.entity_key
+            .cmp(&b.entity_key)
+            .then_with(|| a.metric_key.cmp(&b.metric_key))
+    });
+
+    println!("Created {} facts", facts.len());
+
+    // Print summary, one total per side and currency (never mixed)
+    for side in [BudgetSide::Ingreso, BudgetSide::Gasto] {
+        let (metric_key, _) = side.partida_metric();
+        let (usd_key, _) = side.partida_usd_metric();
+        let total: Decimal = facts
+            .iter()
+            .filter(|f| f.metric_key == metric_key)
+            .map(|f| f.value_num)
+            .sum();
+        let total_usd: Decimal = facts
+            .iter()
+            .filter(|f| f.metric_key == usd_key)
+            .map(|f| f.value_num)
+            .sum();
+        println!(
+            "Total {}: {} CLP ({:.2} billones) + {} USD",
+            side.as_str(),
+            total,
+            total / Decimal::from(1_000_000_000_000i64),
+            total_usd
+        );
+    }
+
+    check_dipres_ley_totals(&mut report, &rows, &facts, |side| {
+        (side.partida_metric().0, side.partida_usd_metric().0)
+    });
+
+    Ok(ParseOutput {
+        facts,
+        report,
+        entities: Vec::new(),
+        linked_entities: Vec::new(),
+    })
+}
+
+/// Parse DIPRES Ley de Presupuestos CSV into one fact per row (line items)
+/// This function is DETERMINISTIC: same CSV = same output
+///
+/// Each fact is attached to its Programa entity, whose parents are the
+/// Capitulo and the Partida. The institutional (partida/capitulo/programa)
+/// and economic (subtitulo/item/asignacion) classifier codes go into dims.
+/// Uses its own metric keys (one per budget side) so line items are never
+/// summed together with the per-Partida totals of `parse_dipres_ley_csv`.
+pub fn parse_dipres_ley_csv_lines(content: &str, source_id: &str) -> Result<ParseOutput> {
+    let mut report = ParseReport::default();
+    let (year, rows) = read_dipres_ley_rows(content, source_id, &mut report)?;
+    let catalog = ClassifierCatalog::embedded()?;
+    let rows = split_dipres_ley_control_rows(&rows, &catalog, year, &mut report);
+    let consolidation = ConsolidationRules::embedded()?;
+    let partida_names = PartidaCatalog::embedded()?;
+
+    let period_start = NaiveDate::from_ymd_opt(year, 1, 1)
+        .context("Invalid year for period_start")?;
+    let period_end = NaiveDate::from_ymd_opt(year, 12, 31)
+        .context("Invalid year for period_end")?;
+
+    let mut facts: Vec<ParsedFact> = Vec::new();
+
+    for &row in &rows {
+        // Skip rows with empty partida (same rule as the per-Partida aggregation)
+        if row.partida.is_empty() {
+            report.skip();
+            continue;
+        }
+
+        if row.capitulo.is_empty() || row.programa.is_empty() {
+            anyhow::bail!(
+                "AMBIGUITY: Line {} has Partida '{}' but no Capitulo/Programa",
+                row.line_num,
+                row.partida
+            );
+        }
+
+        let partida = format!("{:0>2}", row.partida);
+        let capitulo = format!("{:0>2}", row.capitulo);
+        let programa = format!("{:0>2}", row.programa);
+        let partida_name = partida_names.name(year, &partida);
+
+        // Classifier segments, zero-padded; absent levels become null
+        let subtitulo = classifier::segment(&row.subtitulo).map(|c| format!("{:0>2}", c));
+        let item = classifier::segment(&row.item).map(|c| format!("{:0>2}", c));
+        let asignacion = classifier::segment(&row.asignacion).map(|c| format!("{:0>3}", c));
+        let classifier_code = classifier::dotted_code(&row.subtitulo, &row.item, &row.asignacion);
+
+        // Every row must classify as ingreso or gasto for the fiscal year
+        let side = classify_dipres_ley_row(&catalog, year, row)?;
+        report.accept();
+        let (metric_key, metric_name) = side.line_metric();
+        let consolidation_rule = match side {
+            BudgetSide::Gasto => consolidation_rule_for(&consolidation, row).map(|r| r.id.clone()),
+            BudgetSide::Ingreso => None,
+        };
+
+        let fact = ParsedFact {
+            entity_key: format!("programa_{}_{}_{}", partida, capitulo, programa),
+            entity_name: format!(
+                "{} / Capítulo {} / Programa {}",
+                partida_name, capitulo, programa
+            ),
+            entity_type: "programa".to_string(),
+            entity_parents: vec![
+                ParsedEntity {
+                    key: format!("partida_{}", partida),
+                    name: partida_name.clone(),
+                    entity_type: "partida".to_string(),
+                },
+                ParsedEntity {
+                    key: format!("capitulo_{}_{}", partida, capitulo),
+                    name: format!("{} / Capítulo {}", partida_name, capitulo),
+                    entity_type: "capitulo".to_string(),
+                },
+            ],
+            metric_key: metric_key.to_string(),
+            metric_name: metric_name.to_string(),
+            metric_unit: "CLP".to_string(),
+            period_start,
+            period_end,
+            value_num: Decimal::from(row.monto_pesos) * Decimal::ONE_THOUSAND, // CSV is in thousands of pesos
+            location: format!("dipres_ley_csv:line={}", row.line_num),
+            dims: serde_json::json!({
+                "partida_code": partida,
+                "capitulo_code": capitulo,
+                "programa_code": programa,
+                "subtitulo_code": subtitulo,
+                "item_code": item,
+                "asignacion_code": asignacion,
+                "classifier_code": classifier_code,
+                "kind": side.as_str(),
+                "consolidation_rule": consolidation_rule,
+                "denominacion": row.denominacion,
+            }),
+        };
+
+        // Dollar amount of the same line, as its own USD series
+        if row.monto_dolar != 0 {
+            let (usd_key, usd_name) = side.line_usd_metric();
+            let usd_fact = ParsedFact {
+                metric_key: usd_key.to_string(),
+                metric_name: usd_name.to_string(),
+                metric_unit: "USD".to_string(),
+                value_num: Decimal::from(row.monto_dolar) * Decimal::ONE_THOUSAND, // CSV is in thousands of dollars
+                location: format!("dipres_ley_csv:line={}:currency=usd", row.line_num),
+                ..fact.clone()
+            };
+            facts.push(fact);
+            facts.push(usd_fact);
+        } else {
+            facts.push(fact);
+        }
+    }
+
+    if facts.is_empty() {
+        anyhow::bail!("AMBIGUITY: No line items found with a Partida code");
+    }
+
+    println!("Created {} line-item facts", facts.len());
+
+    check_dipres_ley_totals(&mut report, &rows, &facts, |side| {
+        (side.line_metric().0, side.line_usd_metric().0)
+    });
+
+    Ok(ParseOutput {
+        facts,
+        report,
+        entities: Vec::new(),
+        linked_entities: Vec::new(),
+    })
+}
+
+/// Totals checks shared by both DIPRES Ley modes: the facts of each currency
+/// must add up exactly to the detail rows they were built from
+fn check_dipres_ley_totals(
+    report: &mut ParseReport,
+    rows: &[&DipresLeyRow],
+    facts: &[ParsedFact],
+    metrics: impl Fn(BudgetSide) -> (&'static str, &'static str),
+) {
+    let rows = rows.iter().filter(|r| !r.partida.is_empty());
+    let expected_clp: Decimal = rows.clone().map(|r| Decimal::from(r.monto_pesos)).sum();
+    let expected_usd: Decimal = rows.map(|r| Decimal::from(r.monto_dolar)).sum();
+
+    let mut actual_clp = Decimal::ZERO;
+    let mut actual_usd = Decimal::ZERO;
+    for side in [BudgetSide::Ingreso, BudgetSide::Gasto] {
+        let (clp_key, usd_key) = metrics(side);
+        for fact in facts {
+            if fact.metric_key == clp_key {
+                actual_clp += fact.value_num;
+            } else if fact.metric_key == usd_key {
+                actual_usd += fact.value_num;
+            }
+        }
+    }
+
+    report.check_total("rows_vs_facts_clp", expected_clp * Decimal::ONE_THOUSAND, actual_clp);
+    report.check_total("rows_vs_facts_usd", expected_usd * Decimal::ONE_THOUSAND, actual_usd);
+}
+
+/// Separate subtotal rows from detail rows
+///
+/// DIPRES files repeat amounts at several levels of the economic classifier:
+/// within one Programa, a Subtitulo row ("22") or Ítem row ("22.04") that has
+/// rows below it ("22.04", "22.04.001") is their subtotal. Adding it to the
+/// detail would double count, so it is left out and checked instead: its
+/// amount must equal, with zero tolerance, the sum of the detail rows under it.
+/// A row with a Partida but no Subtitulo is a Partida, Capítulo or Programa
+/// total: it is left out too and checked against the detail rows of every
+/// Programa under its prefix, ingresos and gastos summed apart
+/// (PRINCIPLES.md #4). The row is compared with the side its Denominación
+/// names or, if it names none, with the side it matches (a Programa states
+/// balanced ingresos and gastos, so either may); failing that, with the
+/// nearer side, so the mismatch is reported.
+/// Returns the detail rows (including rows without Partida, handled by the
+/// caller) in file order.
+fn split_dipres_ley_control_rows<'a>(
+    rows: &'a [DipresLeyRow],
+    catalog: &ClassifierCatalog,
+    year: i32,
+    report: &mut ParseReport,
+) -> Vec<&'a DipresLeyRow> {
+    // Rows with a classifier code, grouped by Programa ("PP.CC.GG")
+    let mut programas: BTreeMap<String, Vec<(String, &DipresLeyRow)>> = BTreeMap::new();
+    for row in rows.iter().filter(|r| !r.partida.is_empty()) {
+        if let Some(code) = classifier::dotted_code(&row.subtitulo, &row.item, &row.asignacion) {
+            programas
+                .entry(format!(
+                    "{:0>2}.{:0>2}.{:0>2}",
+                    row.partida, row.capitulo, row.programa
+                ))
+                .or_default()
+                .push((code, row));
+        }
+    }
+
+    let is_below = |code: &str, parent: &str| {
+        code.strip_prefix(parent).is_some_and(|rest| rest.starts_with('.'))
+    };
+
+    let mut control_lines: BTreeSet<usize> = BTreeSet::new();
+    let mut expected = (0i64, 0i64);
+    let mut actual = (0i64, 0i64);
+
+    for (programa, coded) in &programas {
+        for (code, row) in coded {
+            if !coded.iter().any(|(other, _)| is_below(other, code)) {
+                continue;
+            }
+            control_lines.insert(row.line_num);
+            report.control();
+
+            // Detail rows under this subtotal: descendants that are not subtotals themselves
+            let detail = coded.iter().filter(|(other, _)| {
+                is_below(other, code) && !coded.iter().any(|(below, _)| is_below(below, other))
+            });
+            let (pesos, dolar) = detail.fold((0i64, 0i64), |(p, d), (_, r)| {
+                (p + r.monto_pesos, d + r.monto_dolar)
+            });
+
+            expected = (expected.0 + row.monto_pesos, expected.1 + row.monto_dolar);
+            actual = (actual.0 + pesos, actual.1 + dolar);
+
+            check_control_row(
+                report,
+                &format!("line={}:programa={}:code={}", row.line_num, programa, code),
+                row,
+                (pesos, dolar),
+            );
+        }
+    }
+
+    // Institutional-level totals: no classifier code, so nothing to classify
+    for row in rows.iter().filter(|r| !r.partida.is_empty()) {
+        if classifier::dotted_code(&row.subtitulo, &row.item, &row.asignacion).is_some() {
+            continue;
+        }
+        let key = [&row.partida, &row.capitulo, &row.programa]
+            .into_iter()
+            .take_while(|code| !code.is_empty())
+            .map(|code| format!("{:0>2}", code))
+            .collect::<Vec<_>>()
+            .join(".");
+        let prefix = format!("{}.", key);
+        control_lines.insert(row.line_num);
+        report.control();
+
+        // Unclassifiable rows are left out here; the caller halts on them
+        let mut sides: BTreeMap<BudgetSide, (i64, i64)> = BTreeMap::new();
+        for (_, r) in programas
+            .iter()
+            .filter(|(programa, _)| **programa == key || programa.starts_with(&prefix))
+            .flat_map(|(_, coded)| coded)
+            .filter(|(_, r)| !control_lines.contains(&r.line_num))
+        {
+            if let Ok(side) = classify_dipres_ley_row(catalog, year, r) {
+                let sum = sides.entry(side).or_default();
+                *sum = (sum.0 + r.monto_pesos, sum.1 + r.monto_dolar);
+            }
+        }
+        let stated = (row.monto_pesos, row.monto_dolar);
+        let detail = |side: BudgetSide| sides.get(&side).copied().unwrap_or_default();
+        let all_sides = [BudgetSide::Ingreso, BudgetSide::Gasto];
+        let side = named_side(&row.denominacion)
+            .or_else(|| all_sides.into_iter().find(|&side| detail(side) == stated))
+            .unwrap_or_else(|| {
+                all_sides
+                    .into_iter()
+                    .min_by_key(|&side| (detail(side).0 - stated.0).abs())
+                    .unwrap_or(BudgetSide::Gasto)
+            });
+        let (pesos, dolar) = detail(side);
+
+        expected = (expected.0 + row.monto_pesos, expected.1 + row.monto_dolar);
+        actual = (actual.0 + pesos, actual.1 + dolar);
+        check_control_row(
+            report,
+            &format!("line={}:code={}:kind={}", row.line_num, key, side.as_str()),
+            row,
+            (pesos, dolar),
+        );
+    }
+
+    if !control_lines.is_empty() {
+        println!(
+            "Detected {} subtotal rows, excluded from aggregation",
+            control_lines.len()
+        );
+        report.check_total(
+            "control_totals_clp",
+            Decimal::from(expected.0) * Decimal::ONE_THOUSAND,
+            Decimal::from(actual.0) * Decimal::ONE_THOUSAND,
+        );
+        report.check_total(
+            "control_totals_usd",
+            Decimal::from(expected.1) * Decimal::ONE_THOUSAND,
+            Decimal::from(actual.1) * Decimal::ONE_THOUSAND,
+        );
+    }
+
+    rows.iter()
+        .filter(|r| !control_lines.contains(&r.line_num))
+        .collect()
+}
+
+/// Side named by a total row's Denominación ("TOTAL INGRESOS", "Total Gastos"), if exactly one
+fn named_side(denominacion: &str) -> Option<BudgetSide> {
+    let words = normalize::fold(denominacion);
+    let words: Vec<&str> = words.split(' ').collect();
+    let names = |stem: &str| words.iter().any(|w| w.starts_with(stem));
+    match (names("ingreso"), names("gasto")) {
+        (true, false) => Some(BudgetSide::Ingreso),
+        (false, true) => Some(BudgetSide::Gasto),
+        _ => None,
+    }
+}
+
+/// Record a control row whose amounts differ from its detail; only mismatches
+/// are recorded one by one, to keep the report small
+fn check_control_row(
+    report: &mut ParseReport,
+    location: &str,
+    row: &DipresLeyRow,
+    (pesos, dolar): (i64, i64),
+) {
+    for (currency, total, sum) in [
+        ("clp", row.monto_pesos, pesos),
+        ("usd", row.monto_dolar, dolar),
+    ] {
+        if total != sum {
+            report.check_total(
+                format!("control_total_{}:{}", currency, location),
+                Decimal::from(total) * Decimal::ONE_THOUSAND,
+                Decimal::from(sum) * Decimal::ONE_THOUSAND,
+            );
+        }
+    }
+}
+
+/// Parse a DIPRES Ley amount cell (whole thousands, es-CL); empty means 0
+/// Returns the reason on failure so the caller can reject the row
+fn parse_dipres_ley_amount(cell: Option<&str>) -> std::result::Result<i64, String> {
+    let cleaned = cell.unwrap_or("").trim();
+    if cleaned.is_empty() {
+        return Ok(0);
+    }
+    let value = numeric::parse(cleaned)?;
+    if !value.fract().is_zero() {
+        return Err(format!("'{}' is not a whole number of thousands", cleaned));
+    }
+    value
+        .to_i64()
+        .ok_or_else(|| format!("'{}' is out of range", cleaned))
+}
+
+/// Read and validate the rows of a DIPRES Ley de Presupuestos CSV
+/// Returns the fiscal year (from source_id) and every parsed row in file order;
+/// rows that cannot be read go to `report` as rejects
+fn read_dipres_ley_rows(
+    content: &str,
+    source_id: &str,
+    report: &mut ParseReport,
+) -> Result<(i32, Vec<DipresLeyRow>)> {
+    println!("=== DIPRES Ley CSV Parser ===");
+    println!("Source ID: {}", source_id);
+
+    // Extract year from source_id (e.g., "dipres-ley-presupuestos-2026")
+    let year: i32 = fiscal_year_from_source_id(source_id)
+        .context("AMBIGUITY: Cannot extract year from source_id. Expected format: dipres-ley-presupuestos-YYYY")?;
+
+    println!("Fiscal year: {}", year);
+
+    // Remove UTF-8 BOM if present
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+
+    // Create CSV reader with semicolon delimiter
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .flexible(false)  // Strict: all rows must have same number of fields
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    // Validate headers match expected (with normalization for accents/encoding differences)
+    let raw_headers: Vec<String> = reader
+        .headers()
+        .context("Failed to read CSV headers")?
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+
+    // Normalize headers for comparison (handle accented vs non-accented)
+    let headers: Vec<String> = raw_headers.iter().map(|h| normalize::clean_label(h)).collect();
+
+    println!("Found {} columns", headers.len());
+    println!("Raw headers: {:?}", raw_headers);
+    println!("Normalized headers: {:?}", headers);
+
+    if headers.len() != DIPRES_LEY_EXPECTED_HEADERS.len() {
+        anyhow::bail!(
+            "AMBIGUITY: Expected {} columns, found {}. Headers: {:?}",
+            DIPRES_LEY_EXPECTED_HEADERS.len(),
+            headers.len(),
+            raw_headers
+        );
+    }
+
+    // Validate each normalized header matches expected (case and punctuation folded)
+    for (i, (found, expected)) in headers.iter().zip(DIPRES_LEY_EXPECTED_HEADERS.iter()).enumerate() {
+        if normalize::fold(found) != normalize::fold(expected) {
+            anyhow::bail!(
+                "AMBIGUITY: Column {} mismatch. Expected '{}', found '{}' (raw: '{}')",
+                i,
+                expected,
+                found,
+                raw_headers[i]
+            );
+        }
+    }
+
+    println!("Headers validated: {:?}", headers);
+
+    // Parse all rows; rows that fail are rejected with their line and reason
+    let mut rows: Vec<DipresLeyRow> = Vec::new();
+
+    for (line_idx, result) in reader.records().enumerate() {
+        let line_num = line_idx + 2; // +1 for 0-index, +1 for header
+
+        let record = match result {
+            Ok(r) => r,
+            Err(e) => {
+                report.reject(line_num, format!("CSV parse error: {}", e));
+                continue;
+            }
+        };
+
+        // Validate field count
+        if record.len() != 9 {
+            report.reject(line_num, format!("Expected 9 fields, found {}", record.len()));
+            continue;
+        }
+
+        // Parse monto_pesos (required, must be a valid integer; empty means 0)
+        let monto_pesos: i64 = match parse_dipres_ley_amount(record.get(7)) {
+            Ok(v) => v,
+            Err(reason) => {
+                report.reject(line_num, format!("Invalid 'Monto Pesos' value {}", reason));
+                continue;
+            }
+        };
+
+        // Parse monto_dolar (optional, empty means 0)
+        let monto_dolar: i64 = match parse_dipres_ley_amount(record.get(8)) {
+            Ok(v) => v,
+            Err(reason) => {
+                report.reject(line_num, format!("Invalid 'Monto Dolar' value {}", reason));
+                continue;
+            }
+        };
+
+        rows.push(DipresLeyRow {
+            partida: record.get(0).unwrap_or("").trim().to_string(),
+            capitulo: record.get(1).unwrap_or("").trim().to_string(),
+            programa: record.get(2).unwrap_or("").trim().to_string(),
+            subtitulo: record.get(3).unwrap_or("").trim().to_string(),
+            item: record.get(4).unwrap_or("").trim().to_string(),
+            asignacion: record.get(5).unwrap_or("").trim().to_string(),
+            denominacion: record.get(6).unwrap_or("").trim().to_string(),
+            monto_pesos,
+            monto_dolar,
+            line_num,
+        });
+    }
+
+    println!("Parsed {} rows, rejected {}", rows.len(), report.rejects.len());
+
+    if rows.is_empty() {
+        anyhow::bail!("AMBIGUITY: No valid rows parsed from CSV");
+    }
+
+    Ok((year, rows))
+}
+
+/// Structural fingerprint of a DIPRES Ley CSV, computed without the strict
+/// header validation so renamed or extra columns still get fingerprinted
+pub fn dipres_ley_fingerprint(content: &str) -> Result<SchemaFingerprint> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let headers: Vec<String> = reader
+        .headers()
+        .context("Failed to read CSV headers")?
+        .iter()
+        .map(normalize::clean_label)
+        .collect();
+    let records: Vec<Vec<String>> = reader
+        .records()
+        .filter_map(|r| r.ok())
+        .map(|r| r.iter().map(|c| c.to_string()).collect())
+        .collect();
+
+    Ok(SchemaFingerprint::from_table(headers, &records))
+}
+
+/// Detect if source is DIPRES Ley CSV format
+pub fn is_dipres_ley_csv(source_id: &str) -> bool {
+    source_id.starts_with("dipres-ley-presupuestos")
+}
+
+/// DIPRES Ley CSV parser in one output granularity; the service picks the
+/// per-Partida one unless line items are asked for
+pub struct DipresLeyParser(pub DipresLeyMode);
+
+impl Parser for DipresLeyParser {
+    fn id(&self) -> &'static str {
+        self.0.parser_id()
+    }
+
+    fn accepts(&self, input: &ArtifactInput) -> bool {
+        is_dipres_ley_csv(input.source_id)
+    }
+
+    fn parse(&self, input: &ArtifactInput) -> Result<ParseOutput> {
+        // Read as raw bytes to handle different encodings (UTF-8, Latin-1)
+        let decoded = decode_artifact(input.bytes, input.source_id)?;
+        println!("DIPRES Ley mode: {:?}", self.0);
+        parse_dipres_ley_decoded(&decoded, input.source_id, self.0)
+    }
+
+    fn fingerprint(&self, input: &ArtifactInput) -> Result<Option<SchemaFingerprint>> {
+        let declared = encoding::declared_encoding(input.source_id)?;
+        let decoded = encoding::decode(input.bytes, declared)?;
+        Ok(Some(dipres_ley_fingerprint(&decoded.text)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+    use rust_decimal_macros::dec;
+
+    // -------------------------------------------------------------------------
+    // DIPRES LEY CSV PARSER TESTS
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_dipres_ley_csv_basic() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;01;01;21;00;000;PRESIDENCIA DE LA REPÚBLICA;100000;0\n\
+                   01;01;01;22;00;000;BIENES Y SERVICIOS;50000;0\n";
+
+        let facts = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+
+        assert_eq!(facts.len(), 1); // Aggregated by partida
+        assert_eq!(facts[0].entity_key, "partida_01");
+        assert_eq!(facts[0].metric_key, "presupuesto_ley_gasto");
+        assert_eq!(facts[0].value_num, dec!(150000) * Decimal::ONE_THOUSAND); // CSV is in thousands
+        assert_eq!(facts[0].period_start.year(), 2026);
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_multiple_partidas() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;01;01;21;00;000;PRESIDENCIA;100000;0\n\
+                   02;01;01;21;00;000;CONGRESO NACIONAL;200000;0\n\
+                   03;01;01;21;00;000;PODER JUDICIAL;300000;0\n";
+
+        let facts = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+
+        assert_eq!(facts.len(), 3);
+        // Sorted by entity_key
+        assert_eq!(facts[0].entity_key, "partida_01");
+        assert_eq!(facts[1].entity_key, "partida_02");
+        assert_eq!(facts[2].entity_key, "partida_03");
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_aggregation() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;01;01;21;00;000;ITEM A;100000;0\n\
+                   01;01;02;22;00;000;ITEM B;200000;0\n\
+                   01;02;01;21;00;000;ITEM C;300000;0\n";
+
+        let facts = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+
+        assert_eq!(facts.len(), 1); // All same partida
+        assert_eq!(facts[0].value_num, dec!(600000) * Decimal::ONE_THOUSAND); // Sum of all
+        assert_eq!(facts[0].dims["aggregated_rows"], 3);
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_separates_ingresos_and_gastos() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   09;01;01;09;00;000;APORTE FISCAL;450000;0\n\
+                   09;01;01;21;00;000;GASTOS EN PERSONAL;300000;0\n\
+                   09;01;01;22;00;000;BIENES Y SERVICIOS;150000;0\n";
+
+        let facts = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+
+        assert_eq!(facts.len(), 2);
+        assert_eq!(facts[0].metric_key, "presupuesto_ley_gasto");
+        assert_eq!(facts[0].value_num, dec!(450000) * Decimal::ONE_THOUSAND);
+        assert_eq!(facts[0].dims["kind"], "gasto");
+        assert_eq!(facts[0].dims["aggregated_rows"], 2);
+        assert_eq!(facts[1].metric_key, "presupuesto_ley_ingreso");
+        assert_eq!(facts[1].value_num, dec!(450000) * Decimal::ONE_THOUSAND);
+        assert_eq!(facts[1].dims["kind"], "ingreso");
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_transfers_to_gobierno_central() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   50;01;03;21;00;000;GASTOS EN PERSONAL;100000;0\n\
+                   50;01;03;24;02;001;A SERVICIO X;200000;0\n\
+                   50;01;03;33;02;004;A SERVICIO Y;300000;0\n";
+
+        let facts = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+
+        assert_eq!(facts.len(), 2);
+        assert_eq!(facts[0].metric_key, "presupuesto_ley_gasto");
+        assert_eq!(facts[0].value_num, dec!(600000) * Decimal::ONE_THOUSAND); // Gross
+        assert_eq!(facts[1].metric_key, "presupuesto_ley_gasto_transferencias");
+        assert_eq!(facts[1].entity_key, "partida_50");
+        assert_eq!(facts[1].value_num, dec!(500000) * Decimal::ONE_THOUSAND);
+        assert_eq!(facts[1].dims["aggregated_rows"], 2);
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_lines_consolidation_rule_dim() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   50;01;03;24;02;001;A SERVICIO X;200000;0\n\
+                   50;01;03;24;01;001;AL SECTOR PRIVADO;100000;0\n";
+
+        let facts = parse_dipres_ley_csv_lines(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+
+        assert_eq!(
+            facts[0].dims["consolidation_rule"],
+            "transferencias_corrientes_gobierno_central"
+        );
+        assert!(facts[1].dims["consolidation_rule"].is_null());
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_usd_series() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   06;01;01;21;00;000;GASTOS EN PERSONAL;100000;40000\n\
+                   06;01;01;22;00;000;BIENES Y SERVICIOS;50000;0\n\
+                   06;01;01;09;00;000;APORTE FISCAL;150000;40000\n";
+
+        let facts = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+        let keys: Vec<&str> = facts.iter().map(|f| f.metric_key.as_str()).collect();
+
+        assert_eq!(
+            keys,
+            vec![
+                "presupuesto_ley_gasto",
+                "presupuesto_ley_gasto_usd",
+                "presupuesto_ley_ingreso",
+                "presupuesto_ley_ingreso_usd"
+            ]
+        );
+        assert_eq!(facts[0].metric_unit, "CLP");
+        assert_eq!(facts[0].value_num, dec!(150000) * Decimal::ONE_THOUSAND); // Pesos only
+        assert_eq!(facts[1].metric_unit, "USD");
+        assert_eq!(facts[1].value_num, dec!(40000) * Decimal::ONE_THOUSAND);
+        assert_eq!(facts[1].dims["aggregated_rows"], 1);
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_lines_usd_series() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   06;01;01;21;00;000;GASTOS EN PERSONAL;100000;40000\n\
+                   06;01;01;22;00;000;BIENES Y SERVICIOS;50000;0\n";
+
+        let facts = parse_dipres_ley_csv_lines(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+
+        assert_eq!(facts.len(), 3);
+        assert_eq!(facts[1].metric_key, "presupuesto_ley_gasto_linea_usd");
+        assert_eq!(facts[1].metric_unit, "USD");
+        assert_eq!(facts[1].value_num, dec!(40000) * Decimal::ONE_THOUSAND);
+        assert_eq!(facts[1].location, "dipres_ley_csv:line=2:currency=usd");
+        assert_eq!(facts[1].dims, facts[0].dims);
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_unclassifiable_row_fails() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   09;01;01;21;00;000;GASTOS EN PERSONAL;300000;0\n\
+                   09;01;01;98;00;000;FUERA DEL CLASIFICADOR;150000;0\n";
+
+        let result = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("AMBIGUITY"));
+
+        // Without Subtitulo the row is the Programa total, and must match its detail
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   09;01;01;21;00;000;GASTOS EN PERSONAL;300000;0\n\
+                   09;01;01;;;;SIN SUBTITULO;150000;0\n";
+        let output = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap();
+        let err = output.report.enforce(1.0).unwrap_err().to_string();
+        assert!(err.contains("AMBIGUITY"));
+        assert!(err.contains("control_total_clp:line=3:code=09.01.01"), "{}", err);
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_determinism() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;01;01;21;00;000;ITEM A;100000;0\n\
+                   02;01;01;21;00;000;ITEM B;200000;0\n";
+
+        let result1 = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+        let result2 = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+
+        // Must be identical
+        assert_eq!(result1.len(), result2.len());
+        for (a, b) in result1.iter().zip(result2.iter()) {
+            assert_eq!(a.entity_key, b.entity_key);
+            assert_eq!(a.value_num, b.value_num);
+            assert_eq!(a.location, b.location);
+        }
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_exact_large_amounts() {
+        // 9,007,199,254,740,993 thousand pesos is beyond f64's exact integer range
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   50;01;01;21;00;000;ITEM A;9007199254740993;0\n\
+                   50;01;01;22;00;000;ITEM B;1;0\n";
+
+        let facts = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+
+        assert_eq!(facts[0].value_num.to_string(), "9007199254740994000");
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_es_cl_amounts() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;01;01;21;00;000;ITEM A;1.234.567;0\n\
+                   01;01;01;22;00;000;ITEM B;10,5;0\n";
+
+        let output = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap();
+
+        assert_eq!(output.facts[0].value_num, dec!(1234567) * Decimal::ONE_THOUSAND);
+        assert!(output.report.rejects[0].reason.contains("whole number"));
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_invalid_amount_is_rejected() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;01;01;21;00;000;ITEM A;100000;0\n\
+                   01;01;01;22;00;000;ITEM B;12x45;0\n\
+                   01;01;01;22;00;000;ITEM C;50000;abc\n";
+
+        let output = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap();
+
+        // Rejected rows are left out instead of being counted as 0
+        assert_eq!(output.facts[0].value_num, dec!(100000) * Decimal::ONE_THOUSAND);
+        assert_eq!(output.report.accepted_rows, 1);
+        assert_eq!(output.report.rejects.len(), 2);
+        assert_eq!(output.report.rejects[0].line, 3);
+        assert!(output.report.rejects[0].reason.contains("Monto Pesos"));
+        assert!(output.report.rejects[1].reason.contains("Monto Dolar"));
+        assert!(output.report.enforce(0.0).is_err());
+        assert!(output.report.enforce(0.7).is_ok());
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_totals_checks_pass() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;01;01;21;00;000;ITEM A;100000;10\n\
+                   02;01;01;09;00;000;ITEM B;200000;0\n";
+
+        let by_partida = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap();
+        let lines = parse_dipres_ley_csv_lines(csv, "dipres-ley-presupuestos-2026").unwrap();
+
+        for report in [by_partida.report, lines.report] {
+            assert_eq!(report.checks.len(), 2);
+            assert!(report.checks.iter().all(|c| c.ok));
+            assert!(report.enforce(0.0).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_subtotal_rows_excluded() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;01;01;22;00;000;BIENES Y SERVICIOS;300;0\n\
+                   01;01;01;22;01;000;ALIMENTOS;100;0\n\
+                   01;01;01;22;04;000;MATERIALES;200;0\n\
+                   01;01;01;22;04;001;MATERIALES DE OFICINA;150;0\n\
+                   01;01;01;22;04;002;TEXTOS;50;0\n\
+                   01;01;02;22;00;000;BIENES Y SERVICIOS;40;0\n";
+
+        let output = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap();
+
+        // 22 and 22.04 of programa 01 are subtotals; programa 02 has no detail below 22
+        assert_eq!(output.facts.len(), 1);
+        assert_eq!(output.facts[0].value_num, dec!(340) * Decimal::ONE_THOUSAND);
+        assert_eq!(output.report.control_rows, 2);
+        assert_eq!(output.report.accepted_rows, 4);
+        assert!(output.report.checks.iter().any(|c| c.name == "control_totals_clp" && c.ok));
+        assert!(output.report.enforce(0.0).is_ok());
+
+        let lines = parse_dipres_ley_csv_lines(csv, "dipres-ley-presupuestos-2026").unwrap();
+        let line_refs: Vec<&str> = lines.facts.iter().map(|f| f.location.as_str()).collect();
+        assert_eq!(
+            line_refs,
+            vec![
+                "dipres_ley_csv:line=3",
+                "dipres_ley_csv:line=5",
+                "dipres_ley_csv:line=6",
+                "dipres_ley_csv:line=7"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_institutional_total_rows_are_control_rows() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;;;;;;PRESIDENCIA DE LA REPUBLICA;350;5\n\
+                   01;01;;;;;SECRETARIA GENERAL;350;5\n\
+                   01;01;01;;;;PROGRAMA 01;300;5\n\
+                   01;01;01;21;00;000;GASTOS EN PERSONAL;100;5\n\
+                   01;01;01;22;00;000;BIENES Y SERVICIOS;200;0\n\
+                   01;01;02;22;00;000;BIENES Y SERVICIOS;50;0\n";
+
+        let output = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap();
+
+        // The Partida, Capítulo and Programa totals are checked, not added
+        let clp: Vec<&ParsedFact> = output.facts.iter().filter(|f| f.metric_unit == "CLP").collect();
+        assert_eq!(clp.len(), 1);
+        let total: Decimal = clp.iter().map(|f| f.value_num).sum();
+        assert_eq!(total, dec!(350) * Decimal::ONE_THOUSAND);
+        assert_eq!(output.report.control_rows, 3);
+        assert_eq!(output.report.accepted_rows, 3);
+        assert!(output.report.enforce(0.0).is_ok(), "{:?}", output.report.checks);
+
+        // A Partida total that does not match its detail halts
+        let csv = csv.replacen("REPUBLICA;350", "REPUBLICA;999", 1);
+        let output = parse_dipres_ley_csv_lines(&csv, "dipres-ley-presupuestos-2026").unwrap();
+        let err = output.report.enforce(1.0).unwrap_err().to_string();
+        assert!(err.contains("control_total_clp:line=2:code=01"), "{}", err);
+        assert!(err.contains("expected 999000, got 350000"), "{}", err);
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_institutional_total_over_ingresos_and_gastos() {
+        // Partida 06 has ingreso (09) and gasto (22) lines; its totals state one side
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   06;;;;;;MINISTERIO DE RELACIONES EXTERIORES;10000;5000\n\
+                   06;01;01;;;;TOTAL INGRESOS;12000;5000\n\
+                   06;01;01;;;;TOTAL GASTOS;10000;5000\n\
+                   06;01;01;09;00;000;APORTE FISCAL;12000;5000\n\
+                   06;01;01;22;00;000;BIENES Y SERVICIOS DE CONSUMO;10000;5000\n";
+
+        let output = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap();
+        assert!(output.report.enforce(0.0).is_ok(), "{:?}", output.report.checks);
+        assert_eq!(output.report.control_rows, 3);
+        let clp: Vec<(&str, Decimal)> = output
+            .facts
+            .iter()
+            .filter(|f| f.metric_unit == "CLP")
+            .map(|f| (f.metric_key.as_str(), f.value_num))
+            .collect();
+        assert_eq!(
+            clp,
+            [
+                ("presupuesto_ley_gasto", dec!(10000) * Decimal::ONE_THOUSAND),
+                ("presupuesto_ley_ingreso", dec!(12000) * Decimal::ONE_THOUSAND)
+            ]
+        );
+
+        // The sum of both sides is not a valid total
+        let csv = csv.replacen("EXTERIORES;10000", "EXTERIORES;22000", 1);
+        let output = parse_dipres_ley_csv(&csv, "dipres-ley-presupuestos-2026").unwrap();
+        let err = output.report.enforce(1.0).unwrap_err().to_string();
+        assert!(err.contains("control_total_clp:line=2:code=06:kind=ingreso"), "{}", err);
+        assert!(err.contains("expected 22000000, got 12000000"), "{}", err);
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_subtotal_mismatch_halts() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;01;01;22;00;000;BIENES Y SERVICIOS;999;0\n\
+                   01;01;01;22;01;000;ALIMENTOS;100;0\n\
+                   01;01;01;22;04;000;MATERIALES;200;0\n";
+
+        let output = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap();
+        let err = output.report.enforce(1.0).unwrap_err().to_string();
+
+        assert!(err.contains("AMBIGUITY"));
+        assert!(err.contains("control_total_clp:line=2:programa=01.01.01:code=22"));
+        assert!(err.contains("expected 999000, got 300000"));
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_partida_name_in_force_for_year() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   05;01;01;21;00;000;GASTOS EN PERSONAL;100;0\n";
+
+        let ley_2025 = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2025").unwrap();
+        let ley_2026 = parse_dipres_ley_csv_lines(csv, "dipres-ley-presupuestos-2026").unwrap();
+
+        assert_eq!(ley_2025.facts[0].entity_name, "Ministerio del Interior y Seguridad Pública");
+        assert_eq!(ley_2026.facts[0].entity_parents[0].name, "Ministerio del Interior");
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_wrong_headers_fails() {
+        let csv = "Wrong;Headers;Here;For;Testing;Invalid;Format;Columns;Data\n\
+                   01;01;01;21;00;000;ITEM;100000;0\n";
+
+        let result = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("AMBIGUITY"));
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_wrong_column_count_fails() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos\n\
+                   01;01;01;21;00;000;ITEM;100000\n"; // Missing Monto Dolar column
+
+        let result = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("AMBIGUITY"));
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_no_year_in_source_id_fails() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;01;01;21;00;000;ITEM;100000;0\n";
+
+        let result = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("AMBIGUITY"));
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_with_bom() {
+        // UTF-8 BOM + valid CSV
+        let csv = "\u{feff}Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;01;01;21;00;000;TEST;100000;0\n";
+
+        let facts = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+        assert_eq!(facts.len(), 1);
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_lines_one_fact_per_row() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   09;01;01;21;00;000;GASTOS EN PERSONAL;100000;0\n\
+                   09;01;01;22;00;000;BIENES Y SERVICIOS;50000;0\n\
+                   09;09;01;24;01;001;ALIMENTACION ESCOLAR;300000;0\n";
+
+        let facts = parse_dipres_ley_csv_lines(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+
+        assert_eq!(facts.len(), 3);
+        assert_eq!(facts[0].entity_key, "programa_09_01_01");
+        assert_eq!(facts[0].entity_type, "programa");
+        assert_eq!(facts[0].metric_key, "presupuesto_ley_gasto_linea");
+        assert_eq!(facts[0].value_num, dec!(100000) * Decimal::ONE_THOUSAND);
+        assert_eq!(facts[0].location, "dipres_ley_csv:line=2");
+        assert_eq!(facts[2].entity_key, "programa_09_09_01");
+        assert_eq!(facts[2].location, "dipres_ley_csv:line=4");
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_lines_classifier_dims() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   09;09;01;24;01;001;ALIMENTACION ESCOLAR;300000;0\n";
+
+        let facts = parse_dipres_ley_csv_lines(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+
+        assert_eq!(
+            facts[0].dims,
+            serde_json::json!({
+                "partida_code": "09",
+                "capitulo_code": "09",
+                "programa_code": "01",
+                "subtitulo_code": "24",
+                "item_code": "01",
+                "asignacion_code": "001",
+                "classifier_code": "24.01.001",
+                "kind": "gasto",
+                "consolidation_rule": null,
+                "denominacion": "ALIMENTACION ESCOLAR",
+            })
+        );
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_lines_parent_chain() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   09;09;01;24;01;001;ALIMENTACION ESCOLAR;300000;0\n";
+
+        let facts = parse_dipres_ley_csv_lines(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+        let parents = &facts[0].entity_parents;
+
+        assert_eq!(parents.len(), 2);
+        assert_eq!(parents[0].key, "partida_09");
+        assert_eq!(parents[0].name, "Ministerio de Educación");
+        assert_eq!(parents[0].entity_type, "partida");
+        assert_eq!(parents[1].key, "capitulo_09_09");
+        assert_eq!(parents[1].entity_type, "capitulo");
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_lines_ingreso_metric() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   09;01;01;09;01;000;LIBRE;450000;0\n";
+
+        let facts = parse_dipres_ley_csv_lines(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+
+        assert_eq!(facts[0].metric_key, "presupuesto_ley_ingreso_linea");
+        assert_eq!(facts[0].dims["kind"], "ingreso");
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_lines_determinism() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;01;01;21;00;000;ITEM A;100000;0\n\
+                   02;01;01;21;00;000;ITEM B;200000;0\n";
+
+        let result1 = parse_dipres_ley_csv_lines(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+        let result2 = parse_dipres_ley_csv_lines(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+
+        assert_eq!(result1, result2);
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_lines_unknown_subtitulo_fails() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   09;01;01;27;00;000;NO EXISTE;100000;0\n";
+
+        let result = parse_dipres_ley_csv_lines(csv, "dipres-ley-presupuestos-2026");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("AMBIGUITY"));
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_lines_missing_programa_fails() {
+        let csv = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   09;01;;21;00;000;GASTOS EN PERSONAL;100000;0\n";
+
+        let result = parse_dipres_ley_csv_lines(csv, "dipres-ley-presupuestos-2026");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("AMBIGUITY"));
+    }
+
+    #[test]
+    fn test_is_dipres_ley_csv() {
+        assert!(is_dipres_ley_csv("dipres-ley-presupuestos-2026"));
+        assert!(is_dipres_ley_csv("dipres-ley-presupuestos-2025"));
+        assert!(!is_dipres_ley_csv("dipres-presupuesto-2026"));
+        assert!(!is_dipres_ley_csv("demo-presupuesto"));
+    }
+
+    // ==========================================================================
+    // Encoding and Header Normalization Tests
+    // ==========================================================================
+
+    #[test]
+    fn test_normalize_header_no_accents() {
+        assert_eq!(normalize::clean_label("Partida"), "Partida");
+        assert_eq!(normalize::clean_label("Monto Pesos"), "Monto Pesos");
+    }
+
+    #[test]
+    fn test_normalize_header_with_accents() {
+        assert_eq!(normalize::clean_label("Capítulo"), "Capitulo");
+        assert_eq!(normalize::clean_label("Subtítulo"), "Subtitulo");
+        assert_eq!(normalize::clean_label("Ítem"), "Item");
+        assert_eq!(normalize::clean_label("Asignación"), "Asignacion");
+        assert_eq!(normalize::clean_label("Denominación"), "Denominacion");
+        assert_eq!(normalize::clean_label("Monto Dólar"), "Monto Dolar");
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_headers_case_and_spacing_tolerated() {
+        let csv = "PARTIDA;Capítulo;Programa;SUBTÍTULO;Item;Asignación;Denominación;Monto\u{a0}Pesos;Monto  Dólar\n\
+                   01;01;01;21;00;000;GASTOS EN PERSONAL;100;0\n";
+        let facts = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2026").unwrap().facts;
+        assert_eq!(facts.len(), 1);
+    }
+
+    #[test]
+    fn test_normalize_header_trims_whitespace() {
+        assert_eq!(normalize::clean_label("  Partida  "), "Partida");
+        assert_eq!(normalize::clean_label("\tCapítulo\t"), "Capitulo");
+    }
+
+    #[test]
+    fn test_decode_artifact_uses_declared_encoding() {
+        // "Capítulo" in Windows-1252, declared latin-1 for 2020
+        let latin1 = [b'C', b'a', b'p', 0xED, b't', b'u', b'l', b'o'];
+        let decoded = decode_artifact(&latin1, "dipres-ley-presupuestos-2020").unwrap();
+        assert_eq!(decoded.text, "Capítulo");
+
+        // 2021 is declared utf-8-bom: Windows-1252 bytes halt
+        let err = decode_artifact(&latin1, "dipres-ley-presupuestos-2021")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("AMBIGUITY"));
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_with_accented_headers() {
+        // Test that accented headers work (like 2021 file format)
+        let csv = "\u{feff}Partida;Capítulo;Programa;Subtítulo;Ítem;Asignación;Denominación;Monto Pesos;Monto Dólar\n\
+                   01;01;01;21;01;001;GASTOS EN PERSONAL;1000000;100\n";
+
+        let facts = parse_dipres_ley_csv(csv, "dipres-ley-presupuestos-2021").unwrap().facts;
+        assert_eq!(facts.len(), 2); // CLP + USD series
+        assert_eq!(facts[0].period_start.year(), 2021);
+    }
+
+    #[test]
+    fn test_dipres_ley_csv_bytes_utf8() {
+        let csv = "\u{feff}Partida;Capitulo;Programa;Subtitulo;Item;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   01;01;01;21;01;001;GASTOS EN PERSONAL;1000000;100\n";
+
+        let decoded = decode_artifact(csv.as_bytes(), "dipres-ley-presupuestos-2026").unwrap();
+        let output =
+            parse_dipres_ley_decoded(&decoded, "dipres-ley-presupuestos-2026", DipresLeyMode::ByPartida)
+                .unwrap();
+        assert_eq!(output.facts.len(), 2); // CLP + USD series
+        assert_eq!(output.report.encoding.as_deref(), Some("utf-8-bom"));
+        assert_eq!(output.report.declared_encoding.as_deref(), Some("utf-8-bom"));
+    }
+}
@@ -0,0 +1,275 @@
+//! DIPRES Presupuesto workbooks (XLS/XLSX) - DIPRES budget format only
+//!
+//! The table is located through the layout declared for the source in
+//! config/xls_layouts.json and its columns mapped by the `dipres_xls_v1`
+//! parser spec in config/sources.json. Not a general XLS parser.
+
+use crate::crosswalk::{crosswalk_entity, EntityCrosswalk, ResolutionLog};
+use crate::numeric;
+use crate::registry::{ArtifactInput, Parser};
+use crate::report::ParseReport;
+use crate::sources::{column_for, SourceRegistry};
+use crate::xls_layout::XlsLayouts;
+use crate::{is_excel_file, ParseOutput, ParsedFact};
+use anyhow::{Context, Result};
+use calamine::{open_workbook_auto_from_rs, Data, Reader};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::io::Cursor;
+
+/// Parser identifier for DIPRES XLS files
+pub const PARSER_ID: &str = "dipres_xls_v1";
+
+/// Parse DIPRES XLS file into facts
+/// This function is DETERMINISTIC: same XLS file = same output
+/// Only supports DIPRES budget format - not a general XLS parser
+pub fn parse_dipres_xls(bytes: &[u8], source_id: &str) -> Result<ParseOutput> {
+    // Open workbook (calamine auto-detects format: xls, xlsx, xlsb, ods)
+    let mut workbook: calamine::Sheets<_> = open_workbook_auto_from_rs(Cursor::new(bytes))
+        .context("Failed to open XLS file")?;
+
+    // Locate the table with the layout declared for the source
+    let layouts = XlsLayouts::embedded()?;
+    let layout = layouts.for_source(source_id);
+    println!(
+        "XLS layout: {} (xls_layouts v{})",
+        if layout.source_family.is_empty() { "default" } else { &layout.source_family },
+        layouts.version
+    );
+    let sheet_names = workbook.sheet_names().to_vec();
+    if sheet_names.is_empty() {
+        anyhow::bail!("XLS file has no sheets");
+    }
+
+    let sheet_name = layout.select_sheet(&sheet_names)?;
+    println!("Reading sheet: '{}' ({} sheets)", sheet_name, sheet_names.len());
+
+    // Get the range (all cells in the sheet)
+    let range = workbook
+        .worksheet_range(&sheet_name)
+        .context("Failed to read sheet")?;
+
+    let (row_count, col_count) = range.get_size();
+    println!("Sheet size: {} rows x {} columns", row_count, col_count);
+
+    let table = layout.extract(&sheet_name, &range)?;
+    if table.rows.is_empty() {
+        anyhow::bail!("Sheet has insufficient rows (need header + data)");
+    }
+    println!(
+        "Header rows: {}-{}, data rows: {}, footnote rows: {}",
+        table.header_rows.0,
+        table.header_rows.1,
+        table.rows.len(),
+        table.footnote_rows
+    );
+    let headers = &table.headers;
+
+    println!("\nDetected columns ({}):", headers.len());
+    for (i, h) in headers.iter().enumerate() {
+        if !h.is_empty() {
+            println!("  [{:2}] {}", i, h);
+        }
+    }
+
+    // Map columns by exact header name from the parser spec in sources.json
+    let mapping = SourceRegistry::embedded()?
+        .parser(PARSER_ID)?
+        .map_columns(headers)?;
+
+    println!("\nColumn mapping:");
+    for choice in &mapping {
+        println!("  {:<9} [{:2}] {}", choice.role, choice.column, choice.header);
+    }
+
+    // Required roles are enforced by map_columns
+    let entity_col = column_for(&mapping, "entity").context("No entity column")?;
+    let amount_col = column_for(&mapping, "amount").context("No amount column")?;
+    let year_col = column_for(&mapping, "year");
+    let category_col = column_for(&mapping, "category");
+
+    // Year column is optional - we may use a fixed year from source_id
+    let fixed_year: Option<i32> = if year_col.is_none() {
+        // Try to extract year from source_id (e.g., "dipres-presupuesto-ley-2024")
+        source_id
+            .split('-')
+            .filter_map(|s| s.parse::<i32>().ok())
+            .find(|y| (2000..=2100).contains(y))
+    } else {
+        None
+    };
+
+    if year_col.is_none() && fixed_year.is_none() {
+        anyhow::bail!(
+            "AMBIGUITY: No year column found and cannot extract year from source_id '{}'",
+            source_id
+        );
+    }
+
+    println!("\nParsing data rows...");
+
+    let mut facts = Vec::new();
+    let mut report = ParseReport {
+        column_mapping: mapping,
+        ..Default::default()
+    };
+    let crosswalk = EntityCrosswalk::embedded()?;
+    let mut entities = ResolutionLog::default();
+
+    for table_row in &table.rows {
+        let (line, row) = (table_row.row as usize, &table_row.cells);
+
+        // Extract entity (rows without one are blank/heading rows)
+        let entity = match row.get(entity_col) {
+            Some(Data::String(s)) if !s.trim().is_empty() => s.trim().to_string(),
+            _ => {
+                report.skip();
+                continue;
+            }
+        };
+
+        // Extract year
+        let year: i32 = if let Some(year_col) = year_col {
+            match row.get(year_col) {
+                Some(Data::Float(f)) => *f as i32,
+                Some(Data::Int(i)) => *i as i32,
+                Some(Data::String(s)) => s.trim().parse().unwrap_or(0),
+                _ => fixed_year.unwrap_or(0),
+            }
+        } else {
+            fixed_year.unwrap_or(0)
+        };
+
+        if !(2000..=2100).contains(&year) {
+            report.reject(line, format!("Invalid year {} for entity '{}'", year, entity));
+            continue;
+        }
+
+        // Extract amount
+        // Excel stores numbers as f64; Decimal::try_from keeps the shortest
+        // representation (0.1 -> 0.1), so no binary noise reaches the database
+        let amount: Decimal = match row.get(amount_col) {
+            Some(Data::Float(f)) => match Decimal::try_from(*f) {
+                Ok(d) => d,
+                Err(_) => {
+                    report.reject(line, format!("Amount {} is not representable", f));
+                    continue;
+                }
+            },
+            Some(Data::Int(i)) => Decimal::from(*i),
+            Some(Data::String(s)) => match numeric::parse(s) {
+                Ok(d) => d,
+                Err(reason) => {
+                    report.reject(line, format!("Invalid amount {}", reason));
+                    continue;
+                }
+            },
+            Some(Data::Empty) | None => {
+                report.skip();
+                continue;
+            }
+            Some(other) => {
+                report.reject(line, format!("Unexpected amount cell {:?}", other));
+                continue;
+            }
+        };
+
+        if amount.is_zero() {
+            report.skip();
+            continue;
+        }
+
+        // Extract category (optional)
+        let category: Option<String> = category_col.and_then(|col| {
+            match row.get(col) {
+                Some(Data::String(s)) if !s.trim().is_empty() => Some(s.trim().to_string()),
+                _ => None,
+            }
+        });
+
+
+        // Create period dates
+        let period_start = NaiveDate::from_ymd_opt(year, 1, 1)
+            .context("Invalid year for period_start")?;
+        let period_end = NaiveDate::from_ymd_opt(year, 12, 31)
+            .context("Invalid year for period_end")?;
+
+        // Build dimensions
+        let mut dims = match &category {
+            Some(cat) => serde_json::json!({ "category": cat }),
+            None => serde_json::json!({}),
+        };
+
+        let Some(entity) =
+            crosswalk_entity(&crosswalk, &mut entities, source_id, year, &entity, &mut dims)
+        else {
+            report.reject(line, format!("Entity '{}' yields an empty key", entity));
+            continue;
+        };
+
+        // Determine metric based on source
+        let (metric_key, metric_name) = if source_id.contains("presupuesto") {
+            ("presupuesto_ley", "Presupuesto de Ley")
+        } else if source_id.contains("gasto") {
+            ("gasto_ejecutado", "Gasto Ejecutado")
+        } else {
+            ("monto", "Monto")
+        };
+
+        facts.push(ParsedFact {
+            entity_key: entity.key,
+            entity_name: entity.name,
+            entity_type: entity.entity_type,
+            entity_parents: Vec::new(),
+            metric_key: metric_key.to_string(),
+            metric_name: metric_name.to_string(),
+            metric_unit: "CLP".to_string(),
+            period_start,
+            period_end,
+            value_num: amount,
+            location: format!(
+                "xls:sheet='{}':cell={}",
+                table.sheet,
+                table.cell_ref(table_row.row, amount_col)
+            ),
+            dims,
+        });
+        report.accept();
+    }
+
+    println!(
+        "Parsed {} facts, skipped {} rows, rejected {} rows",
+        facts.len(),
+        report.skipped_rows,
+        report.rejects.len()
+    );
+
+    if facts.is_empty() {
+        anyhow::bail!("No facts parsed from XLS file - check column mapping");
+    }
+
+    Ok(ParseOutput {
+        facts,
+        report,
+        entities: entities.into_vec(),
+        linked_entities: Vec::new(),
+    })
+}
+
+
+/// Any Excel artifact not claimed by a more specific parser
+pub struct DipresXlsParser;
+
+impl Parser for DipresXlsParser {
+    fn id(&self) -> &'static str {
+        PARSER_ID
+    }
+
+    fn accepts(&self, input: &ArtifactInput) -> bool {
+        is_excel_file(input.mime_type, input.storage_path)
+    }
+
+    fn parse(&self, input: &ArtifactInput) -> Result<ParseOutput> {
+        parse_dipres_xls(input.bytes, input.source_id)
+    }
+}
@@ -0,0 +1,371 @@
+//! Transparencia Activa staffing files: dotación and remuneraciones
+//!
+//! Every organism publishes, month by month, one row per person hired under
+//! each contract type (planta, contrata, honorarios). The bulk export comes as
+//! one file per contract type. Two metrics per organism, month and contract
+//! type, never summed with each other (PRINCIPLES.md #4, Dotación ≠
+//! Remuneraciones):
+//! - `dotacion_personas`: rows reported (people), unit `personas`
+//! - `remuneracion_bruta_mensual`: sum of the gross monthly pay, CLP
+//!
+//! Only aggregates leave the parser: names and other personal columns are not
+//! mapped and never read. A row with an unreadable amount is rejected from
+//! both metrics so they always describe the same rows; a row with no amount
+//! counts as a person and is reported in `dims.sin_monto`.
+
+use crate::numeric;
+use crate::report::ParseReport;
+use crate::sources::{column_for, SourceRegistry};
+use crate::registry::{ArtifactInput, Parser};
+use crate::{decode_artifact, with_encoding, ParseOutput, ParsedFact};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+/// Parser identifier for Transparencia Activa staffing files
+pub const PARSER_ID: &str = "transparencia_dotacion_csv_v1";
+
+/// Source id prefix, e.g. "transparencia-dotacion-contrata-2025"
+const SOURCE_PREFIX: &str = "transparencia-dotacion-";
+
+/// Month names as written in the files (folded)
+const MONTHS: [&str; 12] = [
+    "enero", "febrero", "marzo", "abril", "mayo", "junio", "julio", "agosto", "septiembre",
+    "octubre", "noviembre", "diciembre",
+];
+
+/// Monetary units read as Chilean pesos (folded)
+const PESO_LABELS: &[&str] = &["pesos", "peso", "clp", "peso chileno", "pesos chilenos"];
+
+/// Contract type of a staffing file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Contract {
+    Planta,
+    Contrata,
+    Honorarios,
+}
+
+impl Contract {
+    /// Contract type declared by the source id; None for other sources
+    pub fn from_source_id(source_id: &str) -> Option<Self> {
+        let rest = source_id.strip_prefix(SOURCE_PREFIX)?;
+        match rest.split('-').next()? {
+            "planta" => Some(Contract::Planta),
+            "contrata" => Some(Contract::Contrata),
+            "honorarios" => Some(Contract::Honorarios),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Contract::Planta => "planta",
+            Contract::Contrata => "contrata",
+            Contract::Honorarios => "honorarios",
+        }
+    }
+}
+
+/// Transparencia Activa staffing sources, whatever their contract type
+pub fn is_dotacion_source(source_id: &str) -> bool {
+    source_id.starts_with(SOURCE_PREFIX)
+}
+
+/// Month of the "Mes" column: a name ("Enero") or a number ("1", "01")
+fn month(text: &str) -> Result<u32, String> {
+    let folded = crate::normalize::fold(text);
+    if let Some(i) = MONTHS.iter().position(|m| *m == folded) {
+        return Ok(i as u32 + 1);
+    }
+    match folded.parse::<u32>() {
+        Ok(m) if (1..=12).contains(&m) => Ok(m),
+        _ => Err(format!("Invalid month '{}'", text.trim())),
+    }
+}
+
+/// One organism, month and contract type
+#[derive(Default)]
+struct Group {
+    organismo_name: String,
+    first_line: usize,
+    people: u32,
+    without_amount: u32,
+    gross_pay: Decimal,
+}
+
+/// Parse a Transparencia Activa staffing file (decoded CSV text)
+/// This function is DETERMINISTIC: same input = same output
+pub fn parse_dotacion(content: &str, source_id: &str) -> Result<ParseOutput> {
+    let contract = Contract::from_source_id(source_id).with_context(|| {
+        format!(
+            "AMBIGUITY: Source '{}' does not declare a contract type (planta, contrata, honorarios)",
+            source_id
+        )
+    })?;
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .context("Failed to read CSV header")?
+        .iter()
+        .map(str::to_string)
+        .collect();
+    let mapping = SourceRegistry::embedded()?.parser(PARSER_ID)?.map_columns(&headers)?;
+    let col = |role: &str| column_for(&mapping, role).with_context(|| format!("No {} column", role));
+    let [codigo_col, nombre_col, anio_col, mes_col, unidad_col, monto_col] = [
+        col("organismo_codigo")?,
+        col("organismo_nombre")?,
+        col("anio")?,
+        col("mes")?,
+        col("unidad")?,
+        col("remuneracion")?,
+    ];
+
+    let mut report = ParseReport {
+        column_mapping: mapping.clone(),
+        ..Default::default()
+    };
+    // (organismo code, year, month) -> group
+    let mut groups: BTreeMap<(String, i32, u32), Group> = BTreeMap::new();
+
+    for (line_num, result) in reader.records().enumerate() {
+        let line = line_num + 2;
+        let record = match result {
+            Ok(r) => r,
+            Err(e) => {
+                report.reject(line, e.to_string());
+                continue;
+            }
+        };
+        let cell = |col: usize| record.get(col).unwrap_or_default();
+
+        let codigo = cell(codigo_col);
+        if codigo.is_empty() {
+            report.reject(line, "Missing organism code");
+            continue;
+        }
+        let Ok(year) = cell(anio_col).parse::<i32>() else {
+            report.reject(line, format!("Invalid year '{}'", cell(anio_col)));
+            continue;
+        };
+        let month = match month(cell(mes_col)) {
+            Ok(m) => m,
+            Err(reason) => {
+                report.reject(line, reason);
+                continue;
+            }
+        };
+        let amount = match cell(monto_col) {
+            "" => None,
+            text => {
+                let unit = crate::normalize::fold(cell(unidad_col));
+                if !PESO_LABELS.contains(&unit.as_str()) {
+                    report.reject(line, format!("Pay in '{}' is not converted to CLP", cell(unidad_col)));
+                    continue;
+                }
+                match numeric::parse(text) {
+                    Ok(v) => Some(v),
+                    Err(reason) => {
+                        report.reject(line, format!("Invalid amount {}", reason));
+                        continue;
+                    }
+                }
+            }
+        };
+
+        let group = groups.entry((codigo.to_string(), year, month)).or_insert_with(|| Group {
+            organismo_name: cell(nombre_col).to_string(),
+            first_line: line,
+            ..Default::default()
+        });
+        group.people += 1;
+        match amount {
+            Some(v) => group.gross_pay += v,
+            None => group.without_amount += 1,
+        }
+        report.accept();
+    }
+
+    let mut facts = Vec::new();
+    for ((codigo, year, month), group) in &groups {
+        let period_start = NaiveDate::from_ymd_opt(*year, *month, 1).context("Invalid period")?;
+        let period_end = match month {
+            12 => NaiveDate::from_ymd_opt(year + 1, 1, 1),
+            _ => NaiveDate::from_ymd_opt(*year, month + 1, 1),
+        }
+        .and_then(|d| d.pred_opt())
+        .context("Invalid period")?;
+        let dims = serde_json::json!({
+            "organismo_code": codigo,
+            "contract_type": contract.as_str(),
+            "month": month,
+            "personas": group.people,
+            "sin_monto": group.without_amount,
+        });
+        let fact = |metric_key: &str, metric_name: &str, unit: &str, value: Decimal| ParsedFact {
+            entity_key: format!("organismo_ta_{}", crate::normalize::slug(codigo)),
+            entity_name: group.organismo_name.clone(),
+            entity_type: "organismo".to_string(),
+            entity_parents: Vec::new(),
+            metric_key: metric_key.to_string(),
+            metric_name: metric_name.to_string(),
+            metric_unit: unit.to_string(),
+            period_start,
+            period_end,
+            value_num: value,
+            location: format!("csv:line={}", group.first_line),
+            dims: dims.clone(),
+        };
+        facts.push(fact(
+            "dotacion_personas",
+            "Dotación - Personas",
+            "personas",
+            Decimal::from(group.people),
+        ));
+        if group.people > group.without_amount {
+            facts.push(fact(
+                "remuneracion_bruta_mensual",
+                "Remuneraciones - Bruta Mensual",
+                "CLP",
+                group.gross_pay,
+            ));
+        }
+    }
+
+    // Each metric must add up to the accepted rows it was built from
+    let people: Decimal = facts
+        .iter()
+        .filter(|f| f.metric_key == "dotacion_personas")
+        .map(|f| f.value_num)
+        .sum();
+    report.check_total("rows_vs_dotacion", Decimal::from(report.accepted_rows), people);
+    let pay: Decimal = groups.values().map(|g| g.gross_pay).sum();
+    let pay_facts: Decimal = facts
+        .iter()
+        .filter(|f| f.metric_key == "remuneracion_bruta_mensual")
+        .map(|f| f.value_num)
+        .sum();
+    report.check_total("rows_vs_remuneracion_clp", pay, pay_facts);
+
+    println!(
+        "Created {} dotación / remuneración facts ({} organism-months, {})",
+        facts.len(),
+        groups.len(),
+        contract.as_str()
+    );
+
+    Ok(ParseOutput {
+        facts,
+        report,
+        entities: Vec::new(),
+        linked_entities: Vec::new(),
+    })
+}
+
+/// Transparencia Activa staffing file: headcount and pay, separately
+pub struct DotacionParser;
+
+impl Parser for DotacionParser {
+    fn id(&self) -> &'static str {
+        PARSER_ID
+    }
+
+    fn accepts(&self, input: &ArtifactInput) -> bool {
+        is_dotacion_source(input.source_id)
+    }
+
+    fn parse(&self, input: &ArtifactInput) -> Result<ParseOutput> {
+        let decoded = decode_artifact(input.bytes, input.source_id)?;
+        Ok(with_encoding(parse_dotacion(&decoded.text, input.source_id)?, &decoded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture;
+    use rust_decimal_macros::dec;
+
+    const HEADER: &str = "organismo_nombre;organismo_codigo;anyo;Mes;Nombres;tipo_unidad_monetaria;remuneracionbruta_mensual";
+
+    #[test]
+    fn test_fixture_headcount_and_pay_are_separate_metrics() {
+        let output = parse_dotacion(
+            &String::from_utf8(fixture("transparencia_dotacion_contrata_2025.csv")).unwrap(),
+            "transparencia-dotacion-contrata-2025",
+        )
+        .unwrap();
+        let report = &output.report;
+        assert!(report.enforce(0.0).is_ok(), "{:?} {:?}", report.rejects, report.checks);
+        assert_eq!(report.accepted_rows, 7);
+
+        let find = |metric: &str, org: &str, month: u32| {
+            output
+                .facts
+                .iter()
+                .find(|f| f.metric_key == metric && f.entity_key == org && f.dims["month"] == month)
+        };
+        let people = find("dotacion_personas", "organismo_ta_ab001", 1).unwrap();
+        assert_eq!(people.value_num, dec!(3));
+        assert_eq!(people.metric_unit, "personas");
+        assert_eq!(people.entity_name, "Subsecretaría de Educación");
+        assert_eq!(people.period_end, NaiveDate::from_ymd_opt(2025, 1, 31).unwrap());
+        assert_eq!(people.dims["contract_type"], "contrata");
+        assert_eq!(people.location, "csv:line=2");
+
+        let pay = find("remuneracion_bruta_mensual", "organismo_ta_ab001", 1).unwrap();
+        assert_eq!(pay.value_num, dec!(5150000.5));
+        assert_eq!(pay.metric_unit, "CLP");
+
+        // February: two people, one without a reported amount
+        let february = find("remuneracion_bruta_mensual", "organismo_ta_ab001", 2).unwrap();
+        assert_eq!(february.value_num, dec!(1800000));
+        assert_eq!(february.dims["sin_monto"], 1);
+        assert_eq!(find("dotacion_personas", "organismo_ta_ab001", 2).unwrap().value_num, dec!(2));
+
+        // Nobody with an amount: headcount only
+        assert!(find("dotacion_personas", "organismo_ta_ad010", 1).is_some());
+        assert!(find("remuneracion_bruta_mensual", "organismo_ta_ad010", 1).is_none());
+
+        // Personal columns are never copied
+        assert!(output.facts.iter().all(|f| f.dims.get("Nombres").is_none()));
+    }
+
+    #[test]
+    fn test_bad_rows_leave_both_metrics() {
+        let csv = format!(
+            "{}\nSubsecretaría;AB001;2025;Enero;Ana;Pesos;1.234\nSubsecretaría;AB001;2025;Enero;Luis;UF;40\nSubsecretaría;AB001;2025;Trece;Eva;Pesos;100\nSubsecretaría;AB001;2025;1;Juan;Pesos;1000\n",
+            HEADER
+        );
+        let output = parse_dotacion(&csv, "transparencia-dotacion-planta-2025").unwrap();
+        let reasons: Vec<&str> = output.report.rejects.iter().map(|r| r.reason.as_str()).collect();
+        assert!(reasons[0].contains("ambiguous"));
+        assert!(reasons[1].contains("'UF' is not converted"));
+        assert!(reasons[2].contains("Invalid month"));
+        assert_eq!(output.facts.len(), 2);
+        assert!(output.facts.iter().all(|f| f.dims["personas"] == 1));
+    }
+
+    #[test]
+    fn test_contract_type_comes_from_source_id() {
+        assert_eq!(
+            Contract::from_source_id("transparencia-dotacion-honorarios-2024"),
+            Some(Contract::Honorarios)
+        );
+        assert_eq!(Contract::from_source_id("dipres-dotacion-2024"), None);
+        let err = parse_dotacion(HEADER, "transparencia-dotacion-2025").unwrap_err().to_string();
+        assert!(err.contains("AMBIGUITY") && err.contains("contract type"));
+    }
+
+    #[test]
+    fn test_month_names_and_numbers() {
+        assert_eq!(month("Septiembre"), Ok(9));
+        assert_eq!(month("03"), Ok(3));
+        assert!(month("13").is_err());
+    }
+}
@@ -0,0 +1,524 @@
+//! DIPRES Ejecución Presupuestaria Mensual (monthly budget execution)
+//!
+//! A separate domain from the Ley de Presupuestos (PRINCIPLES.md #4): the Ley
+//! says what was approved for the year, the execution report says what the
+//! budget is today (vigente, after decree modifications) and what was accrued
+//! (devengado) and paid (pagado) each month. Each is its own lifecycle stage
+//! (config/ciclo_presupuestario.json) with its own metric:
+//! `presupuesto_vigente_gasto` (annual), `ejecucion_devengado` and
+//! `ejecucion_pagado` (monthly). None is ever summed with another or with
+//! `presupuesto_ley_*`.
+//!
+//! The workbook is a report: a cover sheet, the data sheet under a title block
+//! that states the unit ("Miles de $"), a two-row header ("Devengado" over the
+//! month names) and footnotes. Its layout comes from config/xls_layouts.json
+//! and its columns from the parser spec in config/sources.json.
+//!
+//! Rows:
+//! - Partida + Capítulo + Programa: a programa, one fact per reported value
+//! - Partida without Programa: a Partida or Capítulo total, checked against
+//!   the programas under it instead of emitted (control row)
+//! - no Partida: blank or heading, skipped
+//!
+//! Months without a value are not reported yet (files are cumulative during
+//! the year); a programa appearing twice halts the parser.
+
+use crate::numeric;
+use crate::partidas::PartidaCatalog;
+use crate::report::ParseReport;
+use crate::sources::{column_for, SourceRegistry};
+use crate::xls_layout::{cell_text, SheetTable, XlsLayouts};
+use crate::registry::{ArtifactInput, Parser};
+use crate::{is_excel_file, unit_scale, year_period, ParseOutput, ParsedEntity, ParsedFact};
+use anyhow::{Context, Result};
+use calamine::{open_workbook_auto_from_rs, Data, Reader};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::io::Cursor;
+
+/// Parser identifier for DIPRES Ejecución Mensual workbooks
+pub const PARSER_ID: &str = "dipres_ejecucion_xls_v1";
+
+/// What a value column measures: the annual vigente or one month of a monthly stage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Measure {
+    Vigente,
+    Devengado(u32),
+    Pagado(u32),
+}
+
+impl Measure {
+    /// Column role in the parser spec, also used in check names
+    fn role(self) -> String {
+        match self {
+            Measure::Vigente => "vigente".to_string(),
+            Measure::Devengado(month) => format!("devengado_{:02}", month),
+            Measure::Pagado(month) => format!("pagado_{:02}", month),
+        }
+    }
+
+    /// (metric_key, metric_name)
+    fn metric(self) -> (&'static str, &'static str) {
+        match self {
+            Measure::Vigente => ("presupuesto_vigente_gasto", "Presupuesto Vigente - Gastos"),
+            Measure::Devengado(_) => ("ejecucion_devengado", "Ejecución Presupuestaria - Devengado"),
+            Measure::Pagado(_) => ("ejecucion_pagado", "Ejecución Presupuestaria - Pagado"),
+        }
+    }
+
+    fn month(self) -> Option<u32> {
+        match self {
+            Measure::Vigente => None,
+            Measure::Devengado(month) | Measure::Pagado(month) => Some(month),
+        }
+    }
+
+    /// Every measure the spec can map, vigente first
+    fn all() -> impl Iterator<Item = Measure> {
+        std::iter::once(Measure::Vigente)
+            .chain((1..=12).map(Measure::Devengado))
+            .chain((1..=12).map(Measure::Pagado))
+    }
+}
+
+/// Source ids of the family, e.g. "dipres-ejecucion-mensual-2025"
+pub fn is_dipres_ejecucion(source_id: &str) -> bool {
+    source_id.starts_with("dipres-ejecucion-mensual")
+}
+
+/// Parse a DIPRES Ejecución Mensual workbook
+/// This function is DETERMINISTIC: same workbook = same output
+pub fn parse_dipres_ejecucion(bytes: &[u8], source_id: &str) -> Result<ParseOutput> {
+    let mut workbook: calamine::Sheets<_> =
+        open_workbook_auto_from_rs(Cursor::new(bytes)).context("Failed to open XLS file")?;
+    let layout = XlsLayouts::embedded()?.for_source(source_id);
+    if layout.source_family.is_empty() {
+        anyhow::bail!("No XLS layout for source '{}'", source_id);
+    }
+
+    let sheet = layout.select_sheet(&workbook.sheet_names())?;
+    println!("Reading sheet: '{}'", sheet);
+    let range = workbook
+        .worksheet_range(&sheet)
+        .context("Failed to read sheet")?;
+    let table = layout.extract(&sheet, &range)?;
+
+    parse_ejecucion_table(&table, source_id)
+}
+
+/// Institutional code cell: numbers or text, zero-padded to two digits
+/// None when empty
+fn code(cell: Option<&Data>) -> Result<Option<String>, String> {
+    let text = match cell {
+        Some(Data::Float(f)) if f.fract() == 0.0 => format!("{}", *f as i64),
+        Some(Data::Int(i)) => i.to_string(),
+        other => cell_text(other),
+    };
+    if text.is_empty() {
+        return Ok(None);
+    }
+    if text.len() > 2 || !text.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid code '{}'", text));
+    }
+    Ok(Some(format!("{:0>2}", text)))
+}
+
+/// Amount cell in the sheet unit; None when empty (not reported yet)
+fn amount(cell: Option<&Data>) -> Result<Option<Decimal>, String> {
+    match cell {
+        None | Some(Data::Empty) => Ok(None),
+        Some(Data::Float(f)) => Decimal::try_from(*f)
+            .map(Some)
+            .map_err(|_| format!("Amount {} is not representable", f)),
+        Some(Data::Int(i)) => Ok(Some(Decimal::from(*i))),
+        Some(Data::String(s)) if s.trim().is_empty() => Ok(None),
+        Some(Data::String(s)) => numeric::parse(s).map(Some),
+        Some(other) => Err(format!("Unexpected amount cell {:?}", other)),
+    }
+}
+
+/// One programa row: amounts in the sheet unit
+struct ProgramaRow {
+    row: u32,
+    denominacion: String,
+    /// (measure, table column, amount)
+    values: Vec<(Measure, usize, Decimal)>,
+}
+
+/// A Partida ("PP") or Capítulo ("PP.CC") total row: (measure, amount) in the sheet unit
+struct ControlRow {
+    row: u32,
+    key: String,
+    values: Vec<(Measure, Decimal)>,
+}
+
+/// Parse the located table of an execution workbook
+fn parse_ejecucion_table(table: &SheetTable, source_id: &str) -> Result<ParseOutput> {
+    let year = crate::fiscal_year_from_source_id(source_id).with_context(|| {
+        format!("AMBIGUITY: Cannot extract fiscal year from source_id '{}'", source_id)
+    })?;
+    let scale = unit_scale(&table.title)?;
+
+    let mapping = SourceRegistry::embedded()?
+        .parser(PARSER_ID)?
+        .map_columns(&table.headers)?;
+    let partida_col = column_for(&mapping, "partida").context("No partida column")?;
+    let capitulo_col = column_for(&mapping, "capitulo").context("No capitulo column")?;
+    let programa_col = column_for(&mapping, "programa").context("No programa column")?;
+    let denominacion_col = column_for(&mapping, "denominacion").context("No denominacion column")?;
+    let columns: Vec<(Measure, usize)> = Measure::all()
+        .filter_map(|m| column_for(&mapping, &m.role()).map(|col| (m, col)))
+        .collect();
+    if !columns.iter().any(|(m, _)| m.month().is_some()) {
+        anyhow::bail!("AMBIGUITY: No month columns found in sheet '{}'", table.sheet);
+    }
+
+    let mut report = ParseReport {
+        column_mapping: mapping,
+        ..Default::default()
+    };
+
+    // "PP.CC.GG" -> row; control rows keyed by "PP" or "PP.CC"
+    let mut programas: BTreeMap<String, ProgramaRow> = BTreeMap::new();
+    let mut controls: Vec<ControlRow> = Vec::new();
+
+    'rows: for table_row in &table.rows {
+        let (row, cells) = (table_row.row, &table_row.cells);
+        let codes = [partida_col, capitulo_col, programa_col].map(|col| code(cells.get(col)));
+        let [partida, capitulo, programa] = match codes {
+            [Ok(p), Ok(c), Ok(g)] => [p, c, g],
+            _ => {
+                let reason = codes.into_iter().find_map(|c| c.err()).unwrap_or_default();
+                report.reject(row as usize, reason);
+                continue;
+            }
+        };
+        let Some(partida) = partida else {
+            report.skip();
+            continue;
+        };
+
+        let mut values = Vec::new();
+        for &(measure, col) in &columns {
+            match amount(cells.get(col)) {
+                Ok(Some(v)) => values.push((measure, col, v)),
+                Ok(None) => {}
+                Err(reason) => {
+                    report.reject(
+                        row as usize,
+                        format!("{}: {}", table.cell_ref(row, col), reason),
+                    );
+                    continue 'rows;
+                }
+            }
+        }
+
+        match (capitulo, programa) {
+            (Some(capitulo), Some(programa)) => {
+                let key = format!("{}.{}.{}", partida, capitulo, programa);
+                if let Some(previous) = programas.get(&key) {
+                    anyhow::bail!(
+                        "AMBIGUITY: Programa {} appears on rows {} and {}",
+                        key,
+                        previous.row,
+                        row
+                    );
+                }
+                programas.insert(
+                    key,
+                    ProgramaRow {
+                        row,
+                        denominacion: cell_text(cells.get(denominacion_col)),
+                        values,
+                    },
+                );
+            }
+            (capitulo, None) => {
+                let key = match capitulo {
+                    Some(c) => format!("{}.{}", partida, c),
+                    None => partida,
+                };
+                report.control();
+                controls.push(ControlRow {
+                    row,
+                    key,
+                    values: values.into_iter().map(|(m, _, v)| (m, v)).collect(),
+                });
+            }
+            (None, Some(_)) => {
+                report.reject(row as usize, "Programa without Capitulo");
+            }
+        }
+    }
+
+    check_control_rows(&mut report, &programas, &controls);
+
+    // The vigente is stated as of the last month the report covers
+    let as_of_month = programas
+        .values()
+        .flat_map(|p| p.values.iter().filter_map(|(m, _, _)| m.month()))
+        .max();
+
+    let partida_names = PartidaCatalog::embedded()?;
+    let mut facts = Vec::new();
+    for (key, programa) in &programas {
+        let [partida, capitulo, code] = [&key[0..2], &key[3..5], &key[6..8]];
+        let partida_name = partida_names.name(year, partida);
+        for &(measure, col, value) in &programa.values {
+            if value.is_zero() {
+                continue;
+            }
+            let (period_start, period_end) = match measure.month() {
+                Some(month) => month_period(year, month)?,
+                None => year_period(year)?,
+            };
+            let (metric_key, metric_name) = measure.metric();
+            facts.push(ParsedFact {
+                entity_key: format!("programa_{}_{}_{}", partida, capitulo, code),
+                entity_name: format!("{} / Capítulo {} / Programa {}", partida_name, capitulo, code),
+                entity_type: "programa".to_string(),
+                entity_parents: vec![
+                    ParsedEntity {
+                        key: format!("partida_{}", partida),
+                        name: partida_name.clone(),
+                        entity_type: "partida".to_string(),
+                    },
+                    ParsedEntity {
+                        key: format!("capitulo_{}_{}", partida, capitulo),
+                        name: format!("{} / Capítulo {}", partida_name, capitulo),
+                        entity_type: "capitulo".to_string(),
+                    },
+                ],
+                metric_key: metric_key.to_string(),
+                metric_name: metric_name.to_string(),
+                metric_unit: "CLP".to_string(),
+                period_start,
+                period_end,
+                value_num: value * scale,
+                location: format!(
+                    "xls:sheet='{}':cell={}",
+                    table.sheet,
+                    table.cell_ref(programa.row, col)
+                ),
+                dims: serde_json::json!({
+                    "partida_code": partida,
+                    "capitulo_code": capitulo,
+                    "programa_code": code,
+                    "denominacion": programa.denominacion,
+                    "month": measure.month(),
+                    "as_of_month": as_of_month,
+                }),
+            });
+        }
+        report.accept();
+    }
+
+    // Facts must add up to the programa rows they were built from
+    let expected: Decimal = programas
+        .values()
+        .flat_map(|p| p.values.iter().map(|(_, _, v)| *v * scale))
+        .sum();
+    let actual: Decimal = facts.iter().map(|f| f.value_num).sum();
+    report.check_total("rows_vs_facts_clp", expected, actual);
+
+    if facts.is_empty() {
+        anyhow::bail!("AMBIGUITY: No programa rows with amounts in sheet '{}'", table.sheet);
+    }
+    println!("Created {} execution facts", facts.len());
+
+    Ok(ParseOutput {
+        facts,
+        report,
+        entities: Vec::new(),
+        linked_entities: Vec::new(),
+    })
+}
+
+/// Each Partida / Capítulo total must equal, column by column, the sum of the
+/// programas under it
+fn check_control_rows(
+    report: &mut ParseReport,
+    programas: &BTreeMap<String, ProgramaRow>,
+    controls: &[ControlRow],
+) {
+    let (mut expected_all, mut actual_all) = (Decimal::ZERO, Decimal::ZERO);
+    for control in controls {
+        let prefix = format!("{}.", control.key);
+        let mut detail: BTreeMap<Measure, Decimal> = BTreeMap::new();
+        for (_, programa) in programas.iter().filter(|(k, _)| k.starts_with(&prefix)) {
+            for &(measure, _, value) in &programa.values {
+                *detail.entry(measure).or_default() += value;
+            }
+        }
+        let stated: BTreeMap<Measure, Decimal> = control.values.iter().copied().collect();
+        for measure in stated.keys().chain(detail.keys()).collect::<std::collections::BTreeSet<_>>() {
+            let expected = stated.get(measure).copied().unwrap_or_default();
+            let actual = detail.get(measure).copied().unwrap_or_default();
+            expected_all += expected;
+            actual_all += actual;
+            if expected != actual {
+                report.check_total(
+                    format!("control_total:row={}:code={}:{}", control.row, control.key, measure.role()),
+                    expected,
+                    actual,
+                );
+            }
+        }
+    }
+    if !controls.is_empty() {
+        report.check_total("control_totals", expected_all, actual_all);
+    }
+}
+
+/// First and last day of a month
+fn month_period(year: i32, month: u32) -> Result<(NaiveDate, NaiveDate)> {
+    let start = NaiveDate::from_ymd_opt(year, month, 1).context("Invalid month")?;
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    }
+    .context("Invalid month")?;
+    Ok((start, next.pred_opt().context("Invalid month")?))
+}
+
+/// Monthly execution workbook: its own domain and layout
+pub struct EjecucionParser;
+
+impl Parser for EjecucionParser {
+    fn id(&self) -> &'static str {
+        PARSER_ID
+    }
+
+    fn accepts(&self, input: &ArtifactInput) -> bool {
+        is_dipres_ejecucion(input.source_id)
+    }
+
+    /// Each monthly workbook restates the year so far
+    fn cumulative(&self) -> bool {
+        true
+    }
+
+    fn parse(&self, input: &ArtifactInput) -> Result<ParseOutput> {
+        if !is_excel_file(input.mime_type, input.storage_path) {
+            anyhow::bail!("AMBIGUITY: DIPRES Ejecución artifact is not an Excel workbook");
+        }
+        parse_dipres_ejecucion(input.bytes, input.source_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture;
+    use chrono::Datelike;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_fixture_monthly_facts() {
+        let output = parse_dipres_ejecucion(
+            &fixture("dipres_ejecucion_2025.xlsx"),
+            "dipres-ejecucion-mensual-2025",
+        )
+        .unwrap();
+        let report = &output.report;
+        assert!(report.enforce(0.0).is_ok(), "{:?}", report.checks);
+        assert_eq!(report.accepted_rows, 3);
+        assert_eq!(report.control_rows, 2);
+
+        let count = |metric: &str| output.facts.iter().filter(|f| f.metric_key == metric).count();
+        // Devengado: 6 + 5 (February is 0) + 3 (April onwards not reported yet)
+        assert_eq!(count("ejecucion_devengado"), 14);
+        // Pagado: 6 + 4 (February is 0, June not reported) + 3
+        assert_eq!(count("ejecucion_pagado"), 13);
+        assert_eq!(count("presupuesto_vigente_gasto"), 3);
+
+        let devengado = output
+            .facts
+            .iter()
+            .find(|f| f.metric_key == "ejecucion_devengado")
+            .unwrap();
+        assert_eq!(devengado.entity_key, "programa_09_01_01");
+        assert_eq!(devengado.entity_parents[0].key, "partida_09");
+        assert_eq!(devengado.period_start, NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+        assert_eq!(devengado.period_end, NaiveDate::from_ymd_opt(2025, 1, 31).unwrap());
+        assert_eq!(devengado.value_num, dec!(1000000));
+        assert_eq!(devengado.location, "xls:sheet='Ejecución Gastos':cell=F7");
+
+        let march = output
+            .facts
+            .iter()
+            .find(|f| {
+                f.entity_key == "programa_09_01_02"
+                    && f.metric_key == "ejecucion_devengado"
+                    && f.dims["month"] == 3
+            })
+            .unwrap();
+        assert_eq!(march.value_num, dec!(1250500));
+        assert_eq!(march.location, "xls:sheet='Ejecución Gastos':cell=H8");
+        assert_eq!(
+            output.facts.iter().filter(|f| f.period_end.month() == 2 && f.period_end.day() == 28).count(),
+            4
+        );
+
+        // Vigente is one annual amount, stated as of the last reported month
+        let vigente = output
+            .facts
+            .iter()
+            .find(|f| f.metric_key == "presupuesto_vigente_gasto" && f.entity_key == "programa_09_01_02")
+            .unwrap();
+        assert_eq!(vigente.value_num, dec!(5000000));
+        assert_eq!(vigente.period_start, NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+        assert_eq!(vigente.period_end, NaiveDate::from_ymd_opt(2025, 12, 31).unwrap());
+        assert_eq!(vigente.dims["month"], serde_json::Value::Null);
+        assert_eq!(vigente.dims["as_of_month"], 6);
+        assert_eq!(vigente.location, "xls:sheet='Ejecución Gastos':cell=E8");
+
+        let pagado = output
+            .facts
+            .iter()
+            .find(|f| f.metric_key == "ejecucion_pagado" && f.entity_key == "programa_09_01_02" && f.dims["month"] == 3)
+            .unwrap();
+        assert_eq!(pagado.value_num, dec!(1000000));
+        assert_eq!(pagado.location, "xls:sheet='Ejecución Gastos':cell=N8");
+
+        let roles: Vec<&str> = report.column_mapping.iter().map(|c| c.role.as_str()).collect();
+        assert!(roles.contains(&"vigente"));
+        assert!(roles.contains(&"devengado_06") && !roles.contains(&"devengado_07"));
+        assert!(roles.contains(&"pagado_06") && !roles.contains(&"pagado_07"));
+    }
+
+    #[test]
+    fn test_fixture_bad_control_total_halts() {
+        let output = parse_dipres_ejecucion(
+            &fixture("dipres_ejecucion_2025_bad_total.xlsx"),
+            "dipres-ejecucion-mensual-2025",
+        )
+        .unwrap();
+        let err = output.report.enforce(1.0).unwrap_err().to_string();
+        assert!(err.contains("control_total:row=6:code=09:devengado_03"), "{}", err);
+    }
+
+    #[test]
+    fn test_unit_must_be_stated() {
+        assert_eq!(unit_scale(&["Año 2025 - Miles de $".to_string()]).unwrap(), dec!(1000));
+        assert!(unit_scale(&["Año 2025".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_measure_roles_match_spec() {
+        let spec = SourceRegistry::embedded().unwrap().parser(PARSER_ID).unwrap().clone();
+        for measure in Measure::all() {
+            assert!(spec.column_roles.contains_key(&measure.role()), "{:?}", measure);
+        }
+        assert_eq!(spec.column_roles.len(), 4 + Measure::all().count());
+    }
+
+    #[test]
+    fn test_month_period() {
+        let (start, end) = month_period(2024, 2).unwrap();
+        assert_eq!((start.day(), end.day()), (1, 29));
+        assert_eq!(month_period(2025, 12).unwrap().1, NaiveDate::from_ymd_opt(2025, 12, 31).unwrap());
+    }
+}
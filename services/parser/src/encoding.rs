@@ -0,0 +1,196 @@
+//! Text encoding of CSV artifacts
+//!
+//! DIPRES alternates between Windows-1252 and UTF-8 with BOM from one fiscal
+//! year to the next, and config/sources.json declares which one each source
+//! uses (`"encoding": "latin-1"` / `"utf-8-bom"`). The declaration is checked
+//! against the bytes instead of trusted: a file that does not look like its
+//! declared encoding, or that decodes to replacement characters, halts the
+//! parser (PRINCIPLES.md #3). Sources without a declaration are detected
+//! (BOM → UTF-8 → Windows-1252) under the same replacement-character rule.
+
+use crate::sources::SourceRegistry;
+use anyhow::{Context, Result};
+use encoding_rs::WINDOWS_1252;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf8Bom,
+    /// "latin-1" in sources.json; decoded as Windows-1252, its superset
+    /// (same as browsers and encoding_rs do for the ISO-8859-1 label)
+    Latin1,
+}
+
+impl TextEncoding {
+    pub fn from_label(label: &str) -> Result<Self> {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Ok(TextEncoding::Utf8),
+            "utf-8-bom" | "utf-8-sig" => Ok(TextEncoding::Utf8Bom),
+            "latin-1" | "latin1" | "iso-8859-1" | "windows-1252" | "cp1252" => Ok(TextEncoding::Latin1),
+            other => anyhow::bail!("AMBIGUITY: Unknown encoding '{}'", other),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "utf-8",
+            TextEncoding::Utf8Bom => "utf-8-bom",
+            TextEncoding::Latin1 => "latin-1",
+        }
+    }
+}
+
+/// Encoding declared for a source in the embedded sources.json, if any
+pub fn declared_encoding(source_id: &str) -> Result<Option<TextEncoding>> {
+    SourceRegistry::embedded()?
+        .source(source_id)
+        .and_then(|s| s.encoding.as_deref())
+        .map(|label| {
+            TextEncoding::from_label(label)
+                .with_context(|| format!("Invalid encoding for source '{}'", source_id))
+        })
+        .transpose()
+}
+
+/// Decoded artifact text and how it was decoded
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    pub text: String,
+    pub declared: Option<TextEncoding>,
+    pub detected: TextEncoding,
+}
+
+/// What the bytes look like: BOM → UTF-8 with BOM, valid UTF-8 → UTF-8,
+/// anything else → Windows-1252
+pub fn detect(raw_bytes: &[u8]) -> TextEncoding {
+    if raw_bytes.starts_with(UTF8_BOM) {
+        TextEncoding::Utf8Bom
+    } else if std::str::from_utf8(raw_bytes).is_ok() {
+        TextEncoding::Utf8
+    } else {
+        TextEncoding::Latin1
+    }
+}
+
+/// Decode an artifact, verifying the declared encoding against the bytes
+///
+/// Pure ASCII without BOM is valid under every declaration. Halts when the
+/// detected encoding differs from the declared one, or when the text holds
+/// U+FFFD (undecodable bytes, or replacement characters already in the file).
+pub fn decode(raw_bytes: &[u8], declared: Option<TextEncoding>) -> Result<Decoded> {
+    let detected = detect(raw_bytes);
+
+    if let Some(declared) = declared {
+        let compatible = declared == detected
+            || (detected == TextEncoding::Utf8 && raw_bytes.is_ascii());
+        if !compatible {
+            anyhow::bail!(
+                "AMBIGUITY: Declared encoding '{}' does not match the bytes (detected '{}')",
+                declared.as_str(),
+                detected.as_str()
+            );
+        }
+    }
+
+    let text = match detected {
+        TextEncoding::Utf8Bom => std::str::from_utf8(&raw_bytes[UTF8_BOM.len()..])
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "AMBIGUITY: File starts with a UTF-8 BOM but is not valid UTF-8 (byte {})",
+                    e.valid_up_to() + UTF8_BOM.len()
+                )
+            })?
+            .to_string(),
+        // detect() only returns Utf8 for valid UTF-8
+        TextEncoding::Utf8 => String::from_utf8_lossy(raw_bytes).into_owned(),
+        TextEncoding::Latin1 => {
+            // WHATWG Windows-1252 maps every byte; the five unassigned ones
+            // (0x81, 0x8D, 0x8F, 0x90, 0x9D) come out as C1 controls
+            let (decoded, _) = WINDOWS_1252.decode_without_bom_handling(raw_bytes);
+            if let Some(c) = decoded.chars().find(|c| ('\u{80}'..='\u{9F}').contains(c)) {
+                anyhow::bail!(
+                    "AMBIGUITY: Byte 0x{:02X} is undefined in Windows-1252",
+                    c as u32
+                );
+            }
+            decoded.into_owned()
+        }
+    };
+
+    if let Some(offset) = text.find('\u{FFFD}') {
+        let line = text[..offset].matches('\n').count() + 1;
+        anyhow::bail!(
+            "AMBIGUITY: Replacement character (U+FFFD) at line {} after decoding as '{}'",
+            line,
+            detected.as_str()
+        );
+    }
+
+    Ok(Decoded {
+        text,
+        declared,
+        detected,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "Capítulo" in Windows-1252: "Cap" + 0xED (í) + "tulo"
+    const LATIN1: &[u8] = &[b'C', b'a', b'p', 0xED, b't', b'u', b'l', b'o'];
+
+    #[test]
+    fn test_declared_encodings_from_sources_json() {
+        assert_eq!(
+            declared_encoding("dipres-ley-presupuestos-2020").unwrap(),
+            Some(TextEncoding::Latin1)
+        );
+        assert_eq!(
+            declared_encoding("dipres-ley-presupuestos-2021").unwrap(),
+            Some(TextEncoding::Utf8Bom)
+        );
+        assert_eq!(declared_encoding("demo-source").unwrap(), None);
+    }
+
+    #[test]
+    fn test_decode_matching_declarations() {
+        let latin1 = decode(LATIN1, Some(TextEncoding::Latin1)).unwrap();
+        assert_eq!(latin1.text, "Capítulo");
+        assert_eq!(latin1.detected, TextEncoding::Latin1);
+
+        let bom = decode("\u{feff}Capítulo".as_bytes(), Some(TextEncoding::Utf8Bom)).unwrap();
+        assert_eq!(bom.text, "Capítulo");
+
+        // ASCII fits any declaration
+        let ascii = decode(b"Partida;Monto", Some(TextEncoding::Latin1)).unwrap();
+        assert_eq!(ascii.text, "Partida;Monto");
+        assert_eq!(ascii.detected, TextEncoding::Utf8);
+    }
+
+    #[test]
+    fn test_decode_halts_on_mismatch() {
+        let err = decode(LATIN1, Some(TextEncoding::Utf8Bom)).unwrap_err().to_string();
+        assert!(err.contains("AMBIGUITY") && err.contains("detected 'latin-1'"));
+        assert!(decode("Capítulo".as_bytes(), Some(TextEncoding::Latin1)).is_err());
+        assert!(decode("\u{feff}Partida".as_bytes(), Some(TextEncoding::Utf8)).is_err());
+    }
+
+    #[test]
+    fn test_decode_halts_on_replacement_characters() {
+        let err = decode("a\nb\u{FFFD}".as_bytes(), None).unwrap_err().to_string();
+        assert!(err.contains("U+FFFD") && err.contains("line 2"));
+        // 0x81 is undefined in Windows-1252
+        assert!(decode(&[b'a', 0x81], None).is_err());
+        // BOM followed by invalid UTF-8
+        assert!(decode(&[0xEF, 0xBB, 0xBF, b'C', 0xED], None).is_err());
+    }
+
+    #[test]
+    fn test_decode_detects_without_declaration() {
+        assert_eq!(decode(LATIN1, None).unwrap().detected, TextEncoding::Latin1);
+        assert_eq!(decode(b"\xEF\xBB\xBFx", None).unwrap().text, "x");
+    }
+}
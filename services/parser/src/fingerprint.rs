@@ -0,0 +1,276 @@
+//! Structural fingerprint of an artifact and drift between fiscal years
+//!
+//! DIPRES publishes one file per fiscal year with the same layout, or so we
+//! assume. Before parsing, the parser records what the file looks like
+//! (headers, column types, institutional and economic codes, row count) and
+//! compares it with the fingerprint of the previous year's artifact of the
+//! same source family, so layout or coverage changes are visible before any
+//! fact is published.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// What an artifact looks like, independent of its amounts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SchemaFingerprint {
+    /// Normalized headers, in file order
+    pub headers: Vec<String>,
+    /// Per column: "integer" | "text" | "empty"
+    pub column_types: Vec<String>,
+    pub partidas: BTreeSet<String>,
+    /// "PP.CC.GG"
+    pub programas: BTreeSet<String>,
+    pub subtitulos: BTreeSet<String>,
+    pub row_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnRename {
+    pub position: usize,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnTypeChange {
+    pub column: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// Differences between two fingerprints (previous → current)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SchemaDrift {
+    pub renamed_columns: Vec<ColumnRename>,
+    pub added_columns: Vec<String>,
+    pub removed_columns: Vec<String>,
+    pub type_changes: Vec<ColumnTypeChange>,
+    pub new_partidas: Vec<String>,
+    pub removed_partidas: Vec<String>,
+    pub new_programas: Vec<String>,
+    pub removed_programas: Vec<String>,
+    pub new_subtitulos: Vec<String>,
+    pub removed_subtitulos: Vec<String>,
+    /// Informational; a different row count alone is not drift
+    pub previous_row_count: usize,
+    pub row_count: usize,
+}
+
+impl SchemaFingerprint {
+    /// Build a fingerprint from normalized headers and raw records
+    /// Code columns are found by header name (Partida, Capitulo, Programa, Subtitulo)
+    pub fn from_table(headers: Vec<String>, records: &[Vec<String>]) -> Self {
+        let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+        let (partida, capitulo, programa, subtitulo) = (
+            column("partida"),
+            column("capitulo"),
+            column("programa"),
+            column("subtitulo"),
+        );
+        let cell = |record: &Vec<String>, idx: Option<usize>| -> Option<String> {
+            let value = record.get(idx?)?.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+        let code = |value: String, width: usize| format!("{:0>width$}", value, width = width);
+
+        let column_types = (0..headers.len())
+            .map(|i| column_type(records.iter().filter_map(|r| r.get(i))))
+            .collect();
+
+        let mut fingerprint = SchemaFingerprint {
+            headers: headers.clone(),
+            column_types,
+            row_count: records.len(),
+            ..Default::default()
+        };
+
+        for record in records {
+            let Some(p) = cell(record, partida).map(|v| code(v, 2)) else {
+                continue;
+            };
+            fingerprint.partidas.insert(p.clone());
+            if let (Some(c), Some(g)) = (cell(record, capitulo), cell(record, programa)) {
+                fingerprint
+                    .programas
+                    .insert(format!("{}.{}.{}", p, code(c, 2), code(g, 2)));
+            }
+            if let Some(s) = cell(record, subtitulo).filter(|s| !s.chars().all(|c| c == '0')) {
+                fingerprint.subtitulos.insert(code(s, 2));
+            }
+        }
+
+        fingerprint
+    }
+}
+
+/// "integer" if every non-empty value is an integer, "empty" if there are none
+fn column_type<'a>(values: impl Iterator<Item = &'a String>) -> String {
+    let mut seen = false;
+    for value in values.map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        seen = true;
+        if value.parse::<i64>().is_err() {
+            return "text".to_string();
+        }
+    }
+    if seen { "integer" } else { "empty" }.to_string()
+}
+
+fn added<T: Ord + Clone>(from: &BTreeSet<T>, to: &BTreeSet<T>) -> Vec<T> {
+    to.difference(from).cloned().collect()
+}
+
+impl SchemaDrift {
+    pub fn between(previous: &SchemaFingerprint, current: &SchemaFingerprint) -> Self {
+        let mut drift = SchemaDrift {
+            previous_row_count: previous.row_count,
+            row_count: current.row_count,
+            ..Default::default()
+        };
+
+        // Same position, different name: renamed. Extra positions: added / removed
+        for (position, (from, to)) in previous.headers.iter().zip(&current.headers).enumerate() {
+            if from != to {
+                drift.renamed_columns.push(ColumnRename {
+                    position,
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+        }
+        drift.added_columns = current.headers.iter().skip(previous.headers.len()).cloned().collect();
+        drift.removed_columns = previous.headers.iter().skip(current.headers.len()).cloned().collect();
+
+        for (i, header) in current.headers.iter().enumerate() {
+            if let (Some(from), Some(to)) = (previous.column_types.get(i), current.column_types.get(i)) {
+                if from != to {
+                    drift.type_changes.push(ColumnTypeChange {
+                        column: header.clone(),
+                        from: from.clone(),
+                        to: to.clone(),
+                    });
+                }
+            }
+        }
+
+        drift.new_partidas = added(&previous.partidas, &current.partidas);
+        drift.removed_partidas = added(&current.partidas, &previous.partidas);
+        drift.new_programas = added(&previous.programas, &current.programas);
+        drift.removed_programas = added(&current.programas, &previous.programas);
+        drift.new_subtitulos = added(&previous.subtitulos, &current.subtitulos);
+        drift.removed_subtitulos = added(&current.subtitulos, &previous.subtitulos);
+        drift
+    }
+
+    pub fn has_drift(&self) -> bool {
+        !(self.renamed_columns.is_empty()
+            && self.added_columns.is_empty()
+            && self.removed_columns.is_empty()
+            && self.type_changes.is_empty()
+            && self.new_partidas.is_empty()
+            && self.removed_partidas.is_empty()
+            && self.new_programas.is_empty()
+            && self.removed_programas.is_empty()
+            && self.new_subtitulos.is_empty()
+            && self.removed_subtitulos.is_empty())
+    }
+
+    /// One line per kind of change, for the parser output
+    pub fn summary(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .renamed_columns
+            .iter()
+            .map(|r| format!("column {} renamed: '{}' -> '{}'", r.position, r.from, r.to))
+            .collect();
+        lines.extend(
+            self.type_changes
+                .iter()
+                .map(|c| format!("column '{}' type changed: {} -> {}", c.column, c.from, c.to)),
+        );
+        for (label, codes) in [
+            ("added columns", &self.added_columns),
+            ("removed columns", &self.removed_columns),
+            ("new partidas", &self.new_partidas),
+            ("removed partidas", &self.removed_partidas),
+            ("new programas", &self.new_programas),
+            ("removed programas", &self.removed_programas),
+            ("new subtitulos", &self.new_subtitulos),
+            ("removed subtitulos", &self.removed_subtitulos),
+        ] {
+            if !codes.is_empty() {
+                lines.push(format!("{} ({}): {}", label, codes.len(), codes.join(", ")));
+            }
+        }
+        lines
+    }
+}
+
+/// Source family: the source id without its year, e.g.
+/// "dipres-ley-presupuestos-2026" -> "dipres-ley-presupuestos"
+pub fn source_family(source_id: &str) -> String {
+    source_id
+        .split('-')
+        .filter(|s| !(s.len() == 4 && s.parse::<i32>().is_ok_and(|y| (2000..=2100).contains(&y))))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(headers: &[&str], rows: &[&[&str]]) -> SchemaFingerprint {
+        SchemaFingerprint::from_table(
+            headers.iter().map(|h| h.to_string()).collect(),
+            &rows
+                .iter()
+                .map(|r| r.iter().map(|c| c.to_string()).collect())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_fingerprint_codes_and_types() {
+        let fp = table(
+            &["Partida", "Capitulo", "Programa", "Subtitulo", "Denominacion", "Monto Pesos"],
+            &[
+                &["1", "01", "01", "21", "PERSONAL", "100"],
+                &["09", "01", "03", "00", "TOTAL", "200"],
+                &["", "", "", "", "", ""],
+            ],
+        );
+        assert_eq!(fp.partidas.iter().collect::<Vec<_>>(), vec!["01", "09"]);
+        assert_eq!(fp.programas.iter().collect::<Vec<_>>(), vec!["01.01.01", "09.01.03"]);
+        assert_eq!(fp.subtitulos.iter().collect::<Vec<_>>(), vec!["21"]);
+        assert_eq!(fp.column_types, vec!["integer", "integer", "integer", "integer", "text", "integer"]);
+        assert_eq!(fp.row_count, 3);
+    }
+
+    #[test]
+    fn test_drift_between_years() {
+        let previous = table(
+            &["Partida", "Capitulo", "Programa", "Monto Pesos"],
+            &[&["01", "01", "01", "1"], &["02", "01", "01", "2"]],
+        );
+        let current = table(
+            &["Partida", "Capitulo", "Programa", "Monto", "Monto Dolar"],
+            &[&["01", "01", "01", "1"], &["03", "01", "01", "x"]],
+        );
+        let drift = SchemaDrift::between(&previous, &current);
+
+        assert!(drift.has_drift());
+        assert_eq!(drift.renamed_columns[0].from, "Monto Pesos");
+        assert_eq!(drift.renamed_columns[0].to, "Monto");
+        assert_eq!(drift.added_columns, vec!["Monto Dolar"]);
+        assert_eq!(drift.type_changes[0].to, "text");
+        assert_eq!(drift.new_partidas, vec!["03"]);
+        assert_eq!(drift.removed_programas, vec!["02.01.01"]);
+        assert!(!SchemaDrift::between(&previous, &previous).has_drift());
+    }
+
+    #[test]
+    fn test_source_family() {
+        assert_eq!(source_family("dipres-ley-presupuestos-2026"), "dipres-ley-presupuestos");
+        assert_eq!(source_family("dipres-ley-2025-presupuestos"), "dipres-ley-presupuestos");
+        assert_eq!(source_family("demo-source"), "demo-source");
+    }
+}
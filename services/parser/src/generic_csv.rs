@@ -0,0 +1,508 @@
+//! Generic (demo) CSV: one amount per entity, category and year
+//!
+//! Comma-delimited, with the column aliases of `CsvRow`. Entity names go
+//! through the crosswalk; the metric follows the source id ("presupuesto",
+//! "gasto", "dotacion"). Fallback parser: accepts any artifact no other
+//! parser claims, and halts row by row on anything it cannot read.
+
+use crate::crosswalk::{crosswalk_entity, EntityCrosswalk, ResolutionLog};
+use crate::numeric;
+use crate::registry::{ArtifactInput, Parser};
+use crate::report::ParseReport;
+use crate::{decode_artifact, with_encoding, ParseOutput, ParsedFact};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+/// Parser identifier for the generic (demo) CSV format
+pub const PARSER_ID: &str = "csv_v1";
+
+/// CSV row structure for demo data (presupuesto format)
+#[derive(Debug, Deserialize)]
+struct CsvRow {
+    #[serde(alias = "entidad", alias = "entity", alias = "organismo")]
+    entity: String,
+    #[serde(alias = "categoria", alias = "category", alias = "item")]
+    category: Option<String>,
+    #[serde(alias = "anio", alias = "year", alias = "periodo")]
+    year: i32,
+    /// Read with `numeric::parse` (es-CL separators, currency, scale)
+    #[serde(alias = "monto", alias = "amount", alias = "valor")]
+    amount: String,
+}
+
+/// Parse CSV content into facts
+/// This function is DETERMINISTIC: same input = same output
+pub fn parse_csv(content: &str, source_id: &str) -> Result<ParseOutput> {
+    let mut facts = Vec::new();
+    let mut report = ParseReport::default();
+    let crosswalk = EntityCrosswalk::embedded()?;
+    let mut entities = ResolutionLog::default();
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    for (line_num, result) in reader.deserialize().enumerate() {
+        let row: CsvRow = match result {
+            Ok(r) => r,
+            Err(e) => {
+                report.reject(line_num + 2, e.to_string());
+                continue;
+            }
+        };
+        let amount = match numeric::parse(&row.amount) {
+            Ok(v) => v,
+            Err(reason) => {
+                report.reject(line_num + 2, format!("Invalid amount {}", reason));
+                continue;
+            }
+        };
+
+        // Create period dates (year -> Jan 1 to Dec 31)
+        let period_start = NaiveDate::from_ymd_opt(row.year, 1, 1)
+            .context("Invalid year for period_start")?;
+        let period_end = NaiveDate::from_ymd_opt(row.year, 12, 31)
+            .context("Invalid year for period_end")?;
+
+        // Build dimensions from category if present
+        let mut dims = match &row.category {
+            Some(cat) if !cat.is_empty() => {
+                serde_json::json!({ "category": cat })
+            }
+            _ => serde_json::json!({}),
+        };
+
+        let Some(entity) = crosswalk_entity(
+            &crosswalk,
+            &mut entities,
+            source_id,
+            row.year,
+            row.entity.trim(),
+            &mut dims,
+        ) else {
+            report.reject(line_num + 2, format!("Entity '{}' yields an empty key", row.entity.trim()));
+            continue;
+        };
+
+        // Determine metric based on source
+        let (metric_key, metric_name) = match source_id {
+            s if s.contains("presupuesto") => ("presupuesto_ejecutado", "Presupuesto Ejecutado"),
+            s if s.contains("gasto") => ("gasto_total", "Gasto Total"),
+            s if s.contains("dotacion") => ("dotacion", "Dotación de Personal"),
+            _ => ("monto", "Monto"),
+        };
+
+        facts.push(ParsedFact {
+            entity_key: entity.key,
+            entity_name: entity.name,
+            entity_type: entity.entity_type,
+            entity_parents: Vec::new(),
+            metric_key: metric_key.to_string(),
+            metric_name: metric_name.to_string(),
+            metric_unit: "CLP".to_string(),
+            period_start,
+            period_end,
+            value_num: amount,
+            location: format!("csv:line={}", line_num + 2), // +2 for 1-indexed + header
+            dims,
+        });
+        report.accept();
+    }
+
+    Ok(ParseOutput {
+        facts,
+        report,
+        entities: entities.into_vec(),
+        linked_entities: Vec::new(),
+    })
+}
+
+/// Fallback parser for comma-delimited CSV
+pub struct GenericCsvParser;
+
+impl Parser for GenericCsvParser {
+    fn id(&self) -> &'static str {
+        PARSER_ID
+    }
+
+    fn accepts(&self, _input: &ArtifactInput) -> bool {
+        true
+    }
+
+    fn parse(&self, input: &ArtifactInput) -> Result<ParseOutput> {
+        let decoded = decode_artifact(input.bytes, input.source_id)?;
+        println!("Parsing generic CSV...");
+        Ok(with_encoding(parse_csv(&decoded.text, input.source_id)?, &decoded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dipres_ley::parse_dipres_ley_csv;
+    use chrono::Datelike;
+    use rust_decimal_macros::dec;
+
+    // -------------------------------------------------------------------------
+    // DETERMINISM TESTS - Same input MUST produce same output
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_parse_csv_determinism() {
+        let csv = "entidad,categoria,anio,monto\nMinisterio de Salud,Personal,2024,1000000\n";
+
+        let result1 = parse_csv(csv, "presupuesto-test").unwrap().facts;
+        let result2 = parse_csv(csv, "presupuesto-test").unwrap().facts;
+
+        assert_eq!(result1.len(), result2.len());
+        assert_eq!(result1[0].entity_key, result2[0].entity_key);
+        assert_eq!(result1[0].value_num, result2[0].value_num);
+        assert_eq!(result1[0].period_start, result2[0].period_start);
+    }
+
+    #[test]
+    fn test_parse_csv_determinism_multiple_runs() {
+        let csv = r#"entidad,categoria,anio,monto
+Ministerio de Educación,Personal,2024,1250000000000
+Ministerio de Educación,Operaciones,2024,450000000000
+Ministerio de Salud,Personal,2024,980000000000
+"#;
+
+        // Run 10 times and verify identical output
+        let baseline = parse_csv(csv, "presupuesto").unwrap().facts;
+        for _ in 0..10 {
+            let result = parse_csv(csv, "presupuesto").unwrap().facts;
+            assert_eq!(baseline.len(), result.len());
+            for (a, b) in baseline.iter().zip(result.iter()) {
+                assert_eq!(a.entity_key, b.entity_key);
+                assert_eq!(a.metric_key, b.metric_key);
+                assert_eq!(a.value_num, b.value_num);
+                assert_eq!(a.location, b.location);
+            }
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // ENTITY KEY NORMALIZATION TESTS
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_entity_key_normalization_basic() {
+        let csv = "entidad,anio,monto\nServicio de Salud Aconcagua,2024,1000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].entity_key, "servicio_de_salud_aconcagua");
+    }
+
+    #[test]
+    fn test_entity_key_normalization_accents() {
+        let csv = "entidad,anio,monto\nDirección de Educación Pública,2024,1000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        // Accents are stripped: keys are plain ASCII
+        assert_eq!(facts[0].entity_key, "direccion_de_educacion_publica");
+        assert_eq!(facts[0].entity_name, "Dirección de Educación Pública");
+    }
+
+    #[test]
+    fn test_entity_key_normalization_dots_removed() {
+        let csv = "entidad,anio,monto\nGob. Regional de Valparaíso,2024,1000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].entity_key, "gob_regional_de_valparaiso");
+    }
+
+    #[test]
+    fn test_entity_key_normalization_special_chars() {
+        let csv = "entidad,anio,monto\n\"Serv. Nacional (SERNAC)\",2024,1000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        // Only alphanumeric and underscore allowed
+        assert!(!facts[0].entity_key.contains('('));
+        assert!(!facts[0].entity_key.contains(')'));
+    }
+
+    #[test]
+    fn test_entity_key_normalization_whitespace() {
+        let csv = "entidad,anio,monto\n\"  Servicio de Salud Aconcagua  \",2024,1000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].entity_key, "servicio_de_salud_aconcagua");
+        assert_eq!(facts[0].entity_name, "Servicio de Salud Aconcagua");
+    }
+
+    #[test]
+    fn test_entity_key_normalization_unicode_forms() {
+        // Precomposed and decomposed accents produce the same key
+        let csv = "entidad,anio,monto\nDirecci\u{f3}n Regional,2024,1\nDireccio\u{301}n Regional,2024,2\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].entity_key, "direccion_regional");
+        assert_eq!(facts[1].entity_key, facts[0].entity_key);
+    }
+
+    #[test]
+    fn test_entity_without_key_is_rejected() {
+        let csv = "entidad,anio,monto\n教育部,2024,1000\nServicio A,2024,1\n";
+        let output = parse_csv(csv, "test").unwrap();
+        assert_eq!(output.facts.len(), 1);
+        assert_eq!(output.report.rejects[0].line, 2);
+        assert!(output.report.rejects[0].reason.contains("empty key"));
+    }
+
+    // -------------------------------------------------------------------------
+    // ENTITY CROSSWALK TESTS
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_csv_entities_join_dipres_partidas() {
+        let csv = "entidad,anio,monto\nMinisterio de Educación,2024,1000\nMINEDUC,2024,500\nServicio de Salud Aconcagua,2024,10\n";
+        let output = parse_csv(csv, "test").unwrap();
+
+        let ley = "Partida;Capitulo;Programa;Subtitulo;Ítem;Asignacion;Denominacion;Monto Pesos;Monto Dolar\n\
+                   09;01;01;21;00;000;GASTOS EN PERSONAL;100;0\n";
+        let ley_facts = parse_dipres_ley_csv(ley, "dipres-ley-presupuestos-2024").unwrap().facts;
+
+        // Both names of the ministry land on the DIPRES Partida entity
+        assert_eq!(output.facts[0].entity_key, ley_facts[0].entity_key);
+        assert_eq!(output.facts[1].entity_key, "partida_09");
+        assert_eq!(output.facts[0].entity_type, "partida");
+        assert_eq!(output.facts[0].entity_name, "Ministerio de Educación");
+        assert_eq!(output.facts[1].dims["source_entity_name"], "MINEDUC");
+        assert_eq!(output.facts[1].dims["entity_match_rule"], "alias");
+
+        // Unmatched names keep a local key and are logged for review
+        assert_eq!(output.facts[2].entity_key, "servicio_de_salud_aconcagua");
+        assert_eq!(output.facts[2].entity_type, "organismo");
+        let unmatched: Vec<&str> = output
+            .entities
+            .iter()
+            .filter(|e| e.rule.is_none())
+            .map(|e| e.source_name.as_str())
+            .collect();
+        assert_eq!(unmatched, vec!["Servicio de Salud Aconcagua"]);
+        assert_eq!(output.entities.len(), 3);
+    }
+
+    // -------------------------------------------------------------------------
+    // METRIC DETECTION TESTS
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_metric_detection_presupuesto() {
+        let csv = "entidad,anio,monto\nTest,2024,1000\n";
+        let facts = parse_csv(csv, "dipres-presupuesto-2024").unwrap().facts;
+        assert_eq!(facts[0].metric_key, "presupuesto_ejecutado");
+        assert_eq!(facts[0].metric_name, "Presupuesto Ejecutado");
+    }
+
+    #[test]
+    fn test_metric_detection_gasto() {
+        let csv = "entidad,anio,monto\nTest,2024,1000\n";
+        let facts = parse_csv(csv, "contraloria-gasto-2024").unwrap().facts;
+        assert_eq!(facts[0].metric_key, "gasto_total");
+        assert_eq!(facts[0].metric_name, "Gasto Total");
+    }
+
+    #[test]
+    fn test_metric_detection_dotacion() {
+        let csv = "entidad,anio,monto\nTest,2024,1000\n";
+        let facts = parse_csv(csv, "dipres-dotacion-2024").unwrap().facts;
+        assert_eq!(facts[0].metric_key, "dotacion");
+        assert_eq!(facts[0].metric_name, "Dotación de Personal");
+    }
+
+    #[test]
+    fn test_metric_detection_unknown() {
+        let csv = "entidad,anio,monto\nTest,2024,1000\n";
+        let facts = parse_csv(csv, "unknown-source").unwrap().facts;
+        assert_eq!(facts[0].metric_key, "monto");
+        assert_eq!(facts[0].metric_name, "Monto");
+    }
+
+    // -------------------------------------------------------------------------
+    // PERIOD DATE TESTS
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_period_dates_year_2024() {
+        let csv = "entidad,anio,monto\nTest,2024,1000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].period_start, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+        assert_eq!(facts[0].period_end, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+    }
+
+    #[test]
+    fn test_period_dates_year_2025() {
+        let csv = "entidad,anio,monto\nTest,2025,1000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].period_start, NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+        assert_eq!(facts[0].period_end, NaiveDate::from_ymd_opt(2025, 12, 31).unwrap());
+    }
+
+    // -------------------------------------------------------------------------
+    // DIMENSIONS TESTS
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_dimensions_with_category() {
+        let csv = "entidad,categoria,anio,monto\nTest,Personal,2024,1000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].dims, serde_json::json!({"category": "Personal"}));
+    }
+
+    #[test]
+    fn test_dimensions_without_category() {
+        let csv = "entidad,anio,monto\nTest,2024,1000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].dims, serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_dimensions_empty_category() {
+        let csv = "entidad,categoria,anio,monto\nTest,,2024,1000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].dims, serde_json::json!({}));
+    }
+
+    // -------------------------------------------------------------------------
+    // LINE LOCATION TESTS
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_line_location_first_row() {
+        let csv = "entidad,anio,monto\nTest,2024,1000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].location, "csv:line=2"); // Header is line 1
+    }
+
+    #[test]
+    fn test_line_location_multiple_rows() {
+        let csv = "entidad,anio,monto\nA,2024,1\nB,2024,2\nC,2024,3\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].location, "csv:line=2");
+        assert_eq!(facts[1].location, "csv:line=3");
+        assert_eq!(facts[2].location, "csv:line=4");
+    }
+
+    // -------------------------------------------------------------------------
+    // VALUE PARSING TESTS
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_value_parsing_integer() {
+        let csv = "entidad,anio,monto\nTest,2024,1000000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].value_num, dec!(1000000));
+    }
+
+    #[test]
+    fn test_value_parsing_large_number() {
+        let csv = "entidad,anio,monto\nTest,2024,1250000000000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].value_num, dec!(1250000000000));
+    }
+
+    #[test]
+    fn test_value_parsing_decimal() {
+        let csv = "entidad,anio,monto\nTest,2024,1234.56\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].value_num, dec!(1234.56));
+    }
+
+    #[test]
+    fn test_value_parsing_es_cl() {
+        let csv = "entidad,anio,monto\nTest,2024,\"1.234.567,5\"\nTest,2024,(1.500.000)\nTest,2024,1.500\n";
+        let output = parse_csv(csv, "test").unwrap();
+        assert_eq!(output.facts[0].value_num, dec!(1234567.5));
+        assert_eq!(output.facts[1].value_num, dec!(-1500000));
+        // "1.500" reads as 1500 (es-CL) or 1.5 (en-US): rejected
+        assert_eq!(output.report.rejects[0].line, 4);
+        assert!(output.report.rejects[0].reason.contains("ambiguous"));
+    }
+
+    // -------------------------------------------------------------------------
+    // COLUMN ALIAS TESTS
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_column_alias_entity() {
+        let csv = "organismo,anio,monto\nTest,2024,1000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].entity_name, "Test");
+    }
+
+    #[test]
+    fn test_column_alias_year() {
+        let csv = "entidad,periodo,monto\nTest,2024,1000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].period_start.year(), 2024);
+    }
+
+    #[test]
+    fn test_column_alias_amount() {
+        let csv = "entidad,anio,valor\nTest,2024,5000\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].value_num, dec!(5000));
+    }
+
+    // -------------------------------------------------------------------------
+    // EDGE CASES
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_empty_csv() {
+        let csv = "entidad,anio,monto\n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts.len(), 0);
+    }
+
+    #[test]
+    fn test_whitespace_trimming() {
+        let csv = "entidad,anio,monto\n  Test  ,  2024  ,  1000  \n";
+        let facts = parse_csv(csv, "test").unwrap().facts;
+        assert_eq!(facts[0].entity_name, "Test");
+        assert_eq!(facts[0].value_num, dec!(1000));
+    }
+
+    #[test]
+    fn test_multiple_entities_same_year() {
+        let csv = r#"entidad,categoria,anio,monto
+Ministerio A,Personal,2024,100
+Ministerio A,Operaciones,2024,200
+Ministerio B,Personal,2024,300
+"#;
+        let facts = parse_csv(csv, "presupuesto").unwrap().facts;
+        assert_eq!(facts.len(), 3);
+        assert_eq!(facts[0].entity_key, "ministerio_a");
+        assert_eq!(facts[1].entity_key, "ministerio_a");
+        assert_eq!(facts[2].entity_key, "ministerio_b");
+    }
+
+    // -------------------------------------------------------------------------
+    // REAL DATA FORMAT TESTS (DIPRES format)
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_dipres_budget_format() {
+        let csv = r#"entidad,categoria,anio,monto
+Ministerio de Educación,Personal,2024,1250000000000
+Ministerio de Educación,Operaciones,2024,450000000000
+Ministerio de Educación,Inversión,2024,380000000000
+Ministerio de Salud,Personal,2024,980000000000
+"#;
+        let facts = parse_csv(csv, "dipres-presupuesto-2024").unwrap().facts;
+
+        assert_eq!(facts.len(), 4);
+        assert_eq!(facts[0].metric_key, "presupuesto_ejecutado");
+        assert_eq!(facts[0].entity_key, "partida_09");
+        assert_eq!(facts[0].value_num, dec!(1250000000000));
+        assert_eq!(facts[0].dims["category"], "Personal");
+    }
+
+    #[test]
+    fn test_csv_invalid_row_is_rejected() {
+        let csv = "entidad,anio,monto\nMinisterio A,2024,1000\nMinisterio B,2024,mil\n";
+        let output = parse_csv(csv, "test").unwrap();
+
+        assert_eq!(output.facts.len(), 1);
+        assert_eq!(output.report.accepted_rows, 1);
+        assert_eq!(output.report.rejects.len(), 1);
+        assert_eq!(output.report.rejects[0].line, 3);
+    }
+}
@@ -0,0 +1,142 @@
+//! Canonical JSON of a parser run, for golden-file regression tests
+//!
+//! PRINCIPLES.md #1: same artifact + same parser version = same output.
+//! tests/golden.rs parses every case of tests/golden/cases.json and compares
+//! this form, byte for byte, with the golden stored next to it; a parser
+//! change that moves any fact, amount, dim or report line fails the test
+//! until the goldens are regenerated on purpose (see docs/ARCHITECTURE.md).
+//!
+//! Canonical form: object keys sorted, facts and entities in the order the
+//! parser emits them (order is part of the output), amounts as decimal
+//! strings with their scale, two-space indentation, trailing newline. The
+//! schema fingerprint is part of it, so a header or coverage variant of an
+//! earlier fiscal year shows up in its golden too.
+
+use crate::fingerprint::SchemaFingerprint;
+use crate::{ParseOutput, ParsedEntity, ParsedFact};
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+
+/// Canonical JSON of a parser run: parser id, facts, report, schema
+/// fingerprint (null for parsers that do not track drift) and entities
+pub fn canonical_json(
+    parser_id: &str,
+    output: &ParseOutput,
+    fingerprint: Option<&SchemaFingerprint>,
+) -> Result<String> {
+    let entities: Vec<Value> = output
+        .entities
+        .iter()
+        .map(|e| {
+            json!({
+                "source_name": e.source_name,
+                "normalized_name": e.normalized_name,
+                "entity_key": e.entity_key,
+                "rule": e.rule.map(|r| r.as_str()),
+                "rows": e.rows,
+            })
+        })
+        .collect();
+    let value = json!({
+        "parser_id": parser_id,
+        "facts": output.facts.iter().map(fact_json).collect::<Vec<_>>(),
+        "report": serde_json::to_value(&output.report)?,
+        "fingerprint": serde_json::to_value(fingerprint)?,
+        "entities": entities,
+        "linked_entities": output.linked_entities.iter().map(entity_json).collect::<Vec<_>>(),
+    });
+
+    let mut text = serde_json::to_string_pretty(&sort_keys(value))?;
+    text.push('\n');
+    Ok(text)
+}
+
+fn fact_json(fact: &ParsedFact) -> Value {
+    json!({
+        "entity_key": fact.entity_key,
+        "entity_name": fact.entity_name,
+        "entity_type": fact.entity_type,
+        "entity_parents": fact.entity_parents.iter().map(entity_json).collect::<Vec<_>>(),
+        "metric_key": fact.metric_key,
+        "metric_name": fact.metric_name,
+        "metric_unit": fact.metric_unit,
+        "period_start": fact.period_start.to_string(),
+        "period_end": fact.period_end.to_string(),
+        "value_num": fact.value_num.to_string(),
+        "location": fact.location,
+        "dims": fact.dims,
+    })
+}
+
+fn entity_json(entity: &ParsedEntity) -> Value {
+    json!({
+        "key": entity.key,
+        "name": entity.name,
+        "entity_type": entity.entity_type,
+    })
+}
+
+/// Sort object keys at every level, whatever map serde_json was built with
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, sort_keys(v)))
+                    .collect::<Map<String, Value>>(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::ParseReport;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    fn output(value_num: Decimal) -> ParseOutput {
+        let day = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        ParseOutput {
+            facts: vec![ParsedFact {
+                entity_key: "partida_09".to_string(),
+                entity_name: "Ministerio de Educación".to_string(),
+                entity_type: "partida".to_string(),
+                entity_parents: Vec::new(),
+                metric_key: "monto".to_string(),
+                metric_name: "Monto".to_string(),
+                metric_unit: "CLP".to_string(),
+                period_start: day,
+                period_end: day,
+                value_num,
+                location: "csv:line=2".to_string(),
+                dims: json!({ "z": 1, "a": { "y": 2, "b": 3 } }),
+            }],
+            report: ParseReport::default(),
+            entities: Vec::new(),
+            linked_entities: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_canonical_json_sorts_keys_and_keeps_scale() {
+        let text = canonical_json("csv_v1", &output(Decimal::new(15000, 1)), None).unwrap();
+
+        assert!(text.ends_with("}\n"));
+        assert!(text.contains("\"value_num\": \"1500.0\""), "{}", text);
+        let a = text.find("\"a\": {").unwrap();
+        assert!(a < text.find("\"z\": 1").unwrap());
+        assert!(text[a..].find("\"b\": 3").unwrap() < text[a..].find("\"y\": 2").unwrap());
+        // 1500.0 and 1500 are different outputs
+        assert_ne!(
+            text,
+            canonical_json("csv_v1", &output(Decimal::new(1500, 0)), None).unwrap()
+        );
+    }
+}
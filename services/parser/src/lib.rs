@@ -0,0 +1,162 @@
+//! Parser library - Transforms raw artifacts into canonical facts
+//!
+//! Every parser implements [`Parser`]: artifact bytes + metadata in, facts +
+//! row-level report out. The service picks one through a [`ParserRegistry`];
+//! a domain crate depends on this library, registers its own parsers and runs
+//! the same service from its own binary (see docs/ARCHITECTURE.md).
+//!
+//! CRITICAL: parsers must be DETERMINISTIC
+//! Same artifact + same parser version = same output
+//!
+//! Catalogs, layouts and rules under config/ are embedded at build time
+//! (`include_str!`), so they are versioned together with the parser: a parser
+//! version always reads an artifact with the config it was built with.
+
+pub mod classifier;
+pub mod consolidation;
+pub mod crosswalk;
+pub mod dipres_ley;
+pub mod dipres_xls;
+pub mod dotacion;
+pub mod ejecucion;
+pub mod encoding;
+pub mod fingerprint;
+pub mod generic_csv;
+pub mod golden;
+pub mod lifecycle;
+pub mod mercadopublico;
+pub mod normalize;
+pub mod numeric;
+pub mod partidas;
+pub mod pdf_layout;
+pub mod pdf_tables;
+pub mod pdf_text;
+pub mod registry;
+pub mod report;
+pub mod service;
+pub mod sources;
+pub mod xls_layout;
+
+pub use registry::{ArtifactInput, Parser, ParserRegistry};
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use crosswalk::EntityResolution;
+use encoding::Decoded;
+use report::ParseReport;
+use rust_decimal::Decimal;
+
+/// An entity referenced by a fact (used for the parent chain of hierarchical entities)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedEntity {
+    pub key: String,
+    pub name: String,
+    pub entity_type: String,
+}
+
+/// A parsed fact ready for insertion
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedFact {
+    pub entity_key: String,
+    pub entity_name: String,
+    pub entity_type: String,
+    /// Ancestors of the fact's entity, root first (e.g. partida -> capitulo)
+    pub entity_parents: Vec<ParsedEntity>,
+    pub metric_key: String,
+    pub metric_name: String,
+    pub metric_unit: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    /// Exact amount (stored as NUMERIC); never routed through f64
+    pub value_num: Decimal,
+    pub location: String, // e.g., "csv:line=5"
+    pub dims: serde_json::Value,
+}
+
+/// Facts plus the row-level report of a parser run
+#[derive(Debug)]
+pub struct ParseOutput {
+    pub facts: Vec<ParsedFact>,
+    pub report: ParseReport,
+    /// Source entity names and how the crosswalk resolved them (empty for
+    /// parsers that read institutional codes directly)
+    pub entities: Vec<EntityResolution>,
+    /// Entities referenced from fact dims that carry no facts of their own
+    /// (e.g. suppliers of a purchase); created with the facts
+    pub linked_entities: Vec<ParsedEntity>,
+}
+
+/// Fiscal year embedded in a source id, e.g. "dipres-ley-presupuestos-2026" -> 2026
+pub fn fiscal_year_from_source_id(source_id: &str) -> Option<i32> {
+    source_id
+        .split('-')
+        .filter_map(|s| s.parse::<i32>().ok())
+        .find(|y| (2000..=2100).contains(y))
+}
+
+/// The whole fiscal year
+pub fn year_period(year: i32) -> Result<(NaiveDate, NaiveDate)> {
+    let start = NaiveDate::from_ymd_opt(year, 1, 1).context("Invalid year")?;
+    let end = NaiveDate::from_ymd_opt(year, 12, 31).context("Invalid year")?;
+    Ok((start, end))
+}
+
+/// Unit stated in a report's title block ("Miles de $"), as a multiplier to pesos
+pub fn unit_scale(title: &[String]) -> Result<Decimal> {
+    let folded: Vec<String> = title.iter().map(|t| normalize::fold(t)).collect();
+    let millions = folded.iter().any(|t| t.contains("millones de"));
+    let thousands = folded.iter().any(|t| t.contains("miles de"));
+    match (thousands, millions) {
+        (true, false) => Ok(Decimal::ONE_THOUSAND),
+        (false, true) => Ok(Decimal::from(1_000_000)),
+        _ => anyhow::bail!(
+            "AMBIGUITY: Title block must state one unit ('Miles de $' or 'Millones de $'), found {:?}",
+            title
+        ),
+    }
+}
+
+/// Decode a text artifact with the encoding declared for its source in
+/// sources.json, verified against the bytes (see encoding.rs)
+pub fn decode_artifact(raw_bytes: &[u8], source_id: &str) -> Result<Decoded> {
+    let declared = encoding::declared_encoding(source_id)?;
+    let decoded = encoding::decode(raw_bytes, declared)?;
+    println!(
+        "Encoding: {} (declared: {})",
+        decoded.detected.as_str(),
+        declared.map_or("none", |e| e.as_str())
+    );
+    Ok(decoded)
+}
+
+/// Record how a text artifact was decoded in its parse report
+pub fn with_encoding(mut output: ParseOutput, decoded: &Decoded) -> ParseOutput {
+    output.report.declared_encoding = decoded.declared.map(|e| e.as_str().to_string());
+    output.report.encoding = Some(decoded.detected.as_str().to_string());
+    output
+}
+
+/// Detect if file is XLS/XLSX based on mime type or file signature
+pub fn is_excel_file(mime_type: &str, storage_path: &str) -> bool {
+    mime_type.contains("excel")
+        || mime_type.contains("spreadsheet")
+        || storage_path.ends_with(".xls")
+        || storage_path.ends_with(".xlsx")
+}
+
+/// Detect if file is a PDF based on mime type or file extension
+pub fn is_pdf_file(mime_type: &str, storage_path: &str) -> bool {
+    mime_type.contains("pdf") || storage_path.to_lowercase().ends_with(".pdf")
+}
+
+/// Helpers shared by the parser modules' tests
+#[cfg(test)]
+pub(crate) mod test_support {
+    /// Raw bytes of a test fixture under tests/fixtures
+    pub(crate) fn fixture(name: &str) -> Vec<u8> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name);
+        std::fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+    }
+}
@@ -0,0 +1,98 @@
+//! Budget lifecycle stages (ley inicial -> vigente -> devengado -> pagado)
+//!
+//! Each stage of the spending cycle is its own metric, produced by its own
+//! parser: the Ley de Presupuestos gives `presupuesto_ley_gasto*`, the monthly
+//! execution report gives `presupuesto_vigente_gasto`, `ejecucion_devengado`
+//! and `ejecucion_pagado`. The seed file config/ciclo_presupuestario.json
+//! links those metrics to their stage; it is synced into `budget_lifecycle`
+//! so the API can compare stages without summing one into another.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// Stages of the spending cycle in order, with the metrics that measure each
+const LIFECYCLE_JSON: &str = include_str!("../../../config/ciclo_presupuestario.json");
+
+/// One stage as declared in the seed file
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct LifecycleStage {
+    /// "ley", "vigente", "devengado", "pagado"
+    pub stage: String,
+    pub name: String,
+    /// "year" (annual amount) or "month" (monthly amounts)
+    pub period: String,
+    /// Metrics measuring the stage, in order of preference
+    pub metrics: Vec<String>,
+    pub description: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LifecycleFile {
+    version: String,
+    stages: Vec<LifecycleStage>,
+}
+
+/// The spending lifecycle, stages in order
+#[derive(Debug)]
+pub struct BudgetLifecycle {
+    pub version: String,
+    pub stages: Vec<LifecycleStage>,
+}
+
+impl BudgetLifecycle {
+    /// Load the lifecycle embedded in the binary
+    pub fn embedded() -> Result<Self> {
+        Self::from_json(LIFECYCLE_JSON)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: LifecycleFile =
+            serde_json::from_str(json).context("Failed to parse budget lifecycle")?;
+        for stage in &file.stages {
+            if stage.period != "year" && stage.period != "month" {
+                anyhow::bail!("Stage '{}' has unknown period '{}'", stage.stage, stage.period);
+            }
+        }
+        let mut metrics: Vec<&String> = file.stages.iter().flat_map(|s| &s.metrics).collect();
+        metrics.sort();
+        if let Some(pair) = metrics.windows(2).find(|w| w[0] == w[1]) {
+            anyhow::bail!("Metric '{}' belongs to more than one lifecycle stage", pair[0]);
+        }
+        Ok(Self {
+            version: file.version,
+            stages: file.stages,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_lifecycle_order() {
+        let lifecycle = BudgetLifecycle::embedded().unwrap();
+        let stages: Vec<&str> = lifecycle.stages.iter().map(|s| s.stage.as_str()).collect();
+        let stage_of = |metric: &str| {
+            lifecycle
+                .stages
+                .iter()
+                .find(|s| s.metrics.iter().any(|m| m == metric))
+        };
+        assert_eq!(stages, ["ley", "vigente", "devengado", "pagado"]);
+        assert_eq!(stage_of("presupuesto_ley_gasto_linea").unwrap().stage, "ley");
+        assert_eq!(stage_of("ejecucion_devengado").unwrap().period, "month");
+        // Dollar series and ingresos are not part of the spending cycle
+        assert!(stage_of("presupuesto_ley_gasto_usd").is_none());
+        assert!(stage_of("presupuesto_ley_ingreso").is_none());
+    }
+
+    #[test]
+    fn test_metric_in_two_stages_is_rejected() {
+        let json = r#"{"version": "t", "stages": [
+            {"stage": "ley", "name": "Ley", "period": "year", "metrics": ["m"], "description": ""},
+            {"stage": "vigente", "name": "Vigente", "period": "year", "metrics": ["m"], "description": ""}
+        ]}"#;
+        assert!(BudgetLifecycle::from_json(json).is_err());
+    }
+}
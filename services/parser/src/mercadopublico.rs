@@ -0,0 +1,643 @@
+//! Mercado Público (ChileCompra) bulk exports: licitaciones and órdenes de compra
+//!
+//! Two domains with their own parser and metric (PRINCIPLES.md #4):
+//! - Licitaciones: one fact per awarded line (`Oferta seleccionada`), metric
+//!   `licitacion_monto_adjudicado`, dated on the award date
+//! - Órdenes de compra: one fact per order, metric `orden_compra_monto` from
+//!   the order total in pesos, dated on the send date; the export repeats the
+//!   total on every item line, so the lines of one order must agree
+//!
+//! The fact entity is the buyer organism (`organismo_mp_<CodigoOrganismo>`).
+//! The supplier is an entity of its own (`proveedor_<RUT>`) referenced from the
+//! fact dims; its RUT must pass the módulo 11 check. Amounts are never
+//! converted: licitación lines in another currency are rejected, orders use
+//! the peso amount the export already states.
+//!
+//! The exports are `;`-separated CSV, monthly, published as a ZIP holding one
+//! CSV; the artifact may be either. Columns come from the parser specs in
+//! config/sources.json.
+
+use crate::numeric;
+use crate::report::ParseReport;
+use crate::sources::{column_for, SourceRegistry};
+use crate::registry::{ArtifactInput, Parser};
+use crate::{decode_artifact, with_encoding, ParseOutput, ParsedEntity, ParsedFact};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::io::Read;
+
+/// Order states that do not commit spending: skipped, not rejected
+const NON_EFFECTIVE_ORDER_STATES: &[&str] = &["cancelada", "eliminada", "guardada"];
+
+/// Currency labels read as Chilean pesos (folded)
+const PESO_LABELS: &[&str] = &["clp", "peso chileno", "pesos chilenos", "peso"];
+
+/// Mercado Público export domains
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Domain {
+    Licitaciones,
+    OrdenesCompra,
+}
+
+impl Domain {
+    /// Domain of a source id, e.g. "mercadopublico-ordenes-compra-2025-01"
+    pub fn from_source_id(source_id: &str) -> Option<Self> {
+        if source_id.starts_with("mercadopublico-licitaciones") {
+            Some(Domain::Licitaciones)
+        } else if source_id.starts_with("mercadopublico-ordenes-compra") {
+            Some(Domain::OrdenesCompra)
+        } else {
+            None
+        }
+    }
+
+    pub fn parser_id(self) -> &'static str {
+        match self {
+            Domain::Licitaciones => "mercadopublico_licitaciones_csv_v1",
+            Domain::OrdenesCompra => "mercadopublico_oc_csv_v1",
+        }
+    }
+}
+
+/// The CSV inside a ZIP export, or the bytes as they are when not zipped
+/// Returns (CSV bytes, ZIP member name)
+pub fn unpack(raw_bytes: &[u8]) -> Result<(Vec<u8>, Option<String>)> {
+    if !raw_bytes.starts_with(b"PK\x03\x04") {
+        return Ok((raw_bytes.to_vec(), None));
+    }
+    let mut archive =
+        zip::ZipArchive::new(std::io::Cursor::new(raw_bytes)).context("Failed to open ZIP export")?;
+    let members: Vec<String> = archive
+        .file_names()
+        .filter(|n| n.to_lowercase().ends_with(".csv"))
+        .map(str::to_string)
+        .collect();
+    let [member] = members.as_slice() else {
+        anyhow::bail!(
+            "AMBIGUITY: ZIP export must hold exactly one CSV, found {}: {:?}",
+            members.len(),
+            members
+        );
+    };
+    let mut bytes = Vec::new();
+    archive
+        .by_name(member)
+        .context("Failed to read ZIP member")?
+        .read_to_end(&mut bytes)
+        .context("Failed to read ZIP member")?;
+    Ok((bytes, Some(member.clone())))
+}
+
+/// Normalize a RUT to "12345678-K", checking its módulo 11 verifier digit
+pub fn rut(text: &str) -> Result<String, String> {
+    let compact: String = text
+        .chars()
+        .filter(|c| !matches!(c, '.' | ' ' | '-'))
+        .collect::<String>()
+        .to_uppercase();
+    let invalid = || Err(format!("Invalid RUT '{}'", text.trim()));
+    if compact.len() < 2 {
+        return invalid();
+    }
+    let (body, dv) = compact.split_at(compact.len() - 1);
+    if body.len() > 8 || !body.chars().all(|c| c.is_ascii_digit()) {
+        return invalid();
+    }
+    let sum: u32 = body
+        .chars()
+        .rev()
+        .zip([2, 3, 4, 5, 6, 7].iter().cycle())
+        .map(|(c, factor)| c.to_digit(10).unwrap_or(0) * factor)
+        .sum();
+    let expected = match 11 - sum % 11 {
+        11 => '0',
+        10 => 'K',
+        d => char::from_digit(d, 10).unwrap_or('?'),
+    };
+    if !dv.starts_with(expected) {
+        return Err(format!("RUT '{}' fails the verifier digit check", text.trim()));
+    }
+    Ok(format!("{}-{}", body.trim_start_matches('0'), dv))
+}
+
+/// Export date: "2025-01-20", optionally with a time, or "20-01-2025"
+fn date(text: &str) -> Result<NaiveDate, String> {
+    let day = text.trim().split([' ', 'T']).next().unwrap_or_default();
+    NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(day, "%d-%m-%Y"))
+        .map_err(|_| format!("Invalid date '{}'", text.trim()))
+}
+
+/// Parse a Mercado Público export (decoded CSV text)
+/// `member` is the ZIP member the text came from, if any, for fact locations
+/// This function is DETERMINISTIC: same input = same output
+pub fn parse_mercadopublico(content: &str, domain: Domain, member: Option<&str>) -> Result<ParseOutput> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .context("Failed to read CSV header")?
+        .iter()
+        .map(str::to_string)
+        .collect();
+    let mapping = SourceRegistry::embedded()?
+        .parser(domain.parser_id())?
+        .map_columns(&headers)?;
+    let table = ExportTable {
+        mapping,
+        member: member.map(str::to_string),
+    };
+
+    // Physical line of each record, counted from its byte offset: descriptions
+    // may hold quoted line breaks, and with CRLF the csv crate reports the
+    // record as starting before the '\n' of the previous line
+    let mut rows = Vec::new();
+    let (mut counted_to, mut line) = (0, 1);
+    for result in reader.records() {
+        let record = result.context("Failed to read CSV record")?;
+        let mut start = record.position().map_or(counted_to, |p| p.byte() as usize);
+        if content[start..].starts_with('\n') {
+            start += 1;
+        }
+        line += content.as_bytes()[counted_to..start].iter().filter(|&&b| b == b'\n').count();
+        counted_to = start;
+        rows.push((line, record));
+    }
+
+    let mut report = ParseReport {
+        column_mapping: table.mapping.clone(),
+        ..Default::default()
+    };
+    let facts = match domain {
+        Domain::Licitaciones => licitacion_facts(&table, &rows, &mut report)?,
+        Domain::OrdenesCompra => orden_compra_facts(&table, &rows, &mut report)?,
+    };
+
+    let mut suppliers: BTreeMap<String, ParsedEntity> = BTreeMap::new();
+    for fact in &facts {
+        let key = fact.dims["proveedor_key"].as_str().unwrap_or_default();
+        suppliers.entry(key.to_string()).or_insert_with(|| ParsedEntity {
+            key: key.to_string(),
+            name: fact.dims["proveedor_nombre"].as_str().unwrap_or_default().to_string(),
+            entity_type: "proveedor".to_string(),
+        });
+    }
+    println!(
+        "Created {} {} facts ({} suppliers)",
+        facts.len(),
+        domain.parser_id(),
+        suppliers.len()
+    );
+
+    Ok(ParseOutput {
+        facts,
+        report,
+        entities: Vec::new(),
+        linked_entities: suppliers.into_values().collect(),
+    })
+}
+
+/// Mapped columns of an export
+struct ExportTable {
+    mapping: Vec<crate::report::ColumnChoice>,
+    member: Option<String>,
+}
+
+impl ExportTable {
+    /// Trimmed cell of a role; empty when the role is unmapped or the cell missing
+    fn cell<'a>(&self, record: &'a csv::StringRecord, role: &str) -> &'a str {
+        column_for(&self.mapping, role)
+            .and_then(|col| record.get(col))
+            .unwrap_or_default()
+    }
+
+    fn location(&self, line: usize) -> String {
+        match &self.member {
+            Some(member) => format!("zip:member='{}':line={}", member, line),
+            None => format!("csv:line={}", line),
+        }
+    }
+}
+
+/// Fields shared by licitación and orden de compra facts
+struct Purchase {
+    organismo_code: String,
+    organismo_name: String,
+    proveedor_rut: String,
+    proveedor_name: String,
+    date: NaiveDate,
+    amount: Decimal,
+}
+
+/// Buyer, supplier, date and amount of a row; Err is the reject reason
+fn purchase(table: &ExportTable, record: &csv::StringRecord, amount_role: &str) -> Result<Purchase, String> {
+    let organismo_code = table.cell(record, "organismo_codigo");
+    if organismo_code.is_empty() {
+        return Err("Missing buyer organism code".to_string());
+    }
+    let amount = numeric::parse(table.cell(record, amount_role))
+        .map_err(|reason| format!("Invalid amount {}", reason))?;
+    Ok(Purchase {
+        organismo_code: organismo_code.to_string(),
+        organismo_name: table.cell(record, "organismo_nombre").to_string(),
+        proveedor_rut: rut(table.cell(record, "proveedor_rut"))?,
+        proveedor_name: table.cell(record, "proveedor_nombre").to_string(),
+        date: date(table.cell(record, "fecha"))?,
+        amount,
+    })
+}
+
+fn purchase_fact(
+    table: &ExportTable,
+    line: usize,
+    purchase: &Purchase,
+    metric: (&str, &str),
+    mut dims: serde_json::Value,
+) -> ParsedFact {
+    dims["organismo_code"] = serde_json::json!(purchase.organismo_code);
+    dims["proveedor_rut"] = serde_json::json!(purchase.proveedor_rut);
+    dims["proveedor_key"] = serde_json::json!(format!("proveedor_{}", purchase.proveedor_rut));
+    dims["proveedor_nombre"] = serde_json::json!(purchase.proveedor_name);
+    ParsedFact {
+        entity_key: format!("organismo_mp_{}", purchase.organismo_code),
+        entity_name: purchase.organismo_name.clone(),
+        entity_type: "organismo".to_string(),
+        entity_parents: Vec::new(),
+        metric_key: metric.0.to_string(),
+        metric_name: metric.1.to_string(),
+        metric_unit: "CLP".to_string(),
+        period_start: purchase.date,
+        period_end: purchase.date,
+        value_num: purchase.amount,
+        location: table.location(line),
+        dims,
+    }
+}
+
+/// One fact per awarded licitación line
+fn licitacion_facts(
+    table: &ExportTable,
+    rows: &[(usize, csv::StringRecord)],
+    report: &mut ParseReport,
+) -> Result<Vec<ParsedFact>> {
+    let mut facts = Vec::new();
+    // (licitación, item, supplier) -> line of the award
+    let mut awarded: BTreeMap<(String, String, String), usize> = BTreeMap::new();
+
+    for (line, record) in rows {
+        // Offers not selected are part of the export but not awards
+        if crate::normalize::fold(table.cell(record, "seleccion")) != "seleccionada" {
+            report.skip();
+            continue;
+        }
+        let codigo = table.cell(record, "codigo");
+        if codigo.is_empty() {
+            report.reject(*line, "Missing licitación code");
+            continue;
+        }
+        let currency = crate::normalize::fold(table.cell(record, "moneda"));
+        if !PESO_LABELS.contains(&currency.as_str()) {
+            report.reject(
+                *line,
+                format!("Amount in '{}' is not converted to CLP", table.cell(record, "moneda")),
+            );
+            continue;
+        }
+        let purchase = match purchase(table, record, "monto") {
+            Ok(p) => p,
+            Err(reason) => {
+                report.reject(*line, reason);
+                continue;
+            }
+        };
+        if purchase.amount.is_zero() {
+            report.skip();
+            continue;
+        }
+        let item = table.cell(record, "item").to_string();
+        let key = (codigo.to_string(), item.clone(), purchase.proveedor_rut.clone());
+        if let Some(first) = awarded.get(&key) {
+            report.reject(
+                *line,
+                format!("Line {} of licitación {} is already awarded to this supplier on line {}", item, codigo, first),
+            );
+            continue;
+        }
+        awarded.insert(key, *line);
+
+        report.accept();
+        facts.push(purchase_fact(
+            table,
+            *line,
+            &purchase,
+            ("licitacion_monto_adjudicado", "Licitaciones - Monto Adjudicado"),
+            serde_json::json!({
+                "licitacion_code": codigo,
+                "licitacion_item": item,
+                "fecha_adjudicacion": purchase.date,
+            }),
+        ));
+    }
+    Ok(facts)
+}
+
+/// An order as first seen in the export
+struct Order {
+    line: usize,
+    purchase: Purchase,
+    estado: String,
+    licitacion: String,
+    lines: usize,
+    /// Set when a later line disagrees with the first one
+    conflict: Option<String>,
+}
+
+/// One fact per orden de compra; its item lines must agree on the order fields
+fn orden_compra_facts(
+    table: &ExportTable,
+    rows: &[(usize, csv::StringRecord)],
+    report: &mut ParseReport,
+) -> Result<Vec<ParsedFact>> {
+    let mut orders: BTreeMap<String, Order> = BTreeMap::new();
+
+    for (line, record) in rows {
+        let codigo = table.cell(record, "codigo");
+        if codigo.is_empty() {
+            report.reject(*line, "Missing orden de compra code");
+            continue;
+        }
+        let estado = table.cell(record, "estado");
+        if NON_EFFECTIVE_ORDER_STATES.contains(&crate::normalize::fold(estado).as_str()) {
+            report.skip();
+            continue;
+        }
+        let purchase = match purchase(table, record, "monto_clp") {
+            Ok(p) => p,
+            Err(reason) => {
+                report.reject(*line, reason);
+                continue;
+            }
+        };
+
+        match orders.get_mut(codigo) {
+            None => {
+                orders.insert(
+                    codigo.to_string(),
+                    Order {
+                        line: *line,
+                        purchase,
+                        estado: estado.to_string(),
+                        licitacion: table.cell(record, "licitacion").to_string(),
+                        lines: 1,
+                        conflict: None,
+                    },
+                );
+            }
+            Some(order) => {
+                order.lines += 1;
+                let first = &order.purchase;
+                let differs = if first.amount != purchase.amount {
+                    Some("total")
+                } else if first.proveedor_rut != purchase.proveedor_rut {
+                    Some("supplier")
+                } else if first.organismo_code != purchase.organismo_code {
+                    Some("buyer")
+                } else if first.date != purchase.date {
+                    Some("date")
+                } else {
+                    None
+                };
+                if let Some(field) = differs {
+                    order.conflict.get_or_insert(format!(
+                        "Orden de compra {} has a different {} on line {} than on line {}",
+                        codigo, field, line, order.line
+                    ));
+                }
+            }
+        }
+    }
+
+    let mut facts = Vec::new();
+    for (codigo, order) in &orders {
+        if let Some(reason) = &order.conflict {
+            report.reject(order.line, reason.clone());
+            continue;
+        }
+        if order.purchase.amount.is_zero() {
+            report.skip();
+            continue;
+        }
+        report.accept();
+        facts.push(purchase_fact(
+            table,
+            order.line,
+            &order.purchase,
+            ("orden_compra_monto", "Órdenes de Compra - Monto Total"),
+            serde_json::json!({
+                "orden_compra_code": codigo,
+                "licitacion_code": (!order.licitacion.is_empty()).then_some(&order.licitacion),
+                "estado": order.estado,
+                "item_lines": order.lines,
+                "fecha_envio": order.purchase.date,
+            }),
+        ));
+    }
+    Ok(facts)
+}
+
+/// Mercado Público export of one domain: CSV, or a ZIP holding one CSV
+pub struct MercadoPublicoParser(pub Domain);
+
+impl Parser for MercadoPublicoParser {
+    fn id(&self) -> &'static str {
+        self.0.parser_id()
+    }
+
+    fn accepts(&self, input: &ArtifactInput) -> bool {
+        Domain::from_source_id(input.source_id) == Some(self.0)
+    }
+
+    fn parse(&self, input: &ArtifactInput) -> Result<ParseOutput> {
+        let (csv_bytes, member) = unpack(input.bytes)?;
+        if let Some(member) = &member {
+            println!("ZIP member: {}", member);
+        }
+        let decoded = decode_artifact(&csv_bytes, input.source_id)?;
+        let output = parse_mercadopublico(&decoded.text, self.0, member.as_deref())?;
+        Ok(with_encoding(output, &decoded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{self, TextEncoding};
+    use crate::test_support::fixture;
+    use rust_decimal_macros::dec;
+
+    /// The Mercado Público exports are Latin-1
+    fn latin1(bytes: &[u8]) -> String {
+        encoding::decode(bytes, Some(TextEncoding::Latin1)).unwrap().text
+    }
+
+    const OC_HEADER: &str = "Codigo;Estado;CodigoOrganismoPublico;OrganismoPublico;FechaEnvio;MontoTotalOC_PesosChilenos;CodigoLicitacion;NombreProveedor;RutSucursal";
+
+    #[test]
+    fn test_rut_check_digit() {
+        assert_eq!(rut("76.086.428-5"), Ok("76086428-5".to_string()));
+        assert_eq!(rut("11.111.111-1"), Ok("11111111-1".to_string()));
+        assert_eq!(rut("12.345.678-5"), Ok("12345678-5".to_string()));
+        assert_eq!(rut("9.999.999-3"), Ok("9999999-3".to_string()));
+        assert_eq!(rut("6.000.000-k"), Ok("6000000-K".to_string()));
+        assert!(rut("76.086.428-4").unwrap_err().contains("verifier"));
+        assert!(rut("").is_err());
+        assert!(rut("12.345.A78-5").is_err());
+    }
+
+    #[test]
+    fn test_fixture_licitaciones() {
+        let output = parse_mercadopublico(
+            &latin1(&fixture("mercadopublico_licitaciones_2025-01.csv")),
+            Domain::Licitaciones,
+            None,
+        )
+        .unwrap();
+        assert!(output.report.enforce(0.0).is_ok(), "{:?}", output.report.rejects);
+        assert_eq!(output.report.accepted_rows, 3);
+        assert_eq!(output.report.skipped_rows, 2);
+        assert_eq!(output.facts.len(), 3);
+
+        let first = &output.facts[0];
+        assert_eq!(first.entity_key, "organismo_mp_7248");
+        assert_eq!(first.entity_name, "Servicio de Salud Metropolitano Norte");
+        assert_eq!(first.metric_key, "licitacion_monto_adjudicado");
+        assert_eq!(first.value_num, dec!(12500000));
+        assert_eq!(first.period_start, NaiveDate::from_ymd_opt(2025, 1, 20).unwrap());
+        assert_eq!(first.location, "csv:line=2");
+        assert_eq!(first.dims["licitacion_code"], "2401-5-LE25");
+        assert_eq!(first.dims["licitacion_item"], "1");
+        assert!(first.dims.get("item_code").is_none());
+        assert_eq!(first.dims["proveedor_key"], "proveedor_76086428-5");
+        assert_eq!(output.facts[1].value_num, dec!(3400000.5));
+
+        let suppliers: Vec<&str> = output.linked_entities.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(suppliers, ["proveedor_76086428-5", "proveedor_77123456-9"]);
+        assert_eq!(output.linked_entities[1].name, "Constructora Ñuble SpA");
+        assert!(output.linked_entities.iter().all(|e| e.entity_type == "proveedor"));
+    }
+
+    #[test]
+    fn test_fixture_ordenes_compra() {
+        let output = parse_mercadopublico(
+            &latin1(&fixture("mercadopublico_ordenes_compra_2025-01.csv")),
+            Domain::OrdenesCompra,
+            Some("2025-1.csv"),
+        )
+        .unwrap();
+        assert!(output.report.enforce(0.0).is_ok(), "{:?}", output.report.rejects);
+        // Three orders; the cancelled one is skipped
+        assert_eq!(output.report.accepted_rows, 3);
+        assert_eq!(output.report.skipped_rows, 1);
+
+        let order = &output.facts[0];
+        assert_eq!(order.dims["orden_compra_code"], "2401-120-SE25");
+        assert_eq!(order.metric_key, "orden_compra_monto");
+        assert_eq!(order.value_num, dec!(14875000));
+        assert_eq!(order.dims["item_lines"], 2);
+        assert_eq!(order.dims["licitacion_code"], "2401-5-LE25");
+        assert_eq!(order.location, "zip:member='2025-1.csv':line=2");
+
+        // Order in UF: the peso amount stated by the export
+        let uf = output.facts.iter().find(|f| f.dims["orden_compra_code"] == "6945-33-AG25").unwrap();
+        assert_eq!(uf.value_num, dec!(1750000));
+        assert_eq!(uf.dims["licitacion_code"], serde_json::Value::Null);
+        assert_eq!(uf.entity_name, "Municipalidad de Providencia");
+    }
+
+    #[test]
+    fn test_order_lines_must_agree() {
+        let csv = format!(
+            "{}\n2401-9-SE25;Aceptada;7248;SSMN;2025-01-10;1000;;Proveedor;76.086.428-5\n2401-9-SE25;Aceptada;7248;SSMN;2025-01-10;2000;;Proveedor;76.086.428-5\n",
+            OC_HEADER
+        );
+        let output = parse_mercadopublico(&csv, Domain::OrdenesCompra, None).unwrap();
+        assert!(output.facts.is_empty());
+        assert_eq!(output.report.rejects[0].line, 2);
+        assert!(output.report.rejects[0].reason.contains("different total on line 3"));
+    }
+
+    #[test]
+    fn test_locations_count_quoted_line_breaks() {
+        let csv = "Nombre;Codigo;Estado;CodigoOrganismoPublico;OrganismoPublico;FechaEnvio;MontoTotalOC_PesosChilenos;NombreProveedor;RutSucursal\r\n\
+                   \"Resmas\r\ncarta\";A-1;Aceptada;7248;SSMN;2025-01-10;1000;Proveedor;76.086.428-5\r\n\
+                   Tóner;A-2;Aceptada;7248;SSMN;2025-01-10;2000;Proveedor;76.086.428-5\r\n";
+        let output = parse_mercadopublico(csv, Domain::OrdenesCompra, None).unwrap();
+        let locations: Vec<&str> = output.facts.iter().map(|f| f.location.as_str()).collect();
+        assert_eq!(locations, ["csv:line=2", "csv:line=4"]);
+    }
+
+    #[test]
+    fn test_invalid_rows_are_rejected() {
+        let csv = format!(
+            "{}\nA-1;Aceptada;7248;SSMN;2025-01-10;1000;;Proveedor;76.086.428-4\nA-2;Aceptada;7248;SSMN;10/01/2025;1000;;Proveedor;76.086.428-5\nA-3;Aceptada;7248;SSMN;2025-01-10;1.000;;Proveedor;76.086.428-5\n",
+            OC_HEADER
+        );
+        let output = parse_mercadopublico(&csv, Domain::OrdenesCompra, None).unwrap();
+        let reasons: Vec<&str> = output.report.rejects.iter().map(|r| r.reason.as_str()).collect();
+        assert!(reasons[0].contains("verifier"));
+        assert!(reasons[1].contains("Invalid date"));
+        assert!(reasons[2].contains("ambiguous"));
+    }
+
+    #[test]
+    fn test_licitacion_in_foreign_currency_is_rejected() {
+        let csv = "CodigoExterno;Codigoitem;CodigoOrganismo;NombreOrganismo;FechaAdjudicacion;RutProveedor;NombreProveedor;Moneda de la Oferta;MontoLineaAdjudica;Oferta seleccionada\n\
+                   1-1-LE25;1;7248;SSMN;2025-01-20;76.086.428-5;Proveedor;Dolar;1500;Seleccionada\n";
+        let output = parse_mercadopublico(csv, Domain::Licitaciones, None).unwrap();
+        assert!(output.facts.is_empty());
+        assert!(output.report.rejects[0].reason.contains("'Dolar' is not converted"));
+    }
+
+    #[test]
+    fn test_missing_required_column_halts() {
+        let err = parse_mercadopublico("Codigo;Estado\nA;Aceptada\n", Domain::OrdenesCompra, None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("AMBIGUITY"));
+    }
+
+    #[test]
+    fn test_unpack_plain_and_zip() {
+        let (bytes, member) = unpack(b"a;b\n1;2\n").unwrap();
+        assert_eq!((bytes.as_slice(), member), (b"a;b\n1;2\n".as_slice(), None));
+
+        let mut zipped = std::io::Cursor::new(Vec::new());
+        let mut writer = zip::ZipWriter::new(&mut zipped);
+        for name in ["2025-1.csv", "leeme.txt"] {
+            writer.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+            std::io::Write::write_all(&mut writer, b"a;b\n").unwrap();
+        }
+        writer.finish().unwrap();
+        let (bytes, member) = unpack(zipped.get_ref()).unwrap();
+        assert_eq!(bytes, b"a;b\n");
+        assert_eq!(member.as_deref(), Some("2025-1.csv"));
+    }
+
+    #[test]
+    fn test_domain_from_source_id() {
+        assert_eq!(
+            Domain::from_source_id("mercadopublico-ordenes-compra-2025-01"),
+            Some(Domain::OrdenesCompra)
+        );
+        assert_eq!(
+            Domain::from_source_id("mercadopublico-licitaciones-2025-01").map(Domain::parser_id),
+            Some("mercadopublico_licitaciones_csv_v1")
+        );
+        assert_eq!(Domain::from_source_id("dipres-ley-presupuestos-2025"), None);
+    }
+}
@@ -0,0 +1,104 @@
+//! Text normalization shared by every parser
+//!
+//! Sources spell the same text in different Unicode forms and encodings
+//! ("Educación" precomposed or as "o" + combining accent, "Ítem" decoded from
+//! Windows-1252, non-breaking spaces). Everything that compares or keys on
+//! text goes through this module:
+//!
+//! - `clean_label`: accents stripped, whitespace collapsed, case kept (headers)
+//! - `fold`: `clean_label` + lowercase + punctuation as spaces (comparisons)
+//! - `slug`: `fold` restricted to `[a-z0-9]` words joined by `_` (entity keys)
+//!
+//! Accents are stripped by NFKD decomposition and dropping combining marks,
+//! so "Ñ" → "N" and compatibility forms ("ﬁ", full-width digits) fold to ASCII.
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// NFKD decomposition without combining marks
+fn strip_diacritics(text: &str) -> impl Iterator<Item = char> + '_ {
+    text.nfkd().filter(|c| !is_combining_mark(*c))
+}
+
+/// Accents stripped and whitespace collapsed, case and punctuation kept
+/// e.g. "  Monto\u{a0}Dólar " -> "Monto Dolar"
+pub fn clean_label(text: &str) -> String {
+    strip_diacritics(text)
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Comparison form: lowercase, no accents, punctuation and whitespace folded
+/// into single spaces. e.g. "Gob. Regional de Valparaíso" -> "gob regional de valparaiso"
+pub fn fold(text: &str) -> String {
+    strip_diacritics(text)
+        .flat_map(char::to_lowercase)
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Stable key: ASCII letters and digits of `fold`, words joined by `_`
+/// e.g. "Ministerio de Educación" -> "ministerio_de_educacion"
+/// Empty when the text has no ASCII letter or digit
+pub fn slug(text: &str) -> String {
+    fold(text)
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_examples() {
+        assert_eq!(clean_label("  Monto\u{a0}Dólar "), "Monto Dolar");
+        assert_eq!(clean_label("Ítem"), "Item");
+        assert_eq!(fold("Gob. Regional de Valparaíso"), "gob regional de valparaiso");
+        assert_eq!(fold("AÑO"), "ano");
+        assert_eq!(slug("Ministerio de Educación"), "ministerio_de_educacion");
+        assert_eq!(slug("Serv. Nacional (SERNAC)"), "serv_nacional_sernac");
+        assert_eq!(slug("Educacio\u{301}n"), "educacion");
+        assert_eq!(slug("ＭＯＰ ２０２４"), "mop_2024");
+        assert_eq!(slug("教育部"), "");
+    }
+
+    proptest! {
+        #[test]
+        fn prop_slug_is_ascii_snake_case(text in "\\PC{0,40}") {
+            let key = slug(&text);
+            prop_assert!(key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'));
+            prop_assert!(!key.starts_with('_') && !key.ends_with('_') && !key.contains("__"));
+        }
+
+        #[test]
+        fn prop_normalizers_are_idempotent(text in "\\PC{0,40}") {
+            prop_assert_eq!(slug(&slug(&text)), slug(&text));
+            prop_assert_eq!(fold(&fold(&text)), fold(&text));
+            prop_assert_eq!(clean_label(&clean_label(&text)), clean_label(&text));
+        }
+
+        #[test]
+        fn prop_unicode_form_does_not_matter(text in "\\PC{0,40}") {
+            let nfc: String = text.nfc().collect();
+            let nfd: String = text.nfd().collect();
+            prop_assert_eq!(fold(&nfc), fold(&nfd));
+            prop_assert_eq!(slug(&nfc), slug(&nfd));
+        }
+
+        #[test]
+        fn prop_spanish_accents_fold_to_ascii(word in "[a-zA-ZáéíóúüñÁÉÍÓÚÜÑ ]{0,30}") {
+            let folded = fold(&word);
+            prop_assert!(folded.is_ascii());
+            prop_assert_eq!(slug(&word), folded.replace(' ', "_"));
+        }
+    }
+}
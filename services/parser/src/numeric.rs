@@ -0,0 +1,182 @@
+//! Amounts written the Chilean way
+//!
+//! Government files write numbers as text in es-CL form ("1.234.567,89"),
+//! sometimes with a currency symbol, parentheses for negatives or a scale
+//! mark ("1.234.567 M$", "12 MM$"). Every parser reads amounts through `parse`:
+//!
+//! - `.` separates thousands in groups of three, `,` is the decimal mark
+//! - a single `.` followed by anything but three digits is a decimal point
+//!   (machine-written "1234.56")
+//! - `(1.234,5)` and `-1.234,5` are negative; `$`, `US$`, `CLP` and `USD` are dropped
+//! - `M$` / `miles de $` scale by a thousand, `MM$` / `millones de $` by a million
+//!
+//! Values that read differently under es-CL and en-US conventions ("1.234",
+//! "1,234", "1,234,567") are rejected instead of guessed (PRINCIPLES.md #3).
+
+use rust_decimal::Decimal;
+
+/// Scale marks, longest first so "MM$" is not read as "M$"
+const SCALES: &[(&str, u32)] = &[
+    ("millones de pesos", 6),
+    ("millones de $", 6),
+    ("millones", 6),
+    ("miles de pesos", 3),
+    ("miles de $", 3),
+    ("miles", 3),
+    ("mm$", 6),
+    ("m$", 3),
+];
+
+/// Currency marks, longest first
+const CURRENCIES: &[&str] = &["us$", "clp$", "clp", "usd", "$"];
+
+/// Parse an es-CL amount; Err carries the reason for the row reject
+pub fn parse(text: &str) -> Result<Decimal, String> {
+    let original = text.trim();
+    let invalid = |why: &str| Err(format!("'{}' {}", original, why));
+
+    let mut rest = original.replace(['\u{a0}', '\u{202f}'], " ").to_lowercase();
+    let mut negative = false;
+    if let Some(inner) = rest.strip_prefix('(').and_then(|r| r.strip_suffix(')')) {
+        negative = true;
+        rest = inner.trim().to_string();
+    }
+    // "-$ 5" and "$ -5" are both negative
+    let mut minus = |rest: &mut String| -> Result<(), String> {
+        if let Some(stripped) = rest.strip_prefix('-') {
+            if negative {
+                return Err(format!("'{}' has more than one negative sign", original));
+            }
+            negative = true;
+            *rest = stripped.trim_start().to_string();
+        }
+        Ok(())
+    };
+    minus(&mut rest)?;
+
+    let mut exponent = 0;
+    for (mark, power) in SCALES {
+        if let Some(stripped) = rest.strip_suffix(mark) {
+            exponent = *power;
+            rest = stripped.trim_end().to_string();
+            break;
+        }
+    }
+    for mark in CURRENCIES {
+        if let Some(stripped) = rest.strip_prefix(mark) {
+            rest = stripped.trim_start().to_string();
+            break;
+        }
+        if let Some(stripped) = rest.strip_suffix(mark) {
+            rest = stripped.trim_end().to_string();
+            break;
+        }
+    }
+    minus(&mut rest)?;
+
+    if rest.is_empty() {
+        return invalid("has no digits");
+    }
+    if !rest.chars().all(|c| c.is_ascii_digit() || c == '.' || c == ',') {
+        return invalid("is not a number");
+    }
+
+    let plain = match (rest.matches('.').count(), rest.matches(',').count()) {
+        (0, 0) => rest.clone(),
+        // "1.234,5" / "1234,5": es-CL decimal comma
+        (dots, 1) => {
+            let (int, frac) = rest.split_once(',').unwrap_or_default();
+            if dots == 0 && frac.len() == 3 {
+                return invalid("is ambiguous: ',' could be a thousands separator");
+            }
+            let Some(int) = thousands(int, dots > 0) else {
+                return invalid("has misplaced thousands separators");
+            };
+            format!("{}.{}", int, frac)
+        }
+        (_, 2..) => return invalid("has more than one ',' (not es-CL)"),
+        // "1234.56": machine-written decimal point
+        (1, 0) if rest.split_once('.').is_some_and(|(_, f)| f.len() != 3) => rest.clone(),
+        (1, 0) => return invalid("is ambiguous: '.' could be a decimal point"),
+        // "1.234.567"
+        (_, 0) => match thousands(&rest, true) {
+            Some(int) => int,
+            None => return invalid("has misplaced thousands separators"),
+        },
+    };
+
+    let Ok(mut value) = plain.parse::<Decimal>() else {
+        return invalid("is not a number");
+    };
+    if plain.starts_with('.') || plain.ends_with('.') {
+        return invalid("has no digits around the decimal mark");
+    }
+    if exponent > 0 {
+        value = match value.checked_mul(Decimal::from(10i64.pow(exponent))) {
+            Some(v) => v,
+            None => return invalid("is out of range"),
+        };
+    }
+    Ok(if negative { -value } else { value })
+}
+
+/// Integer part with optional `.` thousands separators, separators removed
+/// None when the groups are not 1-3 digits followed by groups of exactly 3
+fn thousands(int: &str, grouped: bool) -> Option<String> {
+    if !grouped {
+        return Some(int.to_string());
+    }
+    let mut groups = int.split('.');
+    let first = groups.next()?;
+    if first.is_empty() || first.len() > 3 {
+        return None;
+    }
+    let mut digits = first.to_string();
+    for group in groups {
+        if group.len() != 3 {
+            return None;
+        }
+        digits.push_str(group);
+    }
+    Some(digits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_es_cl_formats() {
+        assert_eq!(parse("1.234.567,89"), Ok(dec!(1234567.89)));
+        assert_eq!(parse("1234,5"), Ok(dec!(1234.5)));
+        assert_eq!(parse("1.234.567"), Ok(dec!(1234567)));
+        assert_eq!(parse("1234.56"), Ok(dec!(1234.56)));
+        assert_eq!(parse(" 1250000000000 "), Ok(dec!(1250000000000)));
+        assert_eq!(parse("0,5"), Ok(dec!(0.5)));
+    }
+
+    #[test]
+    fn test_signs_currency_and_scale() {
+        assert_eq!(parse("(1.234.567)"), Ok(dec!(-1234567)));
+        assert_eq!(parse("-$ 5"), Ok(dec!(-5)));
+        assert_eq!(parse("-1.234,5"), Ok(dec!(-1234.5)));
+        assert_eq!(parse("$ 1.234.567"), Ok(dec!(1234567)));
+        assert_eq!(parse("US$12,5"), Ok(dec!(12.5)));
+        assert_eq!(parse("1.234.567 M$"), Ok(dec!(1234567000)));
+        assert_eq!(parse("12,5 MM$"), Ok(dec!(12500000)));
+        assert_eq!(parse("3 miles de $"), Ok(dec!(3000)));
+        assert_eq!(parse("($ 2 millones)"), Ok(dec!(-2000000)));
+    }
+
+    #[test]
+    fn test_ambiguous_and_invalid_values_are_rejected() {
+        for text in [
+            "1.234", "1,234", "1,234,567", "1.234,567,8", "12.34.567", "1.2345,6", "(-5)", "12x45",
+            "", "$", ",5", "5.", "mil",
+        ] {
+            assert!(parse(text).is_err(), "accepted '{}'", text);
+        }
+        assert!(parse("1,234").unwrap_err().contains("ambiguous"));
+    }
+}
@@ -0,0 +1,106 @@
+//! Partida name catalog (institutional classifier)
+//!
+//! Official Partida names change over time: ministries are renamed or created
+//! (Partida 31 Seguridad Pública is new in 2026, Partida 05 was renamed). The
+//! seed file config/partidas.json maps each code to the name in force per
+//! fiscal year. Embedded at compile time like the economic classifier, and
+//! synced into `partida_catalog` so the API can resolve names per year.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// Official Partida names and the fiscal years each is in force
+const CATALOG_JSON: &str = include_str!("../../../config/partidas.json");
+
+/// One Partida name as declared in the seed file
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct PartidaEntry {
+    /// Two-digit code: "01", "50"
+    pub code: String,
+    pub name: String,
+    pub valid_from: i32,
+    pub valid_to: Option<i32>,
+}
+
+impl PartidaEntry {
+    pub fn is_valid_in(&self, year: i32) -> bool {
+        year >= self.valid_from && self.valid_to.is_none_or(|to| year <= to)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CatalogFile {
+    version: String,
+    entries: Vec<PartidaEntry>,
+}
+
+/// The Partida name catalog
+#[derive(Debug)]
+pub struct PartidaCatalog {
+    pub version: String,
+    pub entries: Vec<PartidaEntry>,
+}
+
+impl PartidaCatalog {
+    /// Load the catalog embedded in the binary
+    pub fn embedded() -> Result<Self> {
+        Self::from_json(CATALOG_JSON)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: CatalogFile =
+            serde_json::from_str(json).context("Failed to parse partida catalog")?;
+        Ok(Self {
+            version: file.version,
+            entries: file.entries,
+        })
+    }
+
+    /// Official name of a Partida in force during `year`, if cataloged
+    pub fn lookup(&self, year: i32, code: &str) -> Option<&PartidaEntry> {
+        let code = format!("{:0>2}", code.trim());
+        self.entries
+            .iter()
+            .find(|e| e.code == code && e.is_valid_in(year))
+    }
+
+    /// Name for display: the cataloged name, or "Partida NN" when the code
+    /// has no name for that year
+    pub fn name(&self, year: i32, code: &str) -> String {
+        match self.lookup(year, code) {
+            Some(entry) => entry.name.clone(),
+            None => format!("Partida {:0>2}", code.trim()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_catalog_names_by_year() {
+        let catalog = PartidaCatalog::embedded().unwrap();
+        assert_eq!(catalog.name(2026, "09"), "Ministerio de Educación");
+        assert_eq!(catalog.name(2025, "05"), "Ministerio del Interior y Seguridad Pública");
+        assert_eq!(catalog.name(2026, "5"), "Ministerio del Interior");
+        assert_eq!(catalog.name(2026, "31"), "Ministerio de Seguridad Pública");
+        assert_eq!(catalog.name(2025, "31"), "Partida 31");
+        assert_eq!(catalog.name(2026, "99"), "Partida 99");
+    }
+
+    #[test]
+    fn test_embedded_catalog_periods_do_not_overlap() {
+        let catalog = PartidaCatalog::embedded().unwrap();
+        for (i, a) in catalog.entries.iter().enumerate() {
+            for b in &catalog.entries[i + 1..] {
+                if a.code != b.code {
+                    continue;
+                }
+                let a_to = a.valid_to.unwrap_or(i32::MAX);
+                let b_to = b.valid_to.unwrap_or(i32::MAX);
+                assert!(a_to < b.valid_from || b_to < a.valid_from, "overlap for {}", a.code);
+            }
+        }
+    }
+}
@@ -0,0 +1,445 @@
+//! PDF layout specs: how to read a table out of a report's text
+//!
+//! Contraloría and DIPRES publish some reports only as PDF. When the PDF has
+//! a text layer, its tables can be read back from the position of each string
+//! (`pdf_text`). config/pdf_layouts.json declares, per source family:
+//!
+//! - `header.detect`: labels (folded) that must all be texts of one line for
+//!   it to be the table header; the header repeats on every page
+//! - `row_tolerance`: points two baselines may differ and still be one row
+//! - `footnote_prefixes`: the table ends at the first line whose first text
+//!   starts with one of them ("Fuente", "Nota")
+//! - `columns`: column roles mapped from the header labels, as a parser spec
+//! - `metrics`: the metric each amount role produces
+//!
+//! Strings on a line closer than `MERGE_GAP` ems are one text ("Presupuesto"
+//! and "Vigente" shown separately). Columns are the horizontal spans of the
+//! header texts: every text of a data line must overlap exactly one of them,
+//! otherwise the page does not match the layout and the parser halts
+//! (PRINCIPLES.md #3). Pages without the header (cover, notes) are skipped;
+//! a document without any, or whose pages disagree on the header, halts.
+//! Rows are numbered within their table, so provenance reads
+//! `page=N; table=T; row=R`.
+
+use crate::normalize;
+use crate::pdf_text::{PageText, TextFragment};
+use crate::sources::ParserSpec;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// How to find and read the table of each PDF report, per source family
+const LAYOUTS_JSON: &str = include_str!("../../../config/pdf_layouts.json");
+
+/// Largest gap between two strings of one text, in ems of their font size
+const MERGE_GAP: f64 = 0.6;
+
+/// Gaps below this (ems) are kerning inside a word, not a space
+const SPACE_GAP: f64 = 0.15;
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct PdfHeaderSpec {
+    pub detect: Vec<String>,
+}
+
+/// Metric produced by an amount column
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct MetricSpec {
+    pub key: String,
+    pub name: String,
+    pub unit: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PdfLayout {
+    pub source_family: String,
+    pub name: String,
+    pub header: PdfHeaderSpec,
+    #[serde(default = "default_row_tolerance")]
+    pub row_tolerance: f64,
+    #[serde(default)]
+    pub footnote_prefixes: Vec<String>,
+    pub columns: ParserSpec,
+    /// Amount role -> metric
+    pub metrics: BTreeMap<String, MetricSpec>,
+}
+
+fn default_row_tolerance() -> f64 {
+    2.0
+}
+
+#[derive(Debug, Deserialize)]
+struct LayoutsFile {
+    version: String,
+    layouts: Vec<PdfLayout>,
+}
+
+#[derive(Debug)]
+pub struct PdfLayouts {
+    pub version: String,
+    pub layouts: Vec<PdfLayout>,
+}
+
+impl PdfLayouts {
+    /// Load the layouts embedded in the binary
+    pub fn embedded() -> Result<Self> {
+        Self::from_json(LAYOUTS_JSON)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: LayoutsFile =
+            serde_json::from_str(json).context("Failed to parse PDF layouts")?;
+        for layout in &file.layouts {
+            let family = &layout.source_family;
+            if layout.header.detect.is_empty() {
+                anyhow::bail!("PDF layout '{}' has no header labels", family);
+            }
+            if layout
+                .footnote_prefixes
+                .iter()
+                .any(|p| normalize::fold(p).is_empty())
+            {
+                anyhow::bail!(
+                    "PDF layout '{}' has a footnote prefix without letters or digits",
+                    family
+                );
+            }
+            let roles = &layout.columns.column_roles;
+            for role in ["partida", "denominacion"]
+                .iter()
+                .copied()
+                .chain(layout.metrics.keys().map(String::as_str))
+            {
+                if !roles.contains_key(role) {
+                    anyhow::bail!("PDF layout '{}' has no column role '{}'", family, role);
+                }
+            }
+        }
+        Ok(Self {
+            version: file.version,
+            layouts: file.layouts,
+        })
+    }
+
+    /// Layout for a source, matched by family
+    pub fn for_source(&self, source_id: &str) -> Option<&PdfLayout> {
+        let family = crate::fingerprint::source_family(source_id);
+        self.layouts.iter().find(|l| l.source_family == family)
+    }
+}
+
+/// A data line of a table: one cell per header column, "" when empty
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfRow {
+    /// 1-based line number within the table
+    pub row: usize,
+    pub cells: Vec<String>,
+}
+
+/// One table occurrence: a header line and the data lines under it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfTable {
+    pub page: u32,
+    /// 1-based table number within the page
+    pub table: usize,
+    pub headers: Vec<String>,
+    /// Lines between the top of the page (or the previous table) and the header
+    pub title: Vec<String>,
+    pub rows: Vec<PdfRow>,
+}
+
+impl PdfTable {
+    /// Provenance of a row, e.g. "page=2; table=1; row=7"
+    pub fn location(&self, row: usize) -> String {
+        format!("page={}; table={}; row={}", self.page, self.table, row)
+    }
+}
+
+/// Horizontal span of a header text
+struct Column {
+    x: f64,
+    x_end: f64,
+}
+
+impl PdfLayout {
+    /// Every table occurrence of the document, in page order
+    pub fn extract(&self, pages: &[PageText]) -> Result<Vec<PdfTable>> {
+        let wanted: Vec<String> = self
+            .header
+            .detect
+            .iter()
+            .map(|l| normalize::fold(l))
+            .collect();
+        let prefixes: Vec<String> = self
+            .footnote_prefixes
+            .iter()
+            .map(|p| normalize::fold(p))
+            .collect();
+        let mut tables: Vec<PdfTable> = Vec::new();
+
+        for page in pages {
+            let mut title: Vec<String> = Vec::new();
+            let mut current: Option<(PdfTable, Vec<Column>)> = None;
+            let mut count = 0;
+            for line in lines(page, self.row_tolerance) {
+                let folded: Vec<String> = line.iter().map(|f| normalize::fold(&f.text)).collect();
+                if wanted.iter().all(|w| folded.contains(w)) {
+                    tables.extend(current.take().map(|(t, _)| t));
+                    count += 1;
+                    let table = PdfTable {
+                        page: page.page,
+                        table: count,
+                        headers: line
+                            .iter()
+                            .map(|f| normalize::clean_label(&f.text))
+                            .collect(),
+                        title: std::mem::take(&mut title),
+                        rows: Vec::new(),
+                    };
+                    let columns = line
+                        .iter()
+                        .map(|f| Column {
+                            x: f.x,
+                            x_end: f.x_end,
+                        })
+                        .collect();
+                    current = Some((table, columns));
+                    continue;
+                }
+
+                let Some((table, columns)) = current.as_mut() else {
+                    title.push(
+                        line.iter()
+                            .map(|f| f.text.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    );
+                    continue;
+                };
+                if prefixes.iter().any(|p| folded[0].starts_with(p.as_str())) {
+                    tables.extend(current.take().map(|(t, _)| t));
+                    continue;
+                }
+                let row = table.rows.len() + 1;
+                let mut cells = vec![String::new(); columns.len()];
+                for fragment in &line {
+                    let under: Vec<usize> = columns
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, c)| fragment.x < c.x_end && fragment.x_end > c.x)
+                        .map(|(i, _)| i)
+                        .collect();
+                    let [col] = under.as_slice() else {
+                        anyhow::bail!(
+                            "AMBIGUITY: '{}' at {} lies under {} columns of layout '{}'; the page does not match the layout",
+                            fragment.text,
+                            table.location(row),
+                            under.len(),
+                            self.source_family
+                        );
+                    };
+                    if !cells[*col].is_empty() {
+                        cells[*col].push(' ');
+                    }
+                    cells[*col].push_str(&fragment.text);
+                }
+                table.rows.push(PdfRow { row, cells });
+            }
+            tables.extend(current.map(|(t, _)| t));
+        }
+
+        let Some(first) = tables.first() else {
+            anyhow::bail!(
+                "AMBIGUITY: No page has a table header with {:?} (layout '{}')",
+                self.header.detect,
+                self.source_family
+            );
+        };
+        if let Some(other) = tables.iter().find(|t| t.headers != first.headers) {
+            anyhow::bail!(
+                "AMBIGUITY: Table header on page {} {:?} differs from page {} {:?}",
+                other.page,
+                other.headers,
+                first.page,
+                first.headers
+            );
+        }
+        Ok(tables)
+    }
+}
+
+/// Lines of a page, top to bottom: strings whose baselines are within
+/// `tolerance`, left to right, with close strings merged into one text
+fn lines(page: &PageText, tolerance: f64) -> Vec<Vec<TextFragment>> {
+    let mut fragments: Vec<&TextFragment> = page.fragments.iter().collect();
+    fragments.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+
+    let mut grouped: Vec<Vec<&TextFragment>> = Vec::new();
+    for fragment in fragments {
+        match grouped.last_mut() {
+            Some(line) if (line[0].y - fragment.y).abs() <= tolerance => line.push(fragment),
+            _ => grouped.push(vec![fragment]),
+        }
+    }
+
+    grouped
+        .into_iter()
+        .map(|mut line| {
+            line.sort_by(|a, b| a.x.total_cmp(&b.x));
+            let mut merged: Vec<TextFragment> = Vec::new();
+            for fragment in line {
+                match merged.last_mut() {
+                    Some(last)
+                        if fragment.x - last.x_end <= MERGE_GAP * last.size.max(fragment.size) =>
+                    {
+                        if fragment.x - last.x_end >= SPACE_GAP * last.size.max(fragment.size) {
+                            last.text.push(' ');
+                        }
+                        last.text.push_str(&fragment.text);
+                        last.x_end = last.x_end.max(fragment.x_end);
+                    }
+                    _ => merged.push(fragment.clone()),
+                }
+            }
+            merged
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A string at (x, y) with a 10 pt font, 5 points per character
+    fn text(x: f64, y: f64, text: &str) -> TextFragment {
+        TextFragment {
+            x,
+            x_end: x + 5.0 * text.chars().count() as f64,
+            y,
+            size: 10.0,
+            text: text.to_string(),
+        }
+    }
+
+    fn layout() -> PdfLayout {
+        PdfLayouts::embedded()
+            .unwrap()
+            .for_source("dipres-informe-ejecucion-anual-2024")
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn test_embedded_layouts() {
+        let layouts = PdfLayouts::embedded().unwrap();
+        let contraloria = layouts
+            .for_source("contraloria-ejecucion-presupuestaria-2024")
+            .unwrap();
+        assert_eq!(
+            contraloria.metrics["devengado"].key,
+            "contraloria_gasto_devengado"
+        );
+        assert!(layouts.for_source("dipres-ley-presupuestos-2024").is_none());
+    }
+
+    #[test]
+    fn test_layout_without_role_for_metric_is_rejected() {
+        let json = r#"{"version": "t", "layouts": [{
+            "source_family": "x", "name": "x", "header": {"detect": ["Partida"]},
+            "columns": {"column_roles": {"partida": ["Partida"], "denominacion": ["Nombre"]}},
+            "metrics": {"monto": {"key": "k", "name": "n", "unit": "CLP"}}
+        }]}"#;
+        let err = PdfLayouts::from_json(json).unwrap_err().to_string();
+        assert!(err.contains("no column role 'monto'"), "{}", err);
+    }
+
+    #[test]
+    fn test_extract_tables_across_pages() {
+        let header = |y: f64| {
+            vec![
+                text(50.0, y, "Partida"),
+                text(100.0, y, "Denominación"),
+                // Two strings close enough to be one label
+                text(250.0, y, "Ley"),
+                text(270.0, y, "Inicial"),
+                text(350.0, y, "Ejecución"),
+            ]
+        };
+        let page1 = PageText {
+            page: 1,
+            fragments: [
+                vec![
+                    text(50.0, 780.0, "Ejecución Presupuestaria 2024"),
+                    text(50.0, 765.0, "Miles de $"),
+                ],
+                header(740.0),
+                // Baselines 1 point apart are one row
+                vec![
+                    text(55.0, 720.0, "09"),
+                    text(100.0, 720.5, "Educación"),
+                    text(250.0, 720.0, "1.000.000"),
+                    text(350.0, 719.5, "950.000,0"),
+                ],
+                vec![text(100.0, 705.0, "(continuación)")],
+            ]
+            .concat(),
+        };
+        let page2 = PageText {
+            page: 2,
+            fragments: [
+                header(740.0),
+                vec![text(55.0, 720.0, "16"), text(250.0, 720.0, "2.000.000")],
+                vec![text(50.0, 700.0, "Fuente: DIPRES")],
+                vec![text(50.0, 690.0, "16"), text(250.0, 690.0, "ignored")],
+            ]
+            .concat(),
+        };
+
+        let tables = layout().extract(&[page1, page2]).unwrap();
+        assert_eq!(tables.len(), 2);
+        assert_eq!(
+            tables[0].headers,
+            ["Partida", "Denominacion", "Ley Inicial", "Ejecucion"]
+        );
+        assert_eq!(
+            tables[0].title,
+            ["Ejecución Presupuestaria 2024", "Miles de $"]
+        );
+        assert_eq!(
+            tables[0].rows[0].cells,
+            ["09", "Educación", "1.000.000", "950.000,0"]
+        );
+        assert_eq!(tables[0].rows[1].cells, ["", "(continuación)", "", ""]);
+        assert_eq!(tables[1].location(1), "page=2; table=1; row=1");
+        // Lines after the footnote are not part of the table
+        assert_eq!(tables[1].rows.len(), 1);
+    }
+
+    #[test]
+    fn test_text_between_columns_halts() {
+        let page = PageText {
+            page: 3,
+            fragments: vec![
+                text(50.0, 740.0, "Partida"),
+                text(100.0, 740.0, "Ley Inicial"),
+                text(250.0, 740.0, "Ejecución"),
+                text(50.0, 720.0, "09"),
+                // Between "Ley Inicial" (100-155) and "Ejecución" (250-295)
+                text(180.0, 720.0, "1.000.000"),
+            ],
+        };
+        let err = layout().extract(&[page]).unwrap_err().to_string();
+        assert!(
+            err.contains("AMBIGUITY") && err.contains("page=3; table=1; row=1"),
+            "{}",
+            err
+        );
+
+        let cover = PageText {
+            page: 1,
+            fragments: vec![text(50.0, 740.0, "Informe de Ejecución")],
+        };
+        let err = layout().extract(&[cover]).unwrap_err().to_string();
+        assert!(err.contains("No page has a table header"), "{}", err);
+    }
+}
@@ -0,0 +1,445 @@
+//! Budget tables read from text-based PDF reports (Contraloría, DIPRES)
+//!
+//! One parser for every PDF source with a layout in config/pdf_layouts.json:
+//! `pdf_text` reads the positioned strings, `pdf_layout` rebuilds the table,
+//! and this module turns its rows into facts. Each row is a Partida:
+//!
+//! - a Partida code: one fact per amount column, metric from the layout
+//! - "Total" in the code or denominación column: control row, checked against
+//!   the sum of every Partida instead of emitted
+//! - no code otherwise: a wrapped denominación or a heading, skipped
+//!
+//! Amounts are in the unit the title block states ("Miles de $") and cover
+//! the fiscal year of the source id. "-" means no amount. A Partida
+//! appearing twice halts the parser.
+
+use crate::numeric;
+use crate::partidas::PartidaCatalog;
+use crate::pdf_layout::{MetricSpec, PdfLayout, PdfLayouts, PdfTable};
+use crate::pdf_text;
+use crate::report::ParseReport;
+use crate::sources::column_for;
+use crate::registry::{ArtifactInput, Parser};
+use crate::{is_pdf_file, unit_scale, year_period, ParseOutput, ParsedFact};
+use anyhow::{Context, Result};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+/// Parser identifier for PDF tables read through a layout spec
+pub const PARSER_ID: &str = "pdf_table_v1";
+
+/// Parse a text-based PDF report of a source with a PDF layout
+/// This function is DETERMINISTIC: same PDF = same output
+pub fn parse_pdf_tables(bytes: &[u8], source_id: &str) -> Result<ParseOutput> {
+    let year = crate::fiscal_year_from_source_id(source_id).with_context(|| {
+        format!(
+            "AMBIGUITY: Cannot extract fiscal year from source_id '{}'",
+            source_id
+        )
+    })?;
+    let layouts = PdfLayouts::embedded()?;
+    let layout = layouts
+        .for_source(source_id)
+        .with_context(|| format!("No PDF layout for source '{}'", source_id))?;
+    println!(
+        "PDF layout: {} (pdf_layouts v{})",
+        layout.name, layouts.version
+    );
+
+    let pages = pdf_text::extract_pages(bytes)?;
+    if pages.iter().all(|p| p.fragments.is_empty()) {
+        anyhow::bail!(
+            "AMBIGUITY: PDF has no text layer on any of its {} pages (scanned?); it cannot be read without OCR",
+            pages.len()
+        );
+    }
+    let tables = layout.extract(&pages)?;
+    println!("Found {} table(s) in {} pages", tables.len(), pages.len());
+
+    parse_tables(layout, &tables, year)
+}
+
+/// Amount cell in the table unit; None when empty or "-"
+fn amount(text: &str) -> Result<Option<Decimal>, String> {
+    match text.trim() {
+        "" | "-" | "–" | "—" => Ok(None),
+        other => numeric::parse(other).map(Some),
+    }
+}
+
+/// One Partida row: (amount column, amount) in the table unit
+struct PartidaRow {
+    location: String,
+    denominacion: String,
+    values: Vec<(usize, Decimal)>,
+}
+
+/// A "Total" row: (amount column, amount) in the table unit
+struct ControlRow {
+    location: String,
+    values: Vec<(usize, Decimal)>,
+}
+
+/// Turn the tables of a document (same header on every page) into facts
+fn parse_tables(layout: &PdfLayout, tables: &[PdfTable], year: i32) -> Result<ParseOutput> {
+    let title: Vec<String> = tables
+        .iter()
+        .flat_map(|t| t.title.iter().cloned())
+        .collect();
+    let scale = unit_scale(&title)?;
+
+    let mapping = layout.columns.map_columns(&tables[0].headers)?;
+    let partida_col = column_for(&mapping, "partida").context("No partida column")?;
+    let denominacion_col =
+        column_for(&mapping, "denominacion").context("No denominacion column")?;
+    // (role, metric, table column)
+    let columns: Vec<(&str, &MetricSpec, usize)> = layout
+        .metrics
+        .iter()
+        .filter_map(|(role, metric)| {
+            column_for(&mapping, role).map(|col| (role.as_str(), metric, col))
+        })
+        .collect();
+    if columns.is_empty() {
+        anyhow::bail!(
+            "AMBIGUITY: No amount column found in layout '{}'",
+            layout.source_family
+        );
+    }
+
+    let mut report = ParseReport {
+        column_mapping: mapping,
+        ..Default::default()
+    };
+    let mut partidas: BTreeMap<String, PartidaRow> = BTreeMap::new();
+    let mut controls: Vec<ControlRow> = Vec::new();
+    // Table rows restart on every page; rejects are numbered across the document
+    let mut line = 0;
+
+    for table in tables {
+        'rows: for row in &table.rows {
+            line += 1;
+            let location = table.location(row.row);
+            let cell = |col: usize| row.cells.get(col).map_or("", |c| c.trim());
+            let mut values = Vec::new();
+            for (i, &(_, _, col)) in columns.iter().enumerate() {
+                match amount(cell(col)) {
+                    Ok(Some(v)) => values.push((i, v)),
+                    Ok(None) => {}
+                    Err(reason) => {
+                        report.reject(line, format!("{}: {}", location, reason));
+                        continue 'rows;
+                    }
+                }
+            }
+
+            let (code, denominacion) = (cell(partida_col), cell(denominacion_col));
+            let label = if code.is_empty() { denominacion } else { code };
+            if crate::normalize::fold(label).starts_with("total") {
+                report.control();
+                controls.push(ControlRow { location, values });
+                continue;
+            }
+            if code.is_empty() {
+                report.skip();
+                continue;
+            }
+            if code.len() > 2 || !code.chars().all(|c| c.is_ascii_digit()) {
+                report.reject(
+                    line,
+                    format!("{}: Invalid Partida code '{}'", location, code),
+                );
+                continue;
+            }
+            let code = format!("{:0>2}", code);
+            if let Some(previous) = partidas.get(&code) {
+                anyhow::bail!(
+                    "AMBIGUITY: Partida {} appears at {} and {}",
+                    code,
+                    previous.location,
+                    location
+                );
+            }
+            partidas.insert(
+                code,
+                PartidaRow {
+                    location,
+                    denominacion: denominacion.to_string(),
+                    values,
+                },
+            );
+        }
+    }
+
+    check_control_rows(&mut report, &columns, &partidas, &controls);
+
+    let (period_start, period_end) = year_period(year)?;
+    let partida_names = PartidaCatalog::embedded()?;
+    let mut facts = Vec::new();
+    for (code, partida) in &partidas {
+        for &(i, value) in &partida.values {
+            if value.is_zero() {
+                continue;
+            }
+            let (role, metric, _) = columns[i];
+            facts.push(ParsedFact {
+                entity_key: format!("partida_{}", code),
+                entity_name: partida_names.name(year, code),
+                entity_type: "partida".to_string(),
+                entity_parents: Vec::new(),
+                metric_key: metric.key.clone(),
+                metric_name: metric.name.clone(),
+                metric_unit: metric.unit.clone(),
+                period_start,
+                period_end,
+                value_num: value * scale,
+                location: partida.location.clone(),
+                dims: serde_json::json!({
+                    "partida_code": code,
+                    "denominacion": partida.denominacion,
+                    "column": role,
+                }),
+            });
+        }
+        report.accept();
+    }
+
+    let expected: Decimal = partidas
+        .values()
+        .flat_map(|p| p.values.iter().map(|(_, v)| *v * scale))
+        .sum();
+    let actual: Decimal = facts.iter().map(|f| f.value_num).sum();
+    report.check_total("rows_vs_facts_clp", expected, actual);
+
+    if facts.is_empty() {
+        anyhow::bail!(
+            "AMBIGUITY: No Partida rows with amounts in layout '{}'",
+            layout.source_family
+        );
+    }
+    println!("Created {} PDF table facts", facts.len());
+
+    Ok(ParseOutput {
+        facts,
+        report,
+        entities: Vec::new(),
+        linked_entities: Vec::new(),
+    })
+}
+
+/// Each "Total" row must equal, column by column, the sum of every Partida
+fn check_control_rows(
+    report: &mut ParseReport,
+    columns: &[(&str, &MetricSpec, usize)],
+    partidas: &BTreeMap<String, PartidaRow>,
+    controls: &[ControlRow],
+) {
+    let mut detail: BTreeMap<usize, Decimal> = BTreeMap::new();
+    for &(i, value) in partidas.values().flat_map(|p| &p.values) {
+        *detail.entry(i).or_default() += value;
+    }
+    let (mut expected_all, mut actual_all) = (Decimal::ZERO, Decimal::ZERO);
+    for control in controls {
+        let stated: BTreeMap<usize, Decimal> = control.values.iter().copied().collect();
+        for (i, &(role, _, _)) in columns.iter().enumerate() {
+            let expected = stated.get(&i).copied().unwrap_or_default();
+            let actual = detail.get(&i).copied().unwrap_or_default();
+            expected_all += expected;
+            actual_all += actual;
+            if expected != actual {
+                report.check_total(
+                    format!("control_total:{}:{}", control.location, role),
+                    expected,
+                    actual,
+                );
+            }
+        }
+    }
+    if !controls.is_empty() {
+        report.check_total("control_totals", expected_all, actual_all);
+    }
+}
+
+/// Text-based PDF report: table rebuilt through the source's layout spec
+pub struct PdfTableParser;
+
+impl Parser for PdfTableParser {
+    fn id(&self) -> &'static str {
+        PARSER_ID
+    }
+
+    fn accepts(&self, input: &ArtifactInput) -> bool {
+        is_pdf_file(input.mime_type, input.storage_path)
+    }
+
+    fn parse(&self, input: &ArtifactInput) -> Result<ParseOutput> {
+        parse_pdf_tables(input.bytes, input.source_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture;
+    use crate::pdf_layout::PdfRow;
+    use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
+
+    const SOURCE_ID: &str = "contraloria-ejecucion-presupuestaria-2024";
+
+    #[test]
+    fn test_fixture_facts_with_page_provenance() {
+        let output =
+            parse_pdf_tables(&fixture("contraloria_ejecucion_2024.pdf"), SOURCE_ID).unwrap();
+        let report = &output.report;
+        assert!(report.enforce(0.0).is_ok(), "{:?}", report.checks);
+        assert_eq!(report.accepted_rows, 5);
+        assert_eq!(report.control_rows, 1);
+        // The wrapped "Derechos Humanos" line
+        assert_eq!(report.skipped_rows, 1);
+        assert!(report
+            .checks
+            .iter()
+            .any(|c| c.name == "control_totals" && c.ok));
+
+        let count = |metric: &str| {
+            output
+                .facts
+                .iter()
+                .filter(|f| f.metric_key == metric)
+                .count()
+        };
+        assert_eq!(count("contraloria_presupuesto_vigente"), 5);
+        assert_eq!(count("contraloria_gasto_devengado"), 5);
+        // Partida 18 pagado is "-"
+        assert_eq!(count("contraloria_gasto_pagado"), 4);
+
+        let devengado = |entity: &str| {
+            output
+                .facts
+                .iter()
+                .find(|f| f.entity_key == entity && f.metric_key == "contraloria_gasto_devengado")
+                .unwrap()
+        };
+        let educacion = devengado("partida_09");
+        assert_eq!(educacion.entity_name, "Ministerio de Educación");
+        assert_eq!(educacion.value_num, dec!(14100250000));
+        assert_eq!(educacion.location, "page=2; table=1; row=1");
+        assert_eq!(
+            educacion.period_start,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+        );
+        assert_eq!(
+            educacion.period_end,
+            NaiveDate::from_ymd_opt(2024, 12, 31).unwrap()
+        );
+        assert_eq!(educacion.dims["denominacion"], "Ministerio de Educación");
+        // Second page of the table (compressed content stream), header repeated
+        assert_eq!(devengado("partida_16").location, "page=3; table=1; row=1");
+        assert_eq!(devengado("partida_12").location, "page=2; table=1; row=4");
+    }
+
+    #[test]
+    fn test_fixture_output_is_deterministic() {
+        let bytes = fixture("contraloria_ejecucion_2024.pdf");
+        let a = parse_pdf_tables(&bytes, SOURCE_ID).unwrap();
+        let b = parse_pdf_tables(&bytes, SOURCE_ID).unwrap();
+        assert_eq!(a.facts, b.facts);
+        assert_eq!(a.report, b.report);
+    }
+
+    #[test]
+    fn test_layout_mismatch_halts() {
+        let err = parse_pdf_tables(
+            &fixture("contraloria_ejecucion_2024_bad_layout.pdf"),
+            SOURCE_ID,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            err.contains("AMBIGUITY") && err.contains("page=3; table=1; row=2"),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains("'-'") && err.contains("under 0 columns"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_source_without_layout_or_year_halts() {
+        let bytes = fixture("contraloria_ejecucion_2024.pdf");
+        let err = parse_pdf_tables(&bytes, "contraloria-otro-informe-2024")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("No PDF layout"), "{}", err);
+        let err = parse_pdf_tables(&bytes, "contraloria-ejecucion-presupuestaria")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("fiscal year"), "{}", err);
+    }
+
+    #[test]
+    fn test_rejects_on_two_pages_have_distinct_lines() {
+        let layouts = PdfLayouts::embedded().unwrap();
+        let layout = layouts.for_source(SOURCE_ID).unwrap();
+        let page = |page: u32, rows: &[[&str; 5]]| PdfTable {
+            page,
+            table: 1,
+            headers: [
+                "Partida",
+                "Denominación",
+                "Presupuesto Vigente",
+                "Gasto Devengado",
+                "Gasto Pagado",
+            ]
+            .map(String::from)
+            .to_vec(),
+            title: vec!["Miles de $".to_string()],
+            rows: rows
+                .iter()
+                .enumerate()
+                .map(|(i, cells)| PdfRow {
+                    row: i + 1,
+                    cells: cells.map(String::from).to_vec(),
+                })
+                .collect(),
+        };
+        // An ambiguous amount on page 2 and a bad code on page 3, both on row 1
+        let tables = [
+            page(
+                2,
+                &[
+                    ["09", "Educación", "100", "1.500", "50"],
+                    ["12", "Obras Públicas", "80", "70", "60"],
+                ],
+            ),
+            page(
+                3,
+                &[
+                    ["X1", "Otra", "10", "10", "10"],
+                    ["16", "Salud", "90", "80", "70"],
+                ],
+            ),
+        ];
+
+        let output = parse_tables(layout, &tables, 2024).unwrap();
+        let rejects = &output.report.rejects;
+        assert_eq!(rejects.len(), 2);
+        assert_eq!(rejects[0].line, 1);
+        assert!(rejects[0].reason.starts_with("page=2; table=1; row=1:"), "{}", rejects[0].reason);
+        assert_eq!(rejects[1].line, 3);
+        assert!(rejects[1].reason.starts_with("page=3; table=1; row=1:"), "{}", rejects[1].reason);
+        assert_eq!(output.report.accepted_rows, 2);
+    }
+
+    #[test]
+    fn test_amount_cells() {
+        assert_eq!(amount(" - "), Ok(None));
+        assert_eq!(amount(""), Ok(None));
+        assert_eq!(amount("(1.500.000)"), Ok(Some(dec!(-1500000))));
+        assert!(amount("1.500").is_err());
+    }
+}
@@ -0,0 +1,701 @@
+//! Positioned text of a text-based PDF
+//!
+//! A PDF has no tables, only strings painted at coordinates. This module walks
+//! each page's content stream and returns every string it shows (`Tj`, `TJ`,
+//! `'`, `"`) with its baseline and horizontal span in page space, decoded to
+//! Unicode. There is no OCR: a scanned page simply has no text, and a font
+//! whose codes cannot be mapped to Unicode halts the parser instead of
+//! producing mojibake (PRINCIPLES.md #3).
+//!
+//! Decoding, in order of preference:
+//! - the font's `/ToUnicode` CMap (`bfchar` and `bfrange`)
+//! - simple fonts: `/Encoding` WinAnsi, MacRoman or Standard (Standard when
+//!   absent); an encoding with `/Differences` needs a ToUnicode CMap
+//!
+//! Widths come from `/Widths` (simple fonts) or `/W` and `/DW` (Type0). The
+//! standard 14 fonts declare no widths and are measured at `DEFAULT_WIDTH`.
+//! Text inside form XObjects is not read.
+
+use anyhow::{Context, Result};
+use lopdf::{Dictionary, Document, Object};
+use std::collections::BTreeMap;
+
+/// Glyph width (thousandths of an em) of simple fonts that declare none
+const DEFAULT_WIDTH: f64 = 500.0;
+
+/// A `TJ` adjustment of at least this much (thousandths of an em) reads as a space
+const WORD_GAP: f64 = 250.0;
+
+/// One shown string: baseline `y` and span `x..x_end` in points, origin bottom-left
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextFragment {
+    pub x: f64,
+    pub x_end: f64,
+    pub y: f64,
+    /// Font size in page space, used to tell word gaps from column gaps
+    pub size: f64,
+    pub text: String,
+}
+
+/// The text of one page, in content stream order
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageText {
+    /// 1-based page number
+    pub page: u32,
+    pub fragments: Vec<TextFragment>,
+}
+
+/// Positioned text of every page
+pub fn extract_pages(bytes: &[u8]) -> Result<Vec<PageText>> {
+    let doc = Document::load_mem(bytes).context("Failed to read PDF")?;
+    let mut pages = Vec::new();
+    for (page, page_id) in doc.get_pages() {
+        let mut fonts = BTreeMap::new();
+        for (name, dict) in doc.get_page_fonts(page_id) {
+            let name = String::from_utf8_lossy(&name).to_string();
+            let font = Font::load(&doc, &name, dict)
+                .with_context(|| format!("Font '{}' on page {}", name, page))?;
+            fonts.insert(name, font);
+        }
+        let content = doc
+            .get_and_decode_page_content(page_id)
+            .with_context(|| format!("Failed to read the content of page {}", page))?;
+        let fragments = show_text(&fonts, &content.operations)
+            .with_context(|| format!("Failed to read the text of page {}", page))?;
+        pages.push(PageText { page, fragments });
+    }
+    Ok(pages)
+}
+
+/// Affine matrix [a b c d e f], PDF row-vector convention
+type Matrix = [f64; 6];
+
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// m1 × m2: apply m1, then m2
+fn multiply(m1: &Matrix, m2: &Matrix) -> Matrix {
+    [
+        m1[0] * m2[0] + m1[1] * m2[2],
+        m1[0] * m2[1] + m1[1] * m2[3],
+        m1[2] * m2[0] + m1[3] * m2[2],
+        m1[2] * m2[1] + m1[3] * m2[3],
+        m1[4] * m2[0] + m1[5] * m2[2] + m2[4],
+        m1[4] * m2[1] + m1[5] * m2[3] + m2[5],
+    ]
+}
+
+fn translate(tx: f64, ty: f64) -> Matrix {
+    [1.0, 0.0, 0.0, 1.0, tx, ty]
+}
+
+/// Graphics state that `q` / `Q` save and restore (the text state is part of it)
+#[derive(Debug, Clone)]
+struct State {
+    ctm: Matrix,
+    font: Option<String>,
+    size: f64,
+    char_spacing: f64,
+    word_spacing: f64,
+    horizontal_scale: f64,
+    leading: f64,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            ctm: IDENTITY,
+            font: None,
+            size: 0.0,
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            horizontal_scale: 1.0,
+            leading: 0.0,
+        }
+    }
+}
+
+/// Walk a content stream and collect the strings it shows
+fn show_text(
+    fonts: &BTreeMap<String, Font>,
+    operations: &[lopdf::content::Operation],
+) -> Result<Vec<TextFragment>> {
+    let mut state = State::default();
+    let mut saved: Vec<State> = Vec::new();
+    let (mut tm, mut tlm) = (IDENTITY, IDENTITY);
+    let mut fragments = Vec::new();
+
+    for op in operations {
+        let operands = &op.operands;
+        let num = |i: usize| -> Result<f64> {
+            operands
+                .get(i)
+                .and_then(|o| o.as_float().ok())
+                .map(f64::from)
+                .with_context(|| {
+                    format!(
+                        "Operator '{}' expects a number at operand {}",
+                        op.operator, i
+                    )
+                })
+        };
+        let matrix =
+            || -> Result<Matrix> { Ok([num(0)?, num(1)?, num(2)?, num(3)?, num(4)?, num(5)?]) };
+        let next_line = |tm: &mut Matrix, tlm: &mut Matrix, tx: f64, ty: f64| {
+            *tlm = multiply(&translate(tx, ty), tlm);
+            *tm = *tlm;
+        };
+
+        match op.operator.as_str() {
+            "q" => saved.push(state.clone()),
+            "Q" => state = saved.pop().unwrap_or_default(),
+            "cm" => state.ctm = multiply(&matrix()?, &state.ctm),
+            "BT" => {
+                tm = IDENTITY;
+                tlm = IDENTITY;
+            }
+            "Tf" => {
+                let name = operands
+                    .first()
+                    .and_then(|o| o.as_name().ok())
+                    .context("Operator 'Tf' expects a font name")?;
+                state.font = Some(String::from_utf8_lossy(name).to_string());
+                state.size = num(1)?;
+            }
+            "Tc" => state.char_spacing = num(0)?,
+            "Tw" => state.word_spacing = num(0)?,
+            "Tz" => state.horizontal_scale = num(0)? / 100.0,
+            "TL" => state.leading = num(0)?,
+            "Td" => next_line(&mut tm, &mut tlm, num(0)?, num(1)?),
+            "TD" => {
+                state.leading = -num(1)?;
+                next_line(&mut tm, &mut tlm, num(0)?, num(1)?);
+            }
+            "Tm" => {
+                tm = matrix()?;
+                tlm = tm;
+            }
+            "T*" => next_line(&mut tm, &mut tlm, 0.0, -state.leading),
+            "Tj" | "'" | "\"" | "TJ" => {
+                if op.operator == "\"" {
+                    state.word_spacing = num(0)?;
+                    state.char_spacing = num(1)?;
+                }
+                if op.operator == "'" || op.operator == "\"" {
+                    next_line(&mut tm, &mut tlm, 0.0, -state.leading);
+                }
+                let pieces: Vec<&Object> = match op.operator.as_str() {
+                    "TJ" => operands
+                        .first()
+                        .and_then(|o| o.as_array().ok())
+                        .context("Operator 'TJ' expects an array")?
+                        .iter()
+                        .collect(),
+                    _ => operands.last().into_iter().collect(),
+                };
+                if let Some(fragment) = show(fonts, &state, &mut tm, &pieces)? {
+                    fragments.push(fragment);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(fragments)
+}
+
+/// Show strings and `TJ` adjustments, advancing the text matrix
+fn show(
+    fonts: &BTreeMap<String, Font>,
+    state: &State,
+    tm: &mut Matrix,
+    pieces: &[&Object],
+) -> Result<Option<TextFragment>> {
+    let name = state
+        .font
+        .as_deref()
+        .context("Text shown before any font was selected")?;
+    let font = fonts
+        .get(name)
+        .with_context(|| format!("Font '{}' is not in the page resources", name))?;
+    let start = multiply(tm, &state.ctm);
+
+    let mut text = String::new();
+    let mut advance = 0.0;
+    for piece in pieces {
+        match piece {
+            Object::String(bytes, _) => {
+                for (code, chars) in font.decode(bytes)? {
+                    let mut width = font.width(code) / 1000.0 * state.size + state.char_spacing;
+                    if font.code_len == 1 && code == 32 {
+                        width += state.word_spacing;
+                    }
+                    advance += width * state.horizontal_scale;
+                    text.push_str(&chars);
+                }
+            }
+            other => {
+                let adjustment = other.as_float().map(f64::from).unwrap_or_default();
+                if adjustment <= -WORD_GAP && !text.ends_with(' ') {
+                    text.push(' ');
+                }
+                advance -= adjustment / 1000.0 * state.size * state.horizontal_scale;
+            }
+        }
+    }
+    *tm = multiply(&translate(advance, 0.0), tm);
+    let end = multiply(tm, &state.ctm);
+
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    // Font size in page space: the vertical scale of the text space
+    let size = state.size * (start[2] * start[2] + start[3] * start[3]).sqrt();
+    Ok(Some(TextFragment {
+        x: start[4].min(end[4]),
+        x_end: start[4].max(end[4]),
+        y: start[5],
+        size,
+        text,
+    }))
+}
+
+/// What the parser needs from a font: Unicode for each code, and its width
+#[derive(Debug, Default)]
+struct Font {
+    name: String,
+    /// Bytes per character code: 1 for simple fonts, 2 for Type0
+    code_len: usize,
+    to_unicode: Option<BTreeMap<u32, String>>,
+    /// Named encoding of a simple font without ToUnicode
+    encoding: Option<String>,
+    widths: BTreeMap<u32, f64>,
+    default_width: f64,
+}
+
+impl Font {
+    fn load(doc: &Document, name: &str, dict: &Dictionary) -> Result<Self> {
+        let subtype = dict
+            .get(b"Subtype")
+            .and_then(Object::as_name_str)
+            .unwrap_or_default();
+        let to_unicode = match dict.get(b"ToUnicode") {
+            Ok(object) => {
+                let stream = deref(doc, object)?
+                    .as_stream()
+                    .context("ToUnicode is not a stream")?;
+                let data = stream
+                    .decompressed_content()
+                    .unwrap_or_else(|_| stream.content.clone());
+                Some(parse_cmap(&data)?)
+            }
+            Err(_) => None,
+        };
+        let mut font = Font {
+            name: name.to_string(),
+            to_unicode,
+            ..Default::default()
+        };
+
+        if subtype == "Type0" {
+            font.code_len = 2;
+            let descendant = dict
+                .get(b"DescendantFonts")
+                .ok()
+                .and_then(|o| deref(doc, o).ok())
+                .and_then(|o| o.as_array().ok())
+                .and_then(|fonts| fonts.first())
+                .and_then(|o| deref(doc, o).ok())
+                .and_then(|o| o.as_dict().ok())
+                .context("Type0 font without a descendant font")?;
+            font.default_width = descendant
+                .get(b"DW")
+                .and_then(Object::as_float)
+                .map_or(1000.0, f64::from);
+            if let Ok(w) = descendant.get(b"W") {
+                font.widths = cid_widths(doc, deref(doc, w)?)?;
+            }
+            if font.to_unicode.is_none() {
+                anyhow::bail!(
+                    "AMBIGUITY: Type0 font '{}' has no ToUnicode map; its text cannot be read",
+                    name
+                );
+            }
+            return Ok(font);
+        }
+
+        font.code_len = 1;
+        match dict.get(b"Widths") {
+            Ok(widths) => {
+                let first = dict
+                    .get(b"FirstChar")
+                    .and_then(Object::as_i64)
+                    .context("Font declares Widths without FirstChar")?;
+                for (i, w) in deref(doc, widths)?
+                    .as_array()
+                    .context("Widths is not an array")?
+                    .iter()
+                    .enumerate()
+                {
+                    let w = deref(doc, w)?.as_float().context("Width is not a number")?;
+                    font.widths.insert((first + i as i64) as u32, f64::from(w));
+                }
+                font.default_width = 0.0;
+            }
+            Err(_) => font.default_width = DEFAULT_WIDTH,
+        }
+        if font.to_unicode.is_none() {
+            let encoding = match dict.get(b"Encoding").map(|o| deref(doc, o)) {
+                Err(_) => "StandardEncoding".to_string(),
+                Ok(Ok(Object::Name(name))) => String::from_utf8_lossy(name).to_string(),
+                Ok(Ok(Object::Dictionary(enc))) if enc.get(b"Differences").is_err() => enc
+                    .get(b"BaseEncoding")
+                    .and_then(Object::as_name_str)
+                    .unwrap_or("StandardEncoding")
+                    .to_string(),
+                Ok(_) => anyhow::bail!(
+                    "AMBIGUITY: Font '{}' has a custom encoding and no ToUnicode map; its text cannot be read",
+                    name
+                ),
+            };
+            if !["WinAnsiEncoding", "MacRomanEncoding", "StandardEncoding"]
+                .contains(&encoding.as_str())
+            {
+                anyhow::bail!(
+                    "AMBIGUITY: Font '{}' has unsupported encoding '{}'",
+                    name,
+                    encoding
+                );
+            }
+            font.encoding = Some(encoding);
+        }
+        Ok(font)
+    }
+
+    /// Split a string into codes and decode each one; Err on an unmapped code
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<(u32, String)>> {
+        if !bytes.len().is_multiple_of(self.code_len) {
+            anyhow::bail!(
+                "String of {} bytes in {}-byte font '{}'",
+                bytes.len(),
+                self.code_len,
+                self.name
+            );
+        }
+        bytes
+            .chunks(self.code_len)
+            .map(|chunk| {
+                let code = chunk.iter().fold(0u32, |acc, b| (acc << 8) | u32::from(*b));
+                let text = match (&self.to_unicode, &self.encoding) {
+                    (Some(map), _) => map.get(&code).cloned().unwrap_or_default(),
+                    (None, encoding) => Document::decode_text(encoding.as_deref(), chunk),
+                };
+                if text.is_empty() {
+                    anyhow::bail!(
+                        "AMBIGUITY: Font '{}' has no Unicode mapping for code 0x{:02X}",
+                        self.name,
+                        code
+                    );
+                }
+                Ok((code, text))
+            })
+            .collect()
+    }
+
+    /// Width of a code in thousandths of an em
+    fn width(&self, code: u32) -> f64 {
+        self.widths
+            .get(&code)
+            .copied()
+            .unwrap_or(self.default_width)
+    }
+}
+
+fn deref<'a>(doc: &'a Document, object: &'a Object) -> Result<&'a Object> {
+    Ok(doc
+        .dereference(object)
+        .context("Broken object reference")?
+        .1)
+}
+
+/// CID widths: `c [w1 w2 ...]` (consecutive codes) or `c_first c_last w`
+fn cid_widths(doc: &Document, w: &Object) -> Result<BTreeMap<u32, f64>> {
+    let items = w.as_array().context("W is not an array")?;
+    let number = |o: &Object| -> Result<f64> {
+        Ok(f64::from(
+            deref(doc, o)?
+                .as_float()
+                .context("W entry is not a number")?,
+        ))
+    };
+    let mut widths = BTreeMap::new();
+    let mut i = 0;
+    while i < items.len() {
+        let first = number(&items[i])? as u32;
+        match items.get(i + 1).map(|o| deref(doc, o)) {
+            Some(Ok(Object::Array(list))) => {
+                for (offset, width) in list.iter().enumerate() {
+                    widths.insert(first + offset as u32, number(width)?);
+                }
+                i += 2;
+            }
+            Some(Ok(_)) => {
+                let last = number(&items[i + 1])? as u32;
+                let width = number(items.get(i + 2).context("W range without a width")?)?;
+                for code in first..=last {
+                    widths.insert(code, width);
+                }
+                i += 3;
+            }
+            _ => anyhow::bail!("W array ends after code {}", first),
+        }
+    }
+    Ok(widths)
+}
+
+/// Tokens of a CMap program that the parser reads
+#[derive(Debug, PartialEq)]
+enum CMapToken {
+    Hex(Vec<u8>),
+    ArrayStart,
+    ArrayEnd,
+    Keyword(String),
+}
+
+fn cmap_tokens(data: &[u8]) -> Result<Vec<CMapToken>> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            b'<' if data.get(i + 1) != Some(&b'<') => {
+                let end = data[i..]
+                    .iter()
+                    .position(|&b| b == b'>')
+                    .context("Unterminated hex string in CMap")?
+                    + i;
+                let digits: Vec<u8> = data[i + 1..end]
+                    .iter()
+                    .copied()
+                    .filter(|b| !b.is_ascii_whitespace())
+                    .collect();
+                let text = std::str::from_utf8(&digits).context("Invalid hex string in CMap")?;
+                let bytes = (0..text.len())
+                    .step_by(2)
+                    .map(|j| u8::from_str_radix(text.get(j..j + 2).unwrap_or("0"), 16))
+                    .collect::<Result<Vec<u8>, _>>()
+                    .context("Invalid hex string in CMap")?;
+                tokens.push(CMapToken::Hex(bytes));
+                i = end + 1;
+            }
+            b'[' => {
+                tokens.push(CMapToken::ArrayStart);
+                i += 1;
+            }
+            b']' => {
+                tokens.push(CMapToken::ArrayEnd);
+                i += 1;
+            }
+            b'%' => {
+                while i < data.len() && data[i] != b'\n' && data[i] != b'\r' {
+                    i += 1;
+                }
+            }
+            b if b.is_ascii_alphabetic() => {
+                let start = i;
+                while i < data.len() && data[i].is_ascii_alphanumeric() {
+                    i += 1;
+                }
+                tokens.push(CMapToken::Keyword(
+                    String::from_utf8_lossy(&data[start..i]).to_string(),
+                ));
+            }
+            _ => i += 1,
+        }
+    }
+    Ok(tokens)
+}
+
+fn code_of(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0u32, |acc, b| (acc << 8) | u32::from(*b))
+}
+
+fn utf16(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// Code -> Unicode from a ToUnicode CMap (`bfchar` and `bfrange` sections)
+fn parse_cmap(data: &[u8]) -> Result<BTreeMap<u32, String>> {
+    let tokens = cmap_tokens(data)?;
+    let mut map = BTreeMap::new();
+    let mut section: Option<&str> = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        match (&tokens[i], section) {
+            (CMapToken::Keyword(k), _) if k == "beginbfchar" || k == "beginbfrange" => {
+                section = Some(if k == "beginbfchar" {
+                    "bfchar"
+                } else {
+                    "bfrange"
+                });
+                i += 1;
+            }
+            (CMapToken::Keyword(k), _) if k.starts_with("end") => {
+                section = None;
+                i += 1;
+            }
+            (CMapToken::Hex(src), Some("bfchar")) => {
+                let Some(CMapToken::Hex(dst)) = tokens.get(i + 1) else {
+                    anyhow::bail!("Invalid bfchar entry in CMap");
+                };
+                map.insert(code_of(src), utf16(dst));
+                i += 2;
+            }
+            (CMapToken::Hex(lo), Some("bfrange")) => {
+                let Some(CMapToken::Hex(hi)) = tokens.get(i + 1) else {
+                    anyhow::bail!("Invalid bfrange entry in CMap");
+                };
+                let (lo, hi) = (code_of(lo), code_of(hi));
+                if hi < lo || hi - lo > 0xFFFF {
+                    anyhow::bail!("Invalid bfrange {:X}..{:X} in CMap", lo, hi);
+                }
+                match tokens.get(i + 2) {
+                    Some(CMapToken::Hex(dst)) if dst.len() >= 2 => {
+                        let units: Vec<u16> = dst
+                            .chunks(2)
+                            .map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]))
+                            .collect();
+                        for code in lo..=hi {
+                            let mut units = units.clone();
+                            let last = units.len() - 1;
+                            units[last] = units[last].wrapping_add((code - lo) as u16);
+                            map.insert(code, String::from_utf16_lossy(&units));
+                        }
+                        i += 3;
+                    }
+                    Some(CMapToken::ArrayStart) => {
+                        let mut j = i + 3;
+                        let mut code = lo;
+                        while let Some(CMapToken::Hex(dst)) = tokens.get(j) {
+                            map.insert(code, utf16(dst));
+                            code += 1;
+                            j += 1;
+                        }
+                        if tokens.get(j) != Some(&CMapToken::ArrayEnd) {
+                            anyhow::bail!("Invalid bfrange array in CMap");
+                        }
+                        i = j + 1;
+                    }
+                    _ => anyhow::bail!("Invalid bfrange entry in CMap"),
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::content::Operation;
+
+    fn op(operator: &str, operands: Vec<Object>) -> Operation {
+        Operation::new(operator, operands)
+    }
+
+    fn winansi_font(widths: &[(u32, f64)]) -> BTreeMap<String, Font> {
+        let font = Font {
+            name: "F1".to_string(),
+            code_len: 1,
+            encoding: Some("WinAnsiEncoding".to_string()),
+            widths: widths.iter().copied().collect(),
+            default_width: 500.0,
+            ..Default::default()
+        };
+        BTreeMap::from([("F1".to_string(), font)])
+    }
+
+    #[test]
+    fn test_cmap_bfchar_and_bfrange() {
+        let cmap = b"/CIDInit /ProcSet findresource begin\n\
+            1 begincodespacerange <0000> <FFFF> endcodespacerange\n\
+            2 beginbfchar <0003> <0020> <0011> <00F3> endbfchar\n\
+            2 beginbfrange <0024> <0026> <0041> <0030> <0031> [<00E9> <00D1>] endbfrange\n\
+            endcmap";
+        let map = parse_cmap(cmap).unwrap();
+        assert_eq!(map[&0x03], " ");
+        assert_eq!(map[&0x11], "ó");
+        assert_eq!(map[&0x24], "A");
+        assert_eq!(map[&0x26], "C");
+        assert_eq!(map[&0x31], "Ñ");
+        assert!(!map.contains_key(&0x27));
+    }
+
+    #[test]
+    fn test_positions_follow_text_and_graphics_state() {
+        let fonts = winansi_font(&[(b'1' as u32, 556.0), (b' ' as u32, 278.0)]);
+        let ops = vec![
+            op("q", vec![]),
+            op(
+                "cm",
+                vec![1.into(), 0.into(), 0.into(), 1.into(), 10.into(), 20.into()],
+            ),
+            op("BT", vec![]),
+            op("Tf", vec![Object::Name(b"F1".to_vec()), 10.into()]),
+            op("Td", vec![100.into(), 500.into()]),
+            op("Tj", vec![Object::string_literal("11")]),
+            op("TL", vec![12.into()]),
+            op("T*", vec![]),
+            op(
+                "TJ",
+                vec![vec![
+                    Object::string_literal("1"),
+                    (-300).into(),
+                    Object::string_literal("1"),
+                ]
+                .into()],
+            ),
+            op("ET", vec![]),
+            op("Q", vec![]),
+        ];
+        let fragments = show_text(&fonts, &ops).unwrap();
+        assert_eq!(fragments.len(), 2);
+        // Origin moved by cm; "11" is 2 × 5.56 points wide
+        assert_eq!((fragments[0].x, fragments[0].y), (110.0, 520.0));
+        assert!((fragments[0].x_end - 121.12).abs() < 1e-9);
+        assert_eq!(fragments[0].size, 10.0);
+        // T* goes down one leading; a large TJ gap is a space
+        assert_eq!((fragments[1].x, fragments[1].y), (110.0, 508.0));
+        assert_eq!(fragments[1].text, "1 1");
+        assert!((fragments[1].x_end - 124.12).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_unmapped_code_halts() {
+        let mut fonts = winansi_font(&[]);
+        fonts.get_mut("F1").unwrap().encoding = Some("StandardEncoding".to_string());
+        // 0x80 is undefined in StandardEncoding
+        let ops = vec![
+            op("BT", vec![]),
+            op("Tf", vec![Object::Name(b"F1".to_vec()), 10.into()]),
+            op(
+                "Tj",
+                vec![Object::String(
+                    vec![b'A', 0x80],
+                    lopdf::StringFormat::Literal,
+                )],
+            ),
+        ];
+        let err = format!("{:#}", show_text(&fonts, &ops).unwrap_err());
+        assert!(err.contains("AMBIGUITY") && err.contains("0x80"), "{}", err);
+
+        let ops = vec![
+            op("BT", vec![]),
+            op("Tj", vec![Object::string_literal("A")]),
+        ];
+        assert!(show_text(&fonts, &ops).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+//! Parser plugin interface
+//!
+//! A parser turns the bytes of one artifact into facts plus a row-level
+//! report. The service runs the first registered parser that accepts the
+//! artifact, so a domain crate adds its parsers by registering them here,
+//! without touching the built-in ones or the service (PRINCIPLES.md #4: each
+//! parser owns one domain and halts on anything else).
+
+use crate::fingerprint::SchemaFingerprint;
+use crate::ParseOutput;
+use crate::{dipres_ley, dipres_xls, dotacion, ejecucion, generic_csv, mercadopublico, pdf_tables};
+use anyhow::Result;
+
+/// An artifact as handed to a parser: its metadata and raw bytes
+#[derive(Debug, Clone, Copy)]
+pub struct ArtifactInput<'a> {
+    pub source_id: &'a str,
+    pub mime_type: &'a str,
+    /// Where the collector stored the file (only its extension is meaningful)
+    pub storage_path: &'a str,
+    pub bytes: &'a [u8],
+}
+
+/// A parser for one artifact format
+/// `parse` must be DETERMINISTIC: same input = same output (PRINCIPLES.md #1)
+pub trait Parser: Send + Sync {
+    /// Parser identifier, stored with every snapshot; must be declared under
+    /// `parsers` in config/sources.json when the parser maps columns from it
+    fn id(&self) -> &'static str;
+
+    /// Whether this parser handles the artifact, from its metadata only
+    fn accepts(&self, input: &ArtifactInput) -> bool;
+
+    /// Parse the artifact into facts; halts with `AMBIGUITY:` on anything it
+    /// cannot read unambiguously
+    fn parse(&self, input: &ArtifactInput) -> Result<ParseOutput>;
+
+    /// Whether each artifact of the source restates the earlier ones (e.g. a
+    /// year-to-date workbook re-issued every month). Publishing its snapshot
+    /// then supersedes those of the earlier artifacts, so their values are
+    /// never added up
+    fn cumulative(&self) -> bool {
+        false
+    }
+
+    /// Structural fingerprint, compared with earlier fiscal years of the same
+    /// source family before parsing so drift is on record even if the parse
+    /// halts. None for parsers that do not track schema drift
+    fn fingerprint(&self, _input: &ArtifactInput) -> Result<Option<SchemaFingerprint>> {
+        Ok(None)
+    }
+}
+
+/// Parsers in the order they are tried
+pub struct ParserRegistry {
+    parsers: Vec<Box<dyn Parser>>,
+    /// Number of parsers registered on top of the built-in ones
+    registered: usize,
+}
+
+impl ParserRegistry {
+    /// A registry without parsers
+    pub fn empty() -> Self {
+        ParserRegistry {
+            parsers: Vec::new(),
+            registered: 0,
+        }
+    }
+
+    /// The parsers of this crate, most specific first; the generic CSV parser
+    /// accepts anything and comes last
+    pub fn builtin() -> Self {
+        let builtin: Vec<Box<dyn Parser>> = vec![
+            Box::new(ejecucion::EjecucionParser),
+            Box::new(mercadopublico::MercadoPublicoParser(
+                mercadopublico::Domain::Licitaciones,
+            )),
+            Box::new(mercadopublico::MercadoPublicoParser(
+                mercadopublico::Domain::OrdenesCompra,
+            )),
+            Box::new(dotacion::DotacionParser),
+            Box::new(pdf_tables::PdfTableParser),
+            Box::new(dipres_xls::DipresXlsParser),
+            Box::new(dipres_ley::DipresLeyParser(
+                dipres_ley::DipresLeyMode::ByPartida,
+            )),
+            Box::new(dipres_ley::DipresLeyParser(
+                dipres_ley::DipresLeyMode::LineItems,
+            )),
+            Box::new(generic_csv::GenericCsvParser),
+        ];
+        ParserRegistry {
+            parsers: builtin,
+            registered: 0,
+        }
+    }
+
+    /// Add a parser, tried after earlier registered parsers and before the
+    /// built-in ones; halts if its id is already taken
+    pub fn register(&mut self, parser: Box<dyn Parser>) -> Result<()> {
+        if self.get(parser.id()).is_some() {
+            anyhow::bail!(
+                "AMBIGUITY: Parser id '{}' is already registered",
+                parser.id()
+            );
+        }
+        self.parsers.insert(self.registered, parser);
+        self.registered += 1;
+        Ok(())
+    }
+
+    /// Parser by id (e.g. to force the line-item variant of a source)
+    pub fn get(&self, id: &str) -> Option<&dyn Parser> {
+        self.parsers
+            .iter()
+            .find(|p| p.id() == id)
+            .map(|p| p.as_ref())
+    }
+
+    /// First parser that accepts the artifact
+    pub fn select(&self, input: &ArtifactInput) -> Option<&dyn Parser> {
+        self.parsers
+            .iter()
+            .find(|p| p.accepts(input))
+            .map(|p| p.as_ref())
+    }
+
+    /// Ids of all parsers, in the order they are tried
+    pub fn ids(&self) -> Vec<&'static str> {
+        self.parsers.iter().map(|p| p.id()).collect()
+    }
+}
+
+impl Default for ParserRegistry {
+    fn default() -> Self {
+        ParserRegistry::builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input<'a>(
+        source_id: &'a str,
+        mime_type: &'a str,
+        storage_path: &'a str,
+    ) -> ArtifactInput<'a> {
+        ArtifactInput {
+            source_id,
+            mime_type,
+            storage_path,
+            bytes: b"",
+        }
+    }
+
+    fn selected(registry: &ParserRegistry, artifact: ArtifactInput) -> &'static str {
+        registry.select(&artifact).map(|p| p.id()).unwrap_or("none")
+    }
+
+    struct Fixed(&'static str);
+
+    impl Parser for Fixed {
+        fn id(&self) -> &'static str {
+            self.0
+        }
+
+        fn accepts(&self, input: &ArtifactInput) -> bool {
+            input.source_id.starts_with("dipres-")
+        }
+
+        fn parse(&self, _input: &ArtifactInput) -> Result<ParseOutput> {
+            anyhow::bail!("not used")
+        }
+    }
+
+    #[test]
+    fn test_builtin_selection_follows_source_and_format() {
+        let registry = ParserRegistry::builtin();
+        let cases = [
+            (
+                input(
+                    "dipres-ejecucion-mensual-2025",
+                    "application/vnd.ms-excel",
+                    "a.xlsx",
+                ),
+                "dipres_ejecucion_xls_v1",
+            ),
+            (
+                input(
+                    "mercadopublico-ordenes-compra-2025-01",
+                    "application/zip",
+                    "a.zip",
+                ),
+                "mercadopublico_oc_csv_v1",
+            ),
+            (
+                input(
+                    "contraloria-ejecucion-presupuestaria-2024",
+                    "application/pdf",
+                    "a.pdf",
+                ),
+                "pdf_table_v1",
+            ),
+            (
+                input(
+                    "dipres-presupuesto-2024",
+                    "application/vnd.ms-excel",
+                    "a.xls",
+                ),
+                "dipres_xls_v1",
+            ),
+            (
+                input("dipres-ley-presupuestos-2026", "text/csv", "a.csv"),
+                "dipres_ley_csv_v1",
+            ),
+            (input("demo-presupuesto", "text/csv", "a.csv"), "csv_v1"),
+        ];
+        for (artifact, expected) in cases {
+            assert_eq!(
+                selected(&registry, artifact),
+                expected,
+                "{}",
+                artifact.source_id
+            );
+        }
+        assert!(registry.get("dipres_ley_csv_lines_v1").is_some());
+    }
+
+    #[test]
+    fn test_registered_parsers_go_before_builtin() {
+        let mut registry = ParserRegistry::builtin();
+        registry.register(Box::new(Fixed("domain_a_v1"))).unwrap();
+        registry.register(Box::new(Fixed("domain_b_v1"))).unwrap();
+
+        assert_eq!(
+            &registry.ids()[..3],
+            ["domain_a_v1", "domain_b_v1", "dipres_ejecucion_xls_v1"]
+        );
+        assert_eq!(
+            selected(
+                &registry,
+                input("dipres-ley-presupuestos-2026", "text/csv", "a.csv")
+            ),
+            "domain_a_v1"
+        );
+        assert_eq!(
+            selected(&registry, input("demo-presupuesto", "text/csv", "a.csv")),
+            "csv_v1"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_parser_id_halts() {
+        let mut registry = ParserRegistry::builtin();
+        let err = registry
+            .register(Box::new(Fixed("csv_v1")))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("AMBIGUITY"), "{}", err);
+        assert!(ParserRegistry::empty()
+            .select(&input("demo", "text/csv", "a.csv"))
+            .is_none());
+    }
+}
@@ -0,0 +1,197 @@
+//! Structured parse report
+//!
+//! Every parser returns, next to its facts, what happened to each input row:
+//! accepted, skipped by design (blank or zero lines) or rejected with a line
+//! number and reason. The report also carries totals checks. It is stored in
+//! `job_runs.detail` and its rejects in `parse_rejects`, so a run can be
+//! audited without its stdout (PRINCIPLES.md #2).
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+/// How many rejects are printed to stdout (all of them are stored)
+const PRINTED_REJECTS: usize = 5;
+
+/// A data row the parser could not turn into a fact
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RowReject {
+    /// Line (CSV), 1-based row (XLS) or 1-based row across every table of a
+    /// PDF in the artifact; the reason carries the PDF page and table
+    pub line: usize,
+    pub reason: String,
+}
+
+/// A sum the parser recomputed and compared with an expected value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TotalsCheck {
+    pub name: String,
+    pub expected: Decimal,
+    pub actual: Decimal,
+    pub ok: bool,
+}
+
+/// Column chosen for a role of the parser spec
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnChoice {
+    pub role: String,
+    /// 0-based position among the table headers
+    pub column: usize,
+    pub header: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ParseReport {
+    pub accepted_rows: usize,
+    /// Rows ignored by design (blank entity, zero amount); not errors
+    pub skipped_rows: usize,
+    /// Subtotal / total rows excluded from aggregation and checked instead
+    pub control_rows: usize,
+    pub rejects: Vec<RowReject>,
+    pub checks: Vec<TotalsCheck>,
+    /// Encoding declared in sources.json (text artifacts only)
+    pub declared_encoding: Option<String>,
+    /// Encoding the bytes were verified and decoded as (text artifacts only)
+    pub encoding: Option<String>,
+    /// Header chosen for each column role (parsers mapping headers by spec)
+    pub column_mapping: Vec<ColumnChoice>,
+}
+
+impl ParseReport {
+    pub fn accept(&mut self) {
+        self.accepted_rows += 1;
+    }
+
+    pub fn skip(&mut self) {
+        self.skipped_rows += 1;
+    }
+
+    pub fn control(&mut self) {
+        self.control_rows += 1;
+    }
+
+    pub fn reject(&mut self, line: usize, reason: impl Into<String>) {
+        self.rejects.push(RowReject {
+            line,
+            reason: reason.into(),
+        });
+    }
+
+    /// Record a totals check; differences are not tolerated (zero tolerance)
+    pub fn check_total(&mut self, name: impl Into<String>, expected: Decimal, actual: Decimal) {
+        self.checks.push(TotalsCheck {
+            name: name.into(),
+            expected,
+            actual,
+            ok: expected == actual,
+        });
+    }
+
+    /// Rejected rows over accepted + rejected rows (skipped and control rows excluded)
+    pub fn reject_ratio(&self) -> f64 {
+        let considered = self.accepted_rows + self.rejects.len();
+        if considered == 0 {
+            0.0
+        } else {
+            self.rejects.len() as f64 / considered as f64
+        }
+    }
+
+    /// Halt if any totals check failed or the reject ratio exceeds `max_reject_ratio`
+    pub fn enforce(&self, max_reject_ratio: f64) -> Result<()> {
+        if let Some(check) = self.checks.iter().find(|c| !c.ok) {
+            anyhow::bail!(
+                "AMBIGUITY: Totals check '{}' failed: expected {}, got {}",
+                check.name,
+                check.expected,
+                check.actual
+            );
+        }
+
+        let ratio = self.reject_ratio();
+        if ratio > max_reject_ratio {
+            let first = &self.rejects[0];
+            anyhow::bail!(
+                "AMBIGUITY: {} of {} rows rejected ({:.2}%), above the {:.2}% threshold. First: line {}: {}",
+                self.rejects.len(),
+                self.accepted_rows + self.rejects.len(),
+                ratio * 100.0,
+                max_reject_ratio * 100.0,
+                first.line,
+                first.reason
+            );
+        }
+        Ok(())
+    }
+
+    pub fn print_summary(&self) {
+        println!(
+            "Parse report: {} accepted, {} skipped, {} control totals, {} rejected",
+            self.accepted_rows,
+            self.skipped_rows,
+            self.control_rows,
+            self.rejects.len()
+        );
+        for choice in &self.column_mapping {
+            println!("  [column] {} -> [{}] {}", choice.role, choice.column, choice.header);
+        }
+        if let Some(encoding) = &self.encoding {
+            println!(
+                "  [encoding] {} (declared: {})",
+                encoding,
+                self.declared_encoding.as_deref().unwrap_or("none")
+            );
+        }
+        for reject in self.rejects.iter().take(PRINTED_REJECTS) {
+            println!("  [reject] line {}: {}", reject.line, reject.reason);
+        }
+        if self.rejects.len() > PRINTED_REJECTS {
+            println!("  ... and {} more (see parse_rejects)", self.rejects.len() - PRINTED_REJECTS);
+        }
+        for check in &self.checks {
+            println!(
+                "  [check] {}: expected {}, got {} -> {}",
+                check.name,
+                check.expected,
+                check.actual,
+                if check.ok { "ok" } else { "FAILED" }
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enforce_within_threshold() {
+        let mut report = ParseReport::default();
+        for _ in 0..99 {
+            report.accept();
+        }
+        report.reject(7, "bad amount");
+        assert!(report.enforce(0.01).is_ok());
+        assert!(report.enforce(0.0).is_err());
+    }
+
+    #[test]
+    fn test_skipped_rows_do_not_count_as_rejects() {
+        let mut report = ParseReport::default();
+        report.accept();
+        report.skip();
+        report.skip();
+        assert_eq!(report.reject_ratio(), 0.0);
+        assert!(report.enforce(0.0).is_ok());
+    }
+
+    #[test]
+    fn test_failed_totals_check_halts() {
+        let mut report = ParseReport::default();
+        report.accept();
+        report.check_total("sum", Decimal::from(10), Decimal::from(9));
+        let err = report.enforce(1.0).unwrap_err().to_string();
+        assert!(err.contains("AMBIGUITY"));
+        assert!(err.contains("sum"));
+    }
+}
@@ -0,0 +1,1328 @@
+//! Parser service: runs a parser over one artifact and publishes its facts
+//!
+//! Responsibilities:
+//! - Load artifact metadata and raw content
+//! - Pick the parser from the registry and parse deterministically
+//! - Upsert entities and metrics
+//! - Insert facts with provenance (evidence chain)
+//! - Mark artifact as parsed or failed
+
+use crate::classifier::ClassifierCatalog;
+use crate::consolidation::ConsolidationRules;
+use crate::crosswalk::EntityResolution;
+use crate::dipres_ley;
+use crate::fingerprint::{self, SchemaDrift, SchemaFingerprint};
+use crate::lifecycle::BudgetLifecycle;
+use crate::partidas::PartidaCatalog;
+use crate::registry::{ArtifactInput, ParserRegistry};
+use crate::report::ParseReport;
+use crate::{fiscal_year_from_source_id, ParseOutput, ParsedFact};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::Parser as _;
+use rust_decimal::Decimal;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use tokio::fs;
+use uuid::Uuid;
+
+#[derive(clap::Parser, Debug)]
+#[command(name = "parser", about = "Parses raw artifacts into canonical facts")]
+struct Args {
+    /// Artifact id to parse (UUID)
+    #[arg(long)]
+    artifact_id: String,
+
+    /// Dry run - don't save to database
+    #[arg(long, default_value = "false")]
+    dry_run: bool,
+
+    /// Verify mode - re-parse and compare with the published snapshot's facts,
+    /// writing nothing; fails if they differ
+    #[arg(long, default_value = "false")]
+    verify: bool,
+
+    /// Re-parse an already parsed artifact, superseding its published snapshot
+    #[arg(long, default_value = "false")]
+    reparse: bool,
+
+    /// DIPRES Ley CSV: emit one fact per line item instead of per-Partida totals
+    #[arg(long, default_value = "false")]
+    line_items: bool,
+
+    /// Maximum share of rejected rows (0.0-1.0) before the parse halts
+    #[arg(long, default_value = "0.0", value_parser = parse_reject_ratio)]
+    max_reject_ratio: f64,
+}
+
+/// A share of rows: a finite number between 0.0 and 1.0
+fn parse_reject_ratio(value: &str) -> std::result::Result<f64, String> {
+    let ratio: f64 = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+    if !ratio.is_finite() || !(0.0..=1.0).contains(&ratio) {
+        return Err(format!("'{}' is not between 0.0 and 1.0", value));
+    }
+    Ok(ratio)
+}
+
+/// Artifact metadata from database
+#[derive(Debug, sqlx::FromRow)]
+struct Artifact {
+    artifact_id: Uuid,
+    source_id: String,
+    url: String,
+    content_hash: String,
+    captured_at: DateTime<Utc>,
+    mime_type: String,
+    storage_path: String,
+    parsed_status: String,
+}
+
+/// A published snapshot of the artifact's source, as seen when publishing
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct PublishedSnapshot {
+    snapshot_id: Uuid,
+    artifact_id: Uuid,
+    captured_at: DateTime<Utc>,
+}
+
+
+/// Get or create entity, returning entity_id
+/// An existing entity without a parent gets linked to `parent_id` if one is given
+async fn get_or_create_entity(
+    pool: &PgPool,
+    key: &str,
+    name: &str,
+    entity_type: &str,
+    parent_id: Option<Uuid>,
+) -> Result<Uuid> {
+    // Try to get existing
+    let existing: Option<(Uuid,)> =
+        sqlx::query_as("SELECT entity_id FROM entities WHERE entity_key = $1")
+            .bind(key)
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((id,)) = existing {
+        if parent_id.is_some() {
+            sqlx::query(
+                "UPDATE entities SET parent_entity_id = $2 WHERE entity_id = $1 AND parent_entity_id IS NULL",
+            )
+            .bind(id)
+            .bind(parent_id)
+            .execute(pool)
+            .await?;
+        }
+        return Ok(id);
+    }
+
+    // Create new
+    let id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO entities (entity_id, entity_key, display_name, entity_type, parent_entity_id, level)
+        VALUES ($1, $2, $3, $4, $5, (SELECT level FROM entity_levels WHERE entity_type = $4))
+        "#,
+    )
+    .bind(id)
+    .bind(key)
+    .bind(name)
+    .bind(entity_type)
+    .bind(parent_id)
+    .execute(pool)
+    .await?;
+
+    Ok(id)
+}
+
+/// Get or create metric, returning metric_id
+async fn get_or_create_metric(
+    pool: &PgPool,
+    key: &str,
+    name: &str,
+    unit: &str,
+) -> Result<Uuid> {
+    // Try to get existing
+    let existing: Option<(Uuid,)> =
+        sqlx::query_as("SELECT metric_id FROM metrics WHERE metric_key = $1")
+            .bind(key)
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((id,)) = existing {
+        return Ok(id);
+    }
+
+    // Create new
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO metrics (metric_id, metric_key, display_name, unit) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(id)
+    .bind(key)
+    .bind(name)
+    .bind(unit)
+    .execute(pool)
+    .await?;
+
+    Ok(id)
+}
+
+/// Create a staging snapshot for this parsing run
+/// Facts in a staging snapshot are not visible to the API until published
+async fn create_snapshot(
+    pool: &PgPool,
+    artifact_id: Uuid,
+    parser: &str,
+    note: &str,
+) -> Result<Uuid> {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO snapshots (snapshot_id, artifact_id, parser, status, note)
+        VALUES ($1, $2, $3, 'staging', $4)
+        "#,
+    )
+    .bind(id)
+    .bind(artifact_id)
+    .bind(parser)
+    .bind(note)
+    .execute(pool)
+    .await?;
+    Ok(id)
+}
+
+/// Published snapshots that a new run over `artifact` supersedes: those of
+/// the same artifact and, for a cumulative parser, those of every earlier
+/// artifact of the source, whose values the new one restates. Publishing an
+/// older cumulative artifact over a newer one halts.
+fn snapshots_to_supersede(
+    artifact: &Artifact,
+    cumulative: bool,
+    published: &[PublishedSnapshot],
+) -> Result<Vec<Uuid>> {
+    let mut superseded = Vec::new();
+    for snapshot in published {
+        if snapshot.artifact_id != artifact.artifact_id {
+            if !cumulative {
+                continue;
+            }
+            if snapshot.captured_at > artifact.captured_at {
+                anyhow::bail!(
+                    "AMBIGUITY: Artifact {} of source '{}' (captured {}) is already published and restates this one (captured {}); parse the newest artifact instead",
+                    snapshot.artifact_id,
+                    artifact.source_id,
+                    snapshot.captured_at,
+                    artifact.captured_at
+                );
+            }
+        }
+        superseded.push(snapshot.snapshot_id);
+    }
+    Ok(superseded)
+}
+
+/// Publish a staging snapshot, superseding the previously published snapshot
+/// for the same artifact + parser, or for the whole source when the parser is
+/// cumulative. Runs in a single transaction so the API never sees two
+/// published snapshots (or none) for the same data.
+/// Returns the number of snapshots superseded.
+async fn publish_snapshot(
+    pool: &PgPool,
+    snapshot_id: Uuid,
+    artifact: &Artifact,
+    parser: &str,
+    cumulative: bool,
+) -> Result<u64> {
+    let mut tx = pool.begin().await?;
+
+    // Snapshots created before migration 002 have no parser recorded
+    let published: Vec<PublishedSnapshot> = sqlx::query_as(
+        r#"
+        SELECT s.snapshot_id, s.artifact_id, a.captured_at
+        FROM snapshots s
+        JOIN artifacts a ON a.artifact_id = s.artifact_id
+        WHERE a.source_id = $2
+          AND (s.parser = $3 OR (s.parser IS NULL AND s.artifact_id = $4))
+          AND s.status = 'published'
+          AND s.snapshot_id <> $1
+        FOR UPDATE OF s
+        "#,
+    )
+    .bind(snapshot_id)
+    .bind(&artifact.source_id)
+    .bind(parser)
+    .bind(artifact.artifact_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    let superseded = snapshots_to_supersede(artifact, cumulative, &published)?;
+
+    sqlx::query(
+        r#"
+        UPDATE snapshots
+        SET status = 'superseded', superseded_by = $1
+        WHERE snapshot_id = ANY($2)
+        "#,
+    )
+    .bind(snapshot_id)
+    .bind(&superseded)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE snapshots
+        SET status = 'published', published_at = now()
+        WHERE snapshot_id = $1 AND status = 'staging'
+        "#,
+    )
+    .bind(snapshot_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(superseded.len() as u64)
+}
+
+/// What identifies a fact across runs, and what a re-run must reproduce
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct FactKey {
+    entity_key: String,
+    metric_key: String,
+    period_start: NaiveDate,
+    period_end: NaiveDate,
+    location: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct FactValue {
+    value_num: Decimal,
+    unit: String,
+    dims: serde_json::Value,
+}
+
+impl FactKey {
+    fn of(fact: &ParsedFact) -> Self {
+        FactKey {
+            entity_key: fact.entity_key.clone(),
+            metric_key: fact.metric_key.clone(),
+            period_start: fact.period_start,
+            period_end: fact.period_end,
+            location: fact.location.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for FactKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | {} | {}..{} | {}",
+            self.entity_key, self.metric_key, self.period_start, self.period_end, self.location
+        )
+    }
+}
+
+/// Facts of the published snapshot of this artifact + parser, or None if
+/// there is none
+async fn load_published_facts(
+    pool: &PgPool,
+    artifact_id: Uuid,
+    parser: &str,
+) -> Result<Option<(Uuid, Vec<(FactKey, FactValue)>)>> {
+    let snapshot_id: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT snapshot_id FROM snapshots
+        WHERE artifact_id = $1 AND parser = $2 AND status = 'published'
+        "#,
+    )
+    .bind(artifact_id)
+    .bind(parser)
+    .fetch_optional(pool)
+    .await?;
+    let Some(snapshot_id) = snapshot_id else {
+        return Ok(None);
+    };
+
+    type Row = (String, String, NaiveDate, NaiveDate, Option<String>, Decimal, String, serde_json::Value);
+    let rows: Vec<Row> =
+        sqlx::query_as(
+            r#"
+            SELECT e.entity_key, m.metric_key, f.period_start, f.period_end, p.location,
+                   f.value_num, f.unit, f.dims
+            FROM facts f
+            JOIN entities e ON e.entity_id = f.entity_id
+            JOIN metrics m ON m.metric_id = f.metric_id
+            LEFT JOIN provenance p ON p.fact_id = f.fact_id
+            WHERE f.snapshot_id = $1
+            "#,
+        )
+        .bind(snapshot_id)
+        .fetch_all(pool)
+        .await?;
+    let facts = rows
+        .into_iter()
+        .map(|(entity_key, metric_key, period_start, period_end, location, value_num, unit, dims)| {
+            (
+                FactKey {
+                    entity_key,
+                    metric_key,
+                    period_start,
+                    period_end,
+                    location: location.unwrap_or_default(),
+                },
+                FactValue { value_num, unit, dims },
+            )
+        })
+        .collect();
+    Ok(Some((snapshot_id, facts)))
+}
+
+/// Differences between the published facts and a re-parse, one line each:
+/// facts missing from either side and facts whose value, unit or dims moved
+fn diff_facts(published: Vec<(FactKey, FactValue)>, parsed: &[ParsedFact]) -> Vec<String> {
+    let mut expected: BTreeMap<FactKey, Vec<FactValue>> = BTreeMap::new();
+    for (key, value) in published {
+        expected.entry(key).or_default().push(value);
+    }
+
+    let mut differences = Vec::new();
+    for fact in parsed {
+        let key = FactKey::of(fact);
+        let value = FactValue {
+            value_num: fact.value_num,
+            unit: fact.metric_unit.clone(),
+            dims: fact.dims.clone(),
+        };
+        match expected.get_mut(&key) {
+            Some(values) if !values.is_empty() => {
+                if let Some(i) = values.iter().position(|v| *v == value) {
+                    values.remove(i);
+                } else {
+                    let published = values.remove(0);
+                    differences.push(format!(
+                        "changed: {} | published {} {} {} | parsed {} {} {}",
+                        key,
+                        published.value_num,
+                        published.unit,
+                        published.dims,
+                        value.value_num,
+                        value.unit,
+                        value.dims
+                    ));
+                }
+            }
+            _ => differences.push(format!("new: {} | {} {}", key, value.value_num, value.unit)),
+        }
+    }
+    for (key, values) in expected {
+        for value in values {
+            differences.push(format!("missing: {} | {} {}", key, value.value_num, value.unit));
+        }
+    }
+    differences
+}
+
+/// Discard a staging snapshot (and its facts) after a failed run
+async fn discard_snapshot(pool: &PgPool, snapshot_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM snapshots WHERE snapshot_id = $1 AND status = 'staging'")
+        .bind(snapshot_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Insert a fact and its provenance
+async fn insert_fact(
+    pool: &PgPool,
+    snapshot_id: Uuid,
+    entity_id: Uuid,
+    metric_id: Uuid,
+    fact: &ParsedFact,
+    artifact_id: Uuid,
+    parser: &str,
+) -> Result<Uuid> {
+    let fact_id = Uuid::new_v4();
+
+    // Insert fact
+    sqlx::query(
+        r#"
+        INSERT INTO facts (fact_id, snapshot_id, entity_id, metric_id, period_start, period_end, value_num, unit, dims)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+    )
+    .bind(fact_id)
+    .bind(snapshot_id)
+    .bind(entity_id)
+    .bind(metric_id)
+    .bind(fact.period_start)
+    .bind(fact.period_end)
+    .bind(fact.value_num)
+    .bind(&fact.metric_unit)
+    .bind(&fact.dims)
+    .execute(pool)
+    .await?;
+
+    // Insert provenance (evidence chain); the method is the parser id
+    sqlx::query(
+        r#"
+        INSERT INTO provenance (fact_id, artifact_id, location, method)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(fact_id)
+    .bind(artifact_id)
+    .bind(&fact.location)
+    .bind(parser)
+    .execute(pool)
+    .await?;
+
+    Ok(fact_id)
+}
+
+/// Insert all facts of a run into a snapshot, creating entities and metrics as needed
+async fn insert_facts(
+    pool: &PgPool,
+    snapshot_id: Uuid,
+    artifact_id: Uuid,
+    parser: &str,
+    facts: &[ParsedFact],
+) -> Result<usize> {
+    // Cache for entity/metric IDs
+    let mut entity_cache: HashMap<String, Uuid> = HashMap::new();
+    let mut metric_cache: HashMap<String, Uuid> = HashMap::new();
+
+    let mut inserted = 0;
+    for fact in facts {
+        // Get or create entity, walking its parent chain from the root
+        let entity_id = if let Some(&id) = entity_cache.get(&fact.entity_key) {
+            id
+        } else {
+            let mut parent_id: Option<Uuid> = None;
+            for parent in &fact.entity_parents {
+                let id = match entity_cache.get(&parent.key) {
+                    Some(&id) => id,
+                    None => {
+                        let id = get_or_create_entity(
+                            pool,
+                            &parent.key,
+                            &parent.name,
+                            &parent.entity_type,
+                            parent_id,
+                        )
+                        .await?;
+                        entity_cache.insert(parent.key.clone(), id);
+                        id
+                    }
+                };
+                parent_id = Some(id);
+            }
+
+            let id = get_or_create_entity(
+                pool,
+                &fact.entity_key,
+                &fact.entity_name,
+                &fact.entity_type,
+                parent_id,
+            )
+            .await?;
+            entity_cache.insert(fact.entity_key.clone(), id);
+            id
+        };
+
+        // Get or create metric
+        let metric_id = if let Some(&id) = metric_cache.get(&fact.metric_key) {
+            id
+        } else {
+            let id = get_or_create_metric(
+                pool,
+                &fact.metric_key,
+                &fact.metric_name,
+                &fact.metric_unit,
+            )
+            .await?;
+            metric_cache.insert(fact.metric_key.clone(), id);
+            id
+        };
+
+        // Insert fact with provenance
+        insert_fact(pool, snapshot_id, entity_id, metric_id, fact, artifact_id, parser).await?;
+        inserted += 1;
+    }
+
+    Ok(inserted)
+}
+
+/// Record how the crosswalk resolved each source name: matched names become
+/// per-source aliases in `entity_aliases`, unmatched names enter (or stay in)
+/// `entity_review_queue`. A queued name that now matches is marked resolved.
+async fn record_entity_resolutions(
+    pool: &PgPool,
+    artifact_id: Uuid,
+    source_id: &str,
+    entities: &[EntityResolution],
+) -> Result<()> {
+    for entity in entities {
+        match entity.rule {
+            Some(rule) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO entity_aliases (source_id, normalized_name, source_name, entity_key, match_rule, last_artifact_id)
+                    VALUES ($1, $2, $3, $4, $5, $6)
+                    ON CONFLICT (source_id, normalized_name) DO UPDATE
+                    SET source_name = EXCLUDED.source_name, entity_key = EXCLUDED.entity_key,
+                        match_rule = EXCLUDED.match_rule, last_artifact_id = EXCLUDED.last_artifact_id,
+                        updated_at = now()
+                    "#,
+                )
+                .bind(source_id)
+                .bind(&entity.normalized_name)
+                .bind(&entity.source_name)
+                .bind(&entity.entity_key)
+                .bind(rule.as_str())
+                .bind(artifact_id)
+                .execute(pool)
+                .await?;
+
+                sqlx::query(
+                    r#"
+                    UPDATE entity_review_queue
+                    SET status = 'resolved', resolved_entity_key = $3, last_seen_at = now()
+                    WHERE source_id = $1 AND normalized_name = $2 AND status = 'pending'
+                    "#,
+                )
+                .bind(source_id)
+                .bind(&entity.normalized_name)
+                .bind(&entity.entity_key)
+                .execute(pool)
+                .await?;
+            }
+            None => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO entity_review_queue
+                      (source_id, normalized_name, source_name, local_entity_key, rows, first_artifact_id, last_artifact_id)
+                    VALUES ($1, $2, $3, $4, $5, $6, $6)
+                    ON CONFLICT (source_id, normalized_name) DO UPDATE
+                    SET source_name = EXCLUDED.source_name, local_entity_key = EXCLUDED.local_entity_key,
+                        rows = EXCLUDED.rows, last_artifact_id = EXCLUDED.last_artifact_id,
+                        status = 'pending', resolved_entity_key = NULL, last_seen_at = now()
+                    "#,
+                )
+                .bind(source_id)
+                .bind(&entity.normalized_name)
+                .bind(&entity.source_name)
+                .bind(&entity.entity_key)
+                .bind(entity.rows as i32)
+                .bind(artifact_id)
+                .execute(pool)
+                .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Upsert the embedded economic classifier catalog into `economic_classifier`
+async fn sync_classifier_catalog(pool: &PgPool, catalog: &ClassifierCatalog) -> Result<()> {
+    for entry in &catalog.entries {
+        sqlx::query(
+            r#"
+            INSERT INTO economic_classifier (code, level, name, kind, valid_from_year, valid_to_year, catalog_version)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (code, valid_from_year) DO UPDATE
+            SET level = EXCLUDED.level, name = EXCLUDED.name, kind = EXCLUDED.kind,
+                valid_to_year = EXCLUDED.valid_to_year, catalog_version = EXCLUDED.catalog_version
+            "#,
+        )
+        .bind(&entry.code)
+        .bind(entry.level())
+        .bind(&entry.name)
+        .bind(&entry.kind)
+        .bind(entry.valid_from)
+        .bind(entry.valid_to)
+        .bind(&catalog.version)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Upsert the embedded Partida name catalog into `partida_catalog`
+async fn sync_partida_catalog(pool: &PgPool, catalog: &PartidaCatalog) -> Result<()> {
+    for entry in &catalog.entries {
+        sqlx::query(
+            r#"
+            INSERT INTO partida_catalog (code, name, valid_from_year, valid_to_year, catalog_version)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (code, valid_from_year) DO UPDATE
+            SET name = EXCLUDED.name, valid_to_year = EXCLUDED.valid_to_year,
+                catalog_version = EXCLUDED.catalog_version
+            "#,
+        )
+        .bind(&entry.code)
+        .bind(&entry.name)
+        .bind(entry.valid_from)
+        .bind(entry.valid_to)
+        .bind(&catalog.version)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Upsert the embedded budget lifecycle into `budget_lifecycle`, one row per metric
+async fn sync_budget_lifecycle(pool: &PgPool, lifecycle: &BudgetLifecycle) -> Result<()> {
+    for (stage_order, stage) in lifecycle.stages.iter().enumerate() {
+        for (priority, metric_key) in stage.metrics.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO budget_lifecycle (metric_key, stage, stage_order, stage_name, metric_priority,
+                                              period_kind, description, lifecycle_version)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (metric_key) DO UPDATE
+                SET stage = EXCLUDED.stage, stage_order = EXCLUDED.stage_order,
+                    stage_name = EXCLUDED.stage_name, metric_priority = EXCLUDED.metric_priority,
+                    period_kind = EXCLUDED.period_kind, description = EXCLUDED.description,
+                    lifecycle_version = EXCLUDED.lifecycle_version
+                "#,
+            )
+            .bind(metric_key)
+            .bind(&stage.stage)
+            .bind(stage_order as i32 + 1)
+            .bind(&stage.name)
+            .bind(priority as i32 + 1)
+            .bind(&stage.period)
+            .bind(&stage.description)
+            .bind(&lifecycle.version)
+            .execute(pool)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Upsert the embedded consolidation rules into `consolidation_rules`
+async fn sync_consolidation_rules(pool: &PgPool, rules: &ConsolidationRules) -> Result<()> {
+    for rule in &rules.rules {
+        sqlx::query(
+            r#"
+            INSERT INTO consolidation_rules (rule_id, classifier_prefix, description, rules_version)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (rule_id) DO UPDATE
+            SET classifier_prefix = EXCLUDED.classifier_prefix, description = EXCLUDED.description,
+                rules_version = EXCLUDED.rules_version
+            "#,
+        )
+        .bind(&rule.id)
+        .bind(&rule.classifier_prefix)
+        .bind(&rule.description)
+        .bind(&rules.version)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Update artifact parsed status
+async fn update_artifact_status(
+    pool: &PgPool,
+    artifact_id: Uuid,
+    status: &str,
+    error: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE artifacts SET parsed_status = $2, parsed_error = $3 WHERE artifact_id = $1",
+    )
+    .bind(artifact_id)
+    .bind(status)
+    .bind(error)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Create job run for parser
+async fn create_job_run(pool: &PgPool, source_id: &str, artifact_id: Uuid) -> Result<Uuid> {
+    let job_run_id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO job_runs (job_run_id, component, source_id, status, detail)
+        VALUES ($1, 'parser', $2, 'running', $3)
+        "#,
+    )
+    .bind(job_run_id)
+    .bind(source_id)
+    .bind(serde_json::json!({ "artifact_id": artifact_id.to_string() }))
+    .execute(pool)
+    .await?;
+    Ok(job_run_id)
+}
+
+/// Finish job run
+async fn finish_job_run(
+    pool: &PgPool,
+    job_run_id: Uuid,
+    status: &str,
+    error: Option<&str>,
+    facts_count: usize,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE job_runs
+        SET finished_at = now(), status = $2, error = $3, detail = detail || $4
+        WHERE job_run_id = $1
+        "#,
+    )
+    .bind(job_run_id)
+    .bind(status)
+    .bind(error)
+    .bind(serde_json::json!({ "facts_created": facts_count }))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Store the parse report in `job_runs.detail` and its rejects in `parse_rejects`
+async fn record_parse_report(
+    pool: &PgPool,
+    job_run_id: Uuid,
+    artifact_id: Uuid,
+    parser_id: &str,
+    report: &ParseReport,
+) -> Result<()> {
+    sqlx::query("UPDATE job_runs SET detail = detail || $2 WHERE job_run_id = $1")
+        .bind(job_run_id)
+        .bind(serde_json::json!({
+            "parser": parser_id,
+            "report": {
+                "accepted_rows": report.accepted_rows,
+                "skipped_rows": report.skipped_rows,
+                "control_rows": report.control_rows,
+                "rejected_rows": report.rejects.len(),
+                "reject_ratio": report.reject_ratio(),
+                "checks": report.checks,
+                "declared_encoding": report.declared_encoding,
+                "encoding": report.encoding,
+                "column_mapping": report.column_mapping,
+            }
+        }))
+        .execute(pool)
+        .await?;
+
+    if !report.rejects.is_empty() {
+        let lines: Vec<i32> = report.rejects.iter().map(|r| r.line as i32).collect();
+        let reasons: Vec<String> = report.rejects.iter().map(|r| r.reason.clone()).collect();
+        sqlx::query(
+            r#"
+            INSERT INTO parse_rejects (job_run_id, artifact_id, parser, line, reason)
+            SELECT $1, $2, $3, r.line, r.reason
+            FROM UNNEST($4::int[], $5::text[]) AS r(line, reason)
+            "#,
+        )
+        .bind(job_run_id)
+        .bind(artifact_id)
+        .bind(parser_id)
+        .bind(&lines)
+        .bind(&reasons)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Compare an artifact's fingerprint with the latest fingerprint of an earlier
+/// fiscal year of the same source family, print the drift and (unless dry
+/// run) store both in `schema_fingerprints` and `job_runs.detail`
+async fn record_schema_fingerprint(
+    pool: &PgPool,
+    artifact: &Artifact,
+    job_run_id: Option<Uuid>,
+    fingerprint: &SchemaFingerprint,
+) -> Result<()> {
+    let family = fingerprint::source_family(&artifact.source_id);
+    let year = fiscal_year_from_source_id(&artifact.source_id)
+        .context("AMBIGUITY: Cannot extract fiscal year from source_id for schema fingerprint")?;
+
+    let previous: Option<(Uuid, i32, serde_json::Value)> = sqlx::query_as(
+        r#"
+        SELECT artifact_id, fiscal_year, fingerprint
+        FROM schema_fingerprints
+        WHERE source_family = $1 AND fiscal_year < $2
+        ORDER BY fiscal_year DESC, created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(&family)
+    .bind(year)
+    .fetch_optional(pool)
+    .await?;
+
+    let drift = match &previous {
+        Some((previous_id, previous_year, value)) => {
+            let previous_fp: SchemaFingerprint = serde_json::from_value(value.clone())
+                .context("Invalid stored schema fingerprint")?;
+            let drift = SchemaDrift::between(&previous_fp, fingerprint);
+            if drift.has_drift() {
+                println!(
+                    "Schema drift vs {} ({}, artifact {}):",
+                    family, previous_year, previous_id
+                );
+                for line in drift.summary() {
+                    println!("  [drift] {}", line);
+                }
+            } else {
+                println!("Schema unchanged vs {} ({})", family, previous_year);
+            }
+            Some(drift)
+        }
+        None => {
+            println!("No earlier fiscal year of {} to compare the schema with", family);
+            None
+        }
+    };
+
+    let Some(job_run_id) = job_run_id else {
+        return Ok(());
+    };
+
+    let compared_to = previous.as_ref().map(|(id, _, _)| *id);
+    let has_drift = drift.as_ref().is_some_and(|d| d.has_drift());
+    let drift_json = drift.map(serde_json::to_value).transpose()?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO schema_fingerprints
+          (artifact_id, source_id, source_family, fiscal_year, fingerprint, compared_to, drift, has_drift)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (artifact_id) DO UPDATE SET
+          fingerprint = EXCLUDED.fingerprint,
+          compared_to = EXCLUDED.compared_to,
+          drift = EXCLUDED.drift,
+          has_drift = EXCLUDED.has_drift,
+          created_at = now()
+        "#,
+    )
+    .bind(artifact.artifact_id)
+    .bind(&artifact.source_id)
+    .bind(&family)
+    .bind(year)
+    .bind(serde_json::to_value(fingerprint)?)
+    .bind(compared_to)
+    .bind(&drift_json)
+    .bind(has_drift)
+    .execute(pool)
+    .await?;
+
+    sqlx::query("UPDATE job_runs SET detail = detail || $2 WHERE job_run_id = $1")
+        .bind(job_run_id)
+        .bind(serde_json::json!({
+            "schema": {
+                "compared_to": compared_to,
+                "has_drift": has_drift,
+                "drift": drift_json,
+            }
+        }))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Parse one artifact (`--artifact-id`) with the first parser of `registry`
+/// that accepts it, and publish its facts as a snapshot
+pub async fn run(registry: ParserRegistry) -> Result<()> {
+    dotenvy::dotenv().ok();
+    let args = Args::parse();
+    let db_url = std::env::var("DB_URL").context("DB_URL env var missing")?;
+
+    let artifact_id: Uuid = args.artifact_id.parse().context("Invalid artifact_id UUID")?;
+
+    println!("=== Estado Transparente Parser ===");
+    println!("Artifact ID: {}", artifact_id);
+    println!(
+        "Mode: {}",
+        if args.dry_run {
+            "dry-run"
+        } else if args.verify {
+            "verify"
+        } else {
+            "live"
+        }
+    );
+
+    // Connect to database
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&db_url)
+        .await
+        .context("Failed to connect to database")?;
+
+    // Load artifact metadata
+    let artifact: Artifact = sqlx::query_as(
+        "SELECT artifact_id, source_id, url, content_hash, captured_at, mime_type, storage_path, parsed_status FROM artifacts WHERE artifact_id = $1"
+    )
+    .bind(artifact_id)
+    .fetch_optional(&pool)
+    .await?
+    .context("Artifact not found")?;
+
+    println!("Source: {}", artifact.source_id);
+    println!("URL: {}", artifact.url);
+    println!("Hash: {}", artifact.content_hash);
+    println!("Status: {}", artifact.parsed_status);
+
+    if artifact.parsed_status == "ok" && !args.verify && !args.reparse {
+        println!("Artifact already parsed. Use --reparse to supersede its snapshot, or --verify to re-check.");
+        return Ok(());
+    }
+
+    // Create job run
+    let job_run_id = if !args.dry_run && !args.verify {
+        Some(create_job_run(&pool, &artifact.source_id, artifact_id).await?)
+    } else {
+        None
+    };
+
+    // Kept outside the run so the report is stored even when the parse halts
+    let mut parse_report: Option<(&'static str, ParseReport)> = None;
+
+    let result = async {
+        // Detect file format and parse accordingly
+        println!("Reading raw file: {}", artifact.storage_path);
+        println!("MIME type: {}", artifact.mime_type);
+
+        let raw_bytes = fs::read(&artifact.storage_path)
+            .await
+            .context("Failed to read artifact file")?;
+        println!("Content size: {} bytes", raw_bytes.len());
+        let input = ArtifactInput {
+            source_id: &artifact.source_id,
+            mime_type: &artifact.mime_type,
+            storage_path: &artifact.storage_path,
+            bytes: &raw_bytes,
+        };
+
+        let mut parser = registry.select(&input).with_context(|| {
+            format!(
+                "AMBIGUITY: No parser accepts source '{}' ({})",
+                artifact.source_id, artifact.mime_type
+            )
+        })?;
+        if args.line_items && parser.id() == dipres_ley::PARSER_ID {
+            parser = registry
+                .get(dipres_ley::LINES_PARSER_ID)
+                .context("DIPRES Ley line-item parser is not registered")?;
+        }
+        println!("\nSelected parser: {}", parser.id());
+
+        // Fingerprint and compare with the previous fiscal year before
+        // parsing, so drift is on record even if the strict parser halts
+        if let Some(fingerprint) = parser.fingerprint(&input)? {
+            record_schema_fingerprint(&pool, &artifact, job_run_id, &fingerprint).await?;
+        }
+
+        let parser_id = parser.id();
+        let output = parser.parse(&input)?;
+
+        println!("Parser: {}", parser_id);
+
+        let ParseOutput {
+            facts,
+            report,
+            entities,
+            linked_entities,
+        } = output;
+        report.print_summary();
+        let unmatched: Vec<&EntityResolution> = entities.iter().filter(|e| e.rule.is_none()).collect();
+        if !entities.is_empty() {
+            println!(
+                "Entity crosswalk: {} names, {} matched, {} unmatched (queued for review)",
+                entities.len(),
+                entities.len() - unmatched.len(),
+                unmatched.len()
+            );
+            for entity in unmatched.iter().take(5) {
+                println!("  [unmatched] '{}' -> {}", entity.source_name, entity.entity_key);
+            }
+        }
+        let enforced = report.enforce(args.max_reject_ratio);
+        parse_report = Some((parser_id, report));
+        enforced?;
+
+        println!("\nParsed {} facts total", facts.len());
+
+        if facts.is_empty() {
+            anyhow::bail!("No facts parsed from artifact");
+        }
+
+        // Print sample facts
+        for (i, fact) in facts.iter().take(3).enumerate() {
+            println!(
+                "  [{}] {} | {} | {} | {} {}",
+                i + 1,
+                fact.entity_name,
+                fact.metric_key,
+                fact.period_start.format("%Y"),
+                fact.value_num,
+                fact.metric_unit
+            );
+        }
+        if facts.len() > 3 {
+            println!("  ... and {} more", facts.len() - 3);
+        }
+
+        if args.dry_run {
+            println!("\nDry run - no facts saved to database");
+            return Ok(facts.len());
+        }
+
+        if args.verify {
+            let (published_id, published) = load_published_facts(&pool, artifact_id, parser_id)
+                .await?
+                .with_context(|| {
+                    format!("Nothing to verify: no published {} snapshot for this artifact", parser_id)
+                })?;
+            let differences = diff_facts(published, &facts);
+            if !differences.is_empty() {
+                for line in differences.iter().take(20) {
+                    println!("  [diff] {}", line);
+                }
+                anyhow::bail!(
+                    "Verify failed: {} difference(s) with published snapshot {}",
+                    differences.len(),
+                    published_id
+                );
+            }
+            println!("\nVerified: output matches published snapshot {} (nothing written)", published_id);
+            return Ok(facts.len());
+        }
+
+        // Keep the classifier catalog table in sync with the embedded seed
+        sync_classifier_catalog(&pool, &ClassifierCatalog::embedded()?).await?;
+        sync_consolidation_rules(&pool, &ConsolidationRules::embedded()?).await?;
+        sync_partida_catalog(&pool, &PartidaCatalog::embedded()?).await?;
+        sync_budget_lifecycle(&pool, &BudgetLifecycle::embedded()?).await?;
+
+        // Create staging snapshot (invisible to the API until published)
+        let snapshot_id = create_snapshot(
+            &pool,
+            artifact_id,
+            parser_id,
+            &format!("Parser run for artifact {}", artifact_id),
+        )
+        .await?;
+        println!("Created staging snapshot: {}", snapshot_id);
+
+        for entity in &linked_entities {
+            get_or_create_entity(&pool, &entity.key, &entity.name, &entity.entity_type, None).await?;
+        }
+        if !linked_entities.is_empty() {
+            println!("Linked entities: {}", linked_entities.len());
+        }
+
+        let inserted = match insert_facts(&pool, snapshot_id, artifact_id, parser_id, &facts).await {
+            Ok(n) => n,
+            Err(e) => {
+                discard_snapshot(&pool, snapshot_id).await?;
+                return Err(e);
+            }
+        };
+
+        // Publish, superseding any previous run of this parser on this
+        // artifact (or on the source, for cumulative parsers)
+        let superseded =
+            match publish_snapshot(&pool, snapshot_id, &artifact, parser_id, parser.cumulative())
+                .await
+            {
+                Ok(n) => n,
+                Err(e) => {
+                    discard_snapshot(&pool, snapshot_id).await?;
+                    return Err(e);
+                }
+            };
+        println!("Published snapshot: {} (superseded {})", snapshot_id, superseded);
+
+        record_entity_resolutions(&pool, artifact_id, &artifact.source_id, &entities).await?;
+
+        // Mark artifact as parsed
+        update_artifact_status(&pool, artifact_id, "ok", None).await?;
+
+        println!("Inserted {} facts with provenance", inserted);
+        Ok::<usize, anyhow::Error>(inserted)
+    }
+    .await;
+
+    // Update job run
+    if let Some(job_id) = job_run_id {
+        if let Some((parser_id, report)) = &parse_report {
+            record_parse_report(&pool, job_id, artifact_id, parser_id, report).await?;
+        }
+        match &result {
+            Ok(count) => finish_job_run(&pool, job_id, "ok", None, *count).await?,
+            Err(e) => {
+                update_artifact_status(&pool, artifact_id, "failed", Some(&e.to_string())).await?;
+                finish_job_run(&pool, job_id, "failed", Some(&e.to_string()), 0).await?;
+            }
+        }
+    }
+
+    let count = result?;
+    println!("\n=== Parsing Complete ===");
+    println!("Facts created: {}", count);
+    println!("Ready for API queries");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_max_reject_ratio_accepts_only_shares() {
+        for ok in ["0", "0.0", "0.01", "1", "1.0"] {
+            assert!(parse_reject_ratio(ok).is_ok(), "{}", ok);
+        }
+        for bad in ["-0.1", "1.5", "NaN", "inf", "-inf", "1%", ""] {
+            assert!(parse_reject_ratio(bad).is_err(), "{}", bad);
+        }
+        assert!(Args::try_parse_from(["parser", "--artifact-id", "x", "--max-reject-ratio", "2"]).is_err());
+        let args = Args::try_parse_from(["parser", "--artifact-id", "x"]).unwrap();
+        assert_eq!(args.max_reject_ratio, 0.0);
+    }
+
+    fn artifact(source_id: &str, month: u32) -> Artifact {
+        Artifact {
+            artifact_id: Uuid::new_v4(),
+            source_id: source_id.to_string(),
+            url: String::new(),
+            content_hash: format!("hash-{}", month),
+            captured_at: Utc.with_ymd_and_hms(2025, month, 5, 0, 0, 0).unwrap(),
+            mime_type: String::new(),
+            storage_path: String::new(),
+            parsed_status: "ok".to_string(),
+        }
+    }
+
+    fn published(artifact: &Artifact) -> PublishedSnapshot {
+        PublishedSnapshot {
+            snapshot_id: Uuid::new_v4(),
+            artifact_id: artifact.artifact_id,
+            captured_at: artifact.captured_at,
+        }
+    }
+
+    #[test]
+    fn test_cumulative_workbooks_of_a_year_do_not_add_up() {
+        let registry = ParserRegistry::builtin();
+        let source_id = "dipres-ejecucion-mensual-2025";
+        let devengado = |name: &str| -> Decimal {
+            let bytes = crate::test_support::fixture(name);
+            let input = ArtifactInput {
+                source_id,
+                mime_type: "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+                storage_path: name,
+                bytes: &bytes,
+            };
+            let parser = registry.select(&input).unwrap();
+            assert!(parser.cumulative());
+            parser
+                .parse(&input)
+                .unwrap()
+                .facts
+                .iter()
+                .filter(|f| f.metric_key == "ejecucion_devengado")
+                .map(|f| f.value_num)
+                .sum()
+        };
+
+        // Issued in February (January only) and in April (through March, restating January)
+        let january = artifact(source_id, 2);
+        let march = artifact(source_id, 4);
+        let january_snapshot = published(&january);
+        let march_snapshot = published(&march);
+        let totals = [
+            (&january_snapshot, devengado("dipres_ejecucion_2025_01.xlsx")),
+            (&march_snapshot, devengado("dipres_ejecucion_2025.xlsx")),
+        ];
+        assert!(totals[0].1 > Decimal::ZERO && totals[0].1 < totals[1].1);
+
+        let superseded =
+            snapshots_to_supersede(&march, true, std::slice::from_ref(&january_snapshot)).unwrap();
+        assert_eq!(superseded, vec![january_snapshot.snapshot_id]);
+
+        // What the API sums after the April run: the latest workbook, not both
+        let published_total: Decimal = totals
+            .iter()
+            .filter(|(snapshot, _)| !superseded.contains(&snapshot.snapshot_id))
+            .map(|(_, total)| *total)
+            .sum();
+        assert_eq!(published_total, totals[1].1);
+        assert_ne!(published_total, totals[0].1 + totals[1].1);
+
+        // Re-parsing January after March would hide the newer months
+        let err = snapshots_to_supersede(&january, true, &[published(&march)])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("AMBIGUITY"), "{}", err);
+    }
+
+    #[test]
+    fn test_non_cumulative_parser_supersedes_its_own_artifact_only() {
+        let january = artifact("mercadopublico-licitaciones-2025-01", 2);
+        let february = artifact("mercadopublico-licitaciones-2025-01", 3);
+        let own = published(&february);
+        let superseded =
+            snapshots_to_supersede(&february, false, &[published(&january), own.clone()]).unwrap();
+        assert_eq!(superseded, vec![own.snapshot_id]);
+    }
+
+    fn fact(location: &str, value_num: Decimal) -> ParsedFact {
+        let day = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        ParsedFact {
+            entity_key: "partida_09".to_string(),
+            entity_name: "Ministerio de Educación".to_string(),
+            entity_type: "partida".to_string(),
+            entity_parents: Vec::new(),
+            metric_key: "ejecucion_devengado".to_string(),
+            metric_name: "Devengado".to_string(),
+            metric_unit: "CLP".to_string(),
+            period_start: day,
+            period_end: day,
+            value_num,
+            location: location.to_string(),
+            dims: serde_json::json!({ "as_of_month": 3 }),
+        }
+    }
+
+    fn stored(fact: &ParsedFact) -> (FactKey, FactValue) {
+        (
+            FactKey::of(fact),
+            FactValue {
+                value_num: fact.value_num,
+                unit: fact.metric_unit.clone(),
+                dims: fact.dims.clone(),
+            },
+        )
+    }
+
+    #[test]
+    fn test_verify_diff_reports_changed_new_and_missing_facts() {
+        let a = fact("csv:line=2", Decimal::new(1000, 0));
+        let b = fact("csv:line=3", Decimal::new(2000, 0));
+        let published = vec![stored(&a), stored(&b)];
+        assert!(diff_facts(published.clone(), &[b.clone(), a.clone()]).is_empty());
+
+        let changed = fact("csv:line=2", Decimal::new(1001, 0));
+        let c = fact("csv:line=4", Decimal::new(3000, 0));
+        let differences = diff_facts(published, &[changed, c]);
+        assert_eq!(differences.len(), 3, "{:?}", differences);
+        assert!(differences[0].starts_with("changed: partida_09 | ejecucion_devengado"));
+        assert!(differences[1].starts_with("new:"));
+        assert!(differences[2].starts_with("missing:") && differences[2].contains("csv:line=3"));
+    }
+}
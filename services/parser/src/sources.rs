@@ -0,0 +1,170 @@
+//! Source registry (config/sources.json) as seen by the parser
+//!
+//! The collector reads sources.json at run time to know what to download; the
+//! parser embeds the same file at compile time for what it declares about
+//! parsing: the encoding of each source and the parser specs (`parsers`).
+//! Embedding keeps parser output a function of the artifact and the parser
+//! version only (PRINCIPLES.md #1).
+
+use crate::normalize;
+use crate::report::ColumnChoice;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Official sources and the column mappings of every parser that reads them
+const SOURCES_JSON: &str = include_str!("../../../config/sources.json");
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SourceEntry {
+    pub id: String,
+    pub encoding: Option<String>,
+}
+
+/// Parser spec; only the fields the parser reads
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ParserSpec {
+    /// Role -> header names that fill it, matched exactly in `normalize::fold` form
+    #[serde(default)]
+    pub column_roles: BTreeMap<String, Vec<String>>,
+    /// Roles that must be mapped for the parse to start
+    #[serde(default)]
+    pub required_roles: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SourceRegistry {
+    pub sources: Vec<SourceEntry>,
+    #[serde(default)]
+    pub parsers: BTreeMap<String, ParserSpec>,
+}
+
+impl SourceRegistry {
+    /// Load the registry embedded in the binary
+    pub fn embedded() -> Result<Self> {
+        Self::from_json(SOURCES_JSON)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Failed to parse sources.json")
+    }
+
+    pub fn source(&self, source_id: &str) -> Option<&SourceEntry> {
+        self.sources.iter().find(|s| s.id == source_id)
+    }
+
+    pub fn parser(&self, parser_id: &str) -> Result<&ParserSpec> {
+        self.parsers
+            .get(parser_id)
+            .with_context(|| format!("No parser spec '{}' in sources.json", parser_id))
+    }
+}
+
+impl ParserSpec {
+    /// Map headers to column roles by exact (folded) name
+    ///
+    /// A header that merely contains a name does not match. Halts when two
+    /// columns match the same role, when one column matches two roles, or
+    /// when a required role has no column (PRINCIPLES.md #3).
+    pub fn map_columns(&self, headers: &[String]) -> Result<Vec<ColumnChoice>> {
+        let folded: Vec<String> = headers.iter().map(|h| normalize::fold(h)).collect();
+        let mut mapping: Vec<ColumnChoice> = Vec::new();
+
+        for (role, names) in &self.column_roles {
+            let names: Vec<String> = names.iter().map(|n| normalize::fold(n)).collect();
+            let matches: Vec<usize> = folded
+                .iter()
+                .enumerate()
+                .filter(|(_, h)| names.contains(h))
+                .map(|(i, _)| i)
+                .collect();
+            match matches.as_slice() {
+                [] if self.required_roles.contains(role) => anyhow::bail!(
+                    "AMBIGUITY: No {} column found. Expected one of: {}",
+                    role,
+                    self.column_roles[role].join(", ")
+                ),
+                [] => {}
+                [column] => mapping.push(ColumnChoice {
+                    role: role.clone(),
+                    column: *column,
+                    header: headers[*column].clone(),
+                }),
+                many => anyhow::bail!(
+                    "AMBIGUITY: {} columns match the {} role: {}",
+                    many.len(),
+                    role,
+                    many.iter()
+                        .map(|&i| format!("'{}'", headers[i]))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            }
+        }
+
+        for (i, a) in mapping.iter().enumerate() {
+            if let Some(b) = mapping[i + 1..].iter().find(|b| b.column == a.column) {
+                anyhow::bail!(
+                    "AMBIGUITY: Column '{}' matches both the {} and {} roles",
+                    a.header,
+                    a.role,
+                    b.role
+                );
+            }
+        }
+
+        Ok(mapping)
+    }
+}
+
+/// Column index chosen for a role, if mapped
+pub fn column_for(mapping: &[ColumnChoice], role: &str) -> Option<usize> {
+    mapping.iter().find(|c| c.role == role).map(|c| c.column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|h| h.to_string()).collect()
+    }
+
+    #[test]
+    fn test_embedded_registry() {
+        let registry = SourceRegistry::embedded().unwrap();
+        assert!(registry.source("dipres-ley-presupuestos-2026").is_some());
+        let spec = registry.parser("dipres_xls_v1").unwrap();
+        assert!(spec.required_roles.contains(&"entity".to_string()));
+        assert!(registry.parser("missing").is_err());
+    }
+
+    #[test]
+    fn test_map_columns_exact_match() {
+        let spec = SourceRegistry::embedded().unwrap().parser("dipres_xls_v1").unwrap().clone();
+        let mapping = spec
+            .map_columns(&headers(&["Programa", "Servicio", "Año", "Monto Total Dólar", "Monto"]))
+            .unwrap();
+        assert_eq!(column_for(&mapping, "entity"), Some(1));
+        assert_eq!(column_for(&mapping, "year"), Some(2));
+        assert_eq!(column_for(&mapping, "amount"), Some(4));
+        assert_eq!(column_for(&mapping, "category"), None);
+
+        // "Programa Presupuestario" contains no entity name exactly
+        let err = spec
+            .map_columns(&headers(&["Programa Presupuestario", "Monto"]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("No entity column"));
+    }
+
+    #[test]
+    fn test_map_columns_halts_on_multiple_candidates() {
+        let spec = SourceRegistry::embedded().unwrap().parser("dipres_xls_v1").unwrap().clone();
+        let err = spec
+            .map_columns(&headers(&["Partida", "Servicio", "Monto"]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("AMBIGUITY") && err.contains("2 columns match the entity role"));
+    }
+}
@@ -0,0 +1,512 @@
+//! Excel layout specs: where the table is inside a workbook
+//!
+//! Government workbooks are reports, not tables: the data sits on one of
+//! several sheets, under a title block, with headers split across merged rows
+//! and footnotes after the last line. config/xls_layouts.json declares, per
+//! source family, how to find the table:
+//!
+//! - `sheet`: exact `name` or `pattern` (`*` wildcard), compared folded;
+//!   more than one match halts (PRINCIPLES.md #3). Default: first sheet
+//! - `header.row` (1-based) or `header.detect` (labels that must all appear
+//!   in the header block); `header.rows` > 1 merges a multi-row header
+//! - `range`: A1 bounds ("A5:K400"); cells outside are ignored
+//! - `footnote_prefixes`: the table ends at the first row whose first
+//!   non-empty cell starts with one of them ("Fuente", "Nota")
+//!
+//! Sources without a spec keep the historical layout: first sheet, header on
+//! row 1. Rows and columns are reported as absolute sheet positions, so
+//! provenance is a real cell reference (`Hoja1` + `F12`).
+
+use crate::normalize;
+use anyhow::{Context, Result};
+use calamine::{Data, Range};
+use serde::Deserialize;
+
+/// Sheet, header rows and footnotes of each Excel workbook, per source family
+const LAYOUTS_JSON: &str = include_str!("../../../config/xls_layouts.json");
+
+/// Rows scanned for `header.detect` when no range is given
+const HEADER_SCAN_ROWS: u32 = 30;
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct SheetSelector {
+    pub name: Option<String>,
+    pub pattern: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct HeaderSpec {
+    /// 1-based sheet row of the first header row
+    pub row: Option<u32>,
+    /// Labels that identify the header block when `row` is not given
+    #[serde(default)]
+    pub detect: Vec<String>,
+    /// Header rows merged into one label per column
+    #[serde(default = "one")]
+    pub rows: u32,
+}
+
+fn one() -> u32 {
+    1
+}
+
+impl Default for HeaderSpec {
+    fn default() -> Self {
+        Self {
+            row: None,
+            detect: Vec::new(),
+            rows: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct XlsLayout {
+    pub source_family: String,
+    #[serde(default)]
+    pub sheet: SheetSelector,
+    #[serde(default)]
+    pub header: HeaderSpec,
+    pub range: Option<String>,
+    #[serde(default)]
+    pub footnote_prefixes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LayoutsFile {
+    version: String,
+    layouts: Vec<XlsLayout>,
+}
+
+#[derive(Debug)]
+pub struct XlsLayouts {
+    pub version: String,
+    pub layouts: Vec<XlsLayout>,
+}
+
+impl XlsLayouts {
+    /// Load the layouts embedded in the binary
+    pub fn embedded() -> Result<Self> {
+        Self::from_json(LAYOUTS_JSON)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: LayoutsFile =
+            serde_json::from_str(json).context("Failed to parse XLS layouts")?;
+        Ok(Self {
+            version: file.version,
+            layouts: file.layouts,
+        })
+    }
+
+    /// Layout for a source (matched by family), or the default layout
+    pub fn for_source(&self, source_id: &str) -> XlsLayout {
+        let family = crate::fingerprint::source_family(source_id);
+        self.layouts
+            .iter()
+            .find(|l| l.source_family == family)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// A data row with its 1-based sheet row number
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableRow {
+    pub row: u32,
+    pub cells: Vec<Data>,
+}
+
+/// The table a layout found in a sheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetTable {
+    pub sheet: String,
+    /// One label per column, merged across header rows
+    pub headers: Vec<String>,
+    /// Non-empty cells above the header block (title, units), in reading order
+    pub title: Vec<String>,
+    /// 1-based sheet rows of the header block
+    pub header_rows: (u32, u32),
+    /// 0-based sheet column of `headers[0]`
+    pub first_col: u32,
+    pub rows: Vec<TableRow>,
+    /// Rows dropped at and after the first footnote
+    pub footnote_rows: usize,
+}
+
+impl SheetTable {
+    /// A1 reference of a table cell, e.g. (12, 5) -> "F12" when the table starts in column A
+    pub fn cell_ref(&self, row: u32, col: usize) -> String {
+        a1(row - 1, self.first_col + col as u32)
+    }
+}
+
+impl XlsLayout {
+    /// Pick the sheet named by the spec, or the first sheet
+    pub fn select_sheet(&self, names: &[String]) -> Result<String> {
+        let matches: Vec<&String> = match (&self.sheet.name, &self.sheet.pattern) {
+            (Some(name), _) => names
+                .iter()
+                .filter(|n| normalize::fold(n) == normalize::fold(name))
+                .collect(),
+            (None, Some(pattern)) => names.iter().filter(|n| wildcard_match(pattern, n)).collect(),
+            (None, None) => names.iter().take(1).collect(),
+        };
+        match matches.as_slice() {
+            [sheet] => Ok(sheet.to_string()),
+            [] => anyhow::bail!(
+                "AMBIGUITY: No sheet matches the layout for '{}'. Sheets: {:?}",
+                self.source_family,
+                names
+            ),
+            many => anyhow::bail!(
+                "AMBIGUITY: {} sheets match the layout for '{}': {:?}",
+                many.len(),
+                self.source_family,
+                many
+            ),
+        }
+    }
+
+    /// Locate header and data rows of `range` (a whole sheet, absolute positions)
+    pub fn extract(&self, sheet: &str, range: &Range<Data>) -> Result<SheetTable> {
+        let (Some(start), Some(end)) = (range.start(), range.end()) else {
+            anyhow::bail!("Sheet '{}' is empty", sheet);
+        };
+        let ((top, left), (bottom, right)) = match &self.range {
+            Some(bounds) => {
+                let (from, to) = parse_a1_range(bounds)?;
+                (
+                    (from.0.max(start.0), from.1.max(start.1)),
+                    (to.0.min(end.0), to.1.min(end.1)),
+                )
+            }
+            None => (start, end),
+        };
+        if top > bottom || left > right {
+            anyhow::bail!("Sheet '{}' has no cells inside {:?}", sheet, self.range);
+        }
+
+        let header_count = self.header.rows.max(1);
+        let text = |r: u32, c: u32| cell_text(range.get_value((r, c)));
+        let block = |first: u32| -> Vec<Vec<String>> {
+            (first..first + header_count)
+                .map(|r| (left..=right).map(|c| text(r, c)).collect())
+                .collect()
+        };
+
+        let header_top = match (self.header.row, self.header.detect.is_empty()) {
+            (Some(row), _) => row.checked_sub(1).context("header.row is 1-based")?,
+            (None, false) => {
+                let wanted: Vec<String> = self.header.detect.iter().map(|l| normalize::fold(l)).collect();
+                let last = bottom.min(top + HEADER_SCAN_ROWS);
+                let folded = |cells: &[String]| -> Vec<String> {
+                    cells.iter().map(|c| normalize::fold(c)).collect()
+                };
+                // The block starts at the first row holding one of the labels
+                // (not at an empty or title row above it) and holds them all
+                (top..=last)
+                    .find(|&r| {
+                        let block = block(r);
+                        let first = folded(&block[0]);
+                        let all = folded(&block.concat());
+                        wanted.iter().any(|w| first.contains(w))
+                            && wanted.iter().all(|w| all.contains(w))
+                    })
+                    .with_context(|| {
+                        format!(
+                            "AMBIGUITY: No header row with {:?} in sheet '{}'",
+                            self.header.detect, sheet
+                        )
+                    })?
+            }
+            (None, true) => top,
+        };
+        let header_bottom = header_top + header_count - 1;
+        if header_top < top || header_bottom > bottom {
+            anyhow::bail!(
+                "AMBIGUITY: Header rows {}-{} are outside the table bounds of sheet '{}'",
+                header_top + 1,
+                header_bottom + 1,
+                sheet
+            );
+        }
+
+        let headers = merge_header_rows(&block(header_top));
+        let title: Vec<String> = (top..header_top)
+            .flat_map(|r| (left..=right).map(move |c| (r, c)))
+            .map(|(r, c)| text(r, c))
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        let prefixes: Vec<String> = self.footnote_prefixes.iter().map(|p| normalize::fold(p)).collect();
+        let mut rows = Vec::new();
+        let mut footnote_rows = 0;
+        for r in header_bottom + 1..=bottom {
+            let cells: Vec<Data> = (left..=right)
+                .map(|c| range.get_value((r, c)).cloned().unwrap_or(Data::Empty))
+                .collect();
+            let first_text = cells
+                .iter()
+                .map(|c| cell_text(Some(c)))
+                .find(|t| !t.is_empty())
+                .map(|t| normalize::fold(&t));
+            if let Some(first) = first_text {
+                if prefixes.iter().any(|p| first.starts_with(p.as_str())) {
+                    footnote_rows = (bottom - r + 1) as usize;
+                    break;
+                }
+            }
+            rows.push(TableRow { row: r + 1, cells });
+        }
+
+        Ok(SheetTable {
+            sheet: sheet.to_string(),
+            headers,
+            title,
+            header_rows: (header_top + 1, header_bottom + 1),
+            first_col: left,
+            rows,
+            footnote_rows,
+        })
+    }
+}
+
+/// One label per column from a header block
+///
+/// Merged cells only carry their value in the top-left cell. A title spanning
+/// several columns ("Presupuesto" over "Ley" / "Vigente") is carried right
+/// while the columns have a label below it; a title merged downwards
+/// ("Partida" over an empty cell) is kept as is.
+fn merge_header_rows(block: &[Vec<String>]) -> Vec<String> {
+    let width = block.first().map_or(0, |r| r.len());
+    let below = |level: usize, col: usize| block[level + 1..].iter().any(|r| !r[col].is_empty());
+
+    let mut filled: Vec<Vec<String>> = block.to_vec();
+    for level in 0..block.len().saturating_sub(1) {
+        let mut carry: Option<String> = None;
+        for col in 0..width {
+            let cell = &block[level][col];
+            if !cell.is_empty() {
+                carry = below(level, col).then(|| cell.clone());
+            } else if let Some(title) = carry.as_ref().filter(|_| below(level, col)) {
+                filled[level][col] = title.clone();
+            } else {
+                carry = None;
+            }
+        }
+    }
+
+    (0..width)
+        .map(|col| {
+            let parts: Vec<&str> = filled
+                .iter()
+                .map(|r| r[col].as_str())
+                .filter(|p| !p.is_empty())
+                .collect();
+            normalize::clean_label(&parts.join(" "))
+        })
+        .collect()
+}
+
+/// Cell as trimmed text; numbers in calamine's display form
+pub fn cell_text(cell: Option<&Data>) -> String {
+    match cell {
+        Some(Data::String(s)) => s.trim().to_string(),
+        Some(Data::Empty) | None => String::new(),
+        Some(other) => format!("{}", other),
+    }
+}
+
+/// `*` matches any run of characters; compared in `normalize::fold` form
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let text = normalize::fold(text);
+    let parts: Vec<String> = pattern.split('*').map(normalize::fold).collect();
+    let mut rest = text.as_str();
+    for (i, part) in parts.iter().map(String::as_str).enumerate() {
+        if part.is_empty() {
+            continue;
+        }
+        if i == 0 {
+            match rest.strip_prefix(part) {
+                Some(r) => rest = r,
+                None => return false,
+            }
+        } else if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        } else {
+            match rest.find(part) {
+                Some(pos) => rest = &rest[pos + part.len()..],
+                None => return false,
+            }
+        }
+    }
+    parts.last().is_some_and(|p| p.is_empty()) || rest.is_empty()
+}
+
+/// 0-based (row, col) -> "F12"
+pub fn a1(row: u32, col: u32) -> String {
+    let mut letters = Vec::new();
+    let mut n = col + 1;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        letters.push((b'A' + rem as u8) as char);
+        n = (n - 1) / 26;
+    }
+    letters.iter().rev().collect::<String>() + &(row + 1).to_string()
+}
+
+/// "F12" -> 0-based (row, col)
+pub fn parse_a1(reference: &str) -> Result<(u32, u32)> {
+    let reference = reference.trim().to_ascii_uppercase();
+    let split = reference
+        .find(|c: char| c.is_ascii_digit())
+        .with_context(|| format!("Invalid cell reference '{}'", reference))?;
+    let (letters, digits) = reference.split_at(split);
+    if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_uppercase()) {
+        anyhow::bail!("Invalid cell reference '{}'", reference);
+    }
+    let row: u32 = digits
+        .parse()
+        .ok()
+        .filter(|r| *r > 0)
+        .with_context(|| format!("Invalid cell reference '{}'", reference))?;
+    let col = letters
+        .bytes()
+        .fold(0u32, |acc, b| acc * 26 + (b - b'A') as u32 + 1);
+    Ok((row - 1, col - 1))
+}
+
+/// "A5:K400" -> ((4, 0), (399, 10))
+fn parse_a1_range(bounds: &str) -> Result<((u32, u32), (u32, u32))> {
+    let (from, to) = bounds
+        .split_once(':')
+        .with_context(|| format!("Invalid range '{}', expected A1:B2", bounds))?;
+    Ok((parse_a1(from)?, parse_a1(to)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet(rows: &[&[&str]]) -> Range<Data> {
+        let width = rows.iter().map(|r| r.len()).max().unwrap_or(1) as u32;
+        let mut range = Range::new((0, 0), (rows.len() as u32 - 1, width - 1));
+        for (r, row) in rows.iter().enumerate() {
+            for (c, value) in row.iter().enumerate() {
+                let cell = match value.parse::<f64>() {
+                    Ok(n) => Data::Float(n),
+                    Err(_) if value.is_empty() => Data::Empty,
+                    Err(_) => Data::String(value.to_string()),
+                };
+                range.set_value((r as u32, c as u32), cell);
+            }
+        }
+        range
+    }
+
+    #[test]
+    fn test_a1_references() {
+        assert_eq!(a1(0, 0), "A1");
+        assert_eq!(a1(11, 5), "F12");
+        assert_eq!(a1(0, 26), "AA1");
+        assert_eq!(parse_a1("aa1").unwrap(), (0, 26));
+        assert_eq!(parse_a1("F12").unwrap(), (11, 5));
+        assert!(parse_a1("12").is_err());
+        assert!(parse_a1("F0").is_err());
+    }
+
+    #[test]
+    fn test_select_sheet() {
+        let names: Vec<String> = ["Portada", "Ejecución Enero", "Ejecución Febrero"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let layout = |name: Option<&str>, pattern: Option<&str>| XlsLayout {
+            source_family: "t".to_string(),
+            sheet: SheetSelector {
+                name: name.map(String::from),
+                pattern: pattern.map(String::from),
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(layout(None, None).select_sheet(&names).unwrap(), "Portada");
+        assert_eq!(layout(Some("ejecucion enero"), None).select_sheet(&names).unwrap(), "Ejecución Enero");
+        assert_eq!(layout(None, Some("*febrero")).select_sheet(&names).unwrap(), "Ejecución Febrero");
+        let err = layout(None, Some("Ejecucion*")).select_sheet(&names).unwrap_err().to_string();
+        assert!(err.contains("AMBIGUITY") && err.contains("2 sheets"));
+        assert!(layout(Some("Marzo"), None).select_sheet(&names).is_err());
+    }
+
+    #[test]
+    fn test_default_layout_is_header_on_first_row() {
+        let range = sheet(&[&["Partida", "Monto"], &["Educación", "100"]]);
+        let table = XlsLayout::default().extract("Hoja1", &range).unwrap();
+        assert_eq!(table.headers, vec!["Partida", "Monto"]);
+        assert_eq!(table.rows.len(), 1);
+        assert_eq!(table.rows[0].row, 2);
+        assert_eq!(table.cell_ref(2, 1), "B2");
+    }
+
+    #[test]
+    fn test_detected_multi_row_header_and_footnotes() {
+        let range = sheet(&[
+            &["Ejecución Presupuestaria", "", "", ""],
+            &["", "", "", ""],
+            &["Partida", "Presupuesto", "", "Nota"],
+            &["", "Ley", "Vigente", ""],
+            &["Educación", "100", "120", ""],
+            &["Salud", "200", "210", ""],
+            &["Fuente: DIPRES", "", "", ""],
+            &["(1) Cifras en miles de pesos", "", "", ""],
+        ]);
+        let layout = XlsLayout {
+            source_family: "t".to_string(),
+            header: HeaderSpec {
+                row: None,
+                detect: vec!["Partida".to_string(), "Ley".to_string()],
+                rows: 2,
+            },
+            footnote_prefixes: vec!["Fuente".to_string()],
+            ..Default::default()
+        };
+        let table = layout.extract("Hoja1", &range).unwrap();
+        assert_eq!(table.header_rows, (3, 4));
+        assert_eq!(table.title, vec!["Ejecución Presupuestaria"]);
+        assert_eq!(table.headers, vec!["Partida", "Presupuesto Ley", "Presupuesto Vigente", "Nota"]);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.footnote_rows, 2);
+        assert_eq!(table.cell_ref(table.rows[1].row, 2), "C6");
+    }
+
+    #[test]
+    fn test_range_bounds_and_explicit_header_row() {
+        let range = sheet(&[
+            &["", "título", ""],
+            &["", "Partida", "Monto"],
+            &["", "Educación", "100"],
+            &["", "Total", "100"],
+        ]);
+        let layout = XlsLayout {
+            source_family: "t".to_string(),
+            header: HeaderSpec {
+                row: Some(2),
+                ..Default::default()
+            },
+            range: Some("B1:C3".to_string()),
+            ..Default::default()
+        };
+        let table = layout.extract("Hoja1", &range).unwrap();
+        assert_eq!(table.headers, vec!["Partida", "Monto"]);
+        assert_eq!(table.first_col, 1);
+        assert_eq!(table.rows.len(), 1);
+        assert_eq!(table.cell_ref(3, 1), "C3");
+    }
+
+    #[test]
+    fn test_embedded_layouts_parse() {
+        let layouts = XlsLayouts::embedded().unwrap();
+        assert_eq!(layouts.for_source("sin-layout-2026"), XlsLayout::default());
+    }
+}
@@ -0,0 +1,500 @@
+#!/usr/bin/env python3
+"""Regenerate the fixtures of the parser tests (stdlib only).
+
+    python3 services/parser/tests/fixtures/make_fixtures.py
+
+The workbooks mimic DIPRES "Ejecución Presupuestaria Mensual": a cover sheet,
+the data sheet under a title block with a two-row header (Presupuesto Vigente,
+then Devengado and Pagado over the month names) and footnotes, and a notes
+sheet; the workbook is cumulative, so an earlier issue (January) is the same
+file with the later months blank. The CSV files mimic the Mercado Público bulk exports (licitaciones and
+órdenes de compra): `;`-separated, Latin-1; the staffing file mimics a
+Transparencia Activa "Personal a contrata" export (UTF-8, made-up people).
+The DIPRES Ley CSV (`;`, UTF-8 with BOM, accented headers), the demo CSV and
+the DIPRES Presupuesto workbook feed the golden-file cases (tests/golden).
+The PDF mimics the Contraloría "Ejecución Presupuestaria por Partida" report:
+a cover page, then the table with its header repeated on each page, a Total
+row and a footnote; text in Helvetica (WinAnsi, declared widths), the last
+page's content compressed. Output is deterministic.
+"""
+
+import os
+import zipfile
+import zlib
+from xml.sax.saxutils import escape
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+MONTHS = ["Enero", "Febrero", "Marzo", "Abril", "Mayo", "Junio"]
+
+
+def col(n):
+    name = ""
+    n += 1
+    while n:
+        n, rem = divmod(n - 1, 26)
+        name = chr(65 + rem) + name
+    return name
+
+
+def cell(ref, value):
+    if value is None or value == "":
+        return ""
+    if isinstance(value, (int, float)):
+        return f'<c r="{ref}"><v>{value}</v></c>'
+    return f'<c r="{ref}" t="inlineStr"><is><t>{escape(value)}</t></is></c>'
+
+
+def sheet_xml(rows, merges=()):
+    body = []
+    for r, row in enumerate(rows, start=1):
+        cells = "".join(cell(f"{col(c)}{r}", v) for c, v in enumerate(row))
+        body.append(f'<row r="{r}">{cells}</row>')
+    merge = ""
+    if merges:
+        merge = f'<mergeCells count="{len(merges)}">' + "".join(
+            f'<mergeCell ref="{m}"/>' for m in merges
+        ) + "</mergeCells>"
+    return (
+        '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>'
+        '<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">'
+        f'<sheetData>{"".join(body)}</sheetData>{merge}</worksheet>'
+    )
+
+
+def write_xlsx(path, sheets):
+    names = [name for name, _ in sheets]
+    content_types = (
+        '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>'
+        '<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">'
+        '<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>'
+        '<Default Extension="xml" ContentType="application/xml"/>'
+        '<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>'
+        + "".join(
+            f'<Override PartName="/xl/worksheets/sheet{i}.xml" '
+            'ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>'
+            for i in range(1, len(names) + 1)
+        )
+        + "</Types>"
+    )
+    root_rels = (
+        '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>'
+        '<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">'
+        '<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>'
+        "</Relationships>"
+    )
+    workbook = (
+        '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>'
+        '<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" '
+        'xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>'
+        + "".join(
+            f'<sheet name="{escape(n)}" sheetId="{i}" r:id="rId{i}"/>'
+            for i, n in enumerate(names, start=1)
+        )
+        + "</sheets></workbook>"
+    )
+    workbook_rels = (
+        '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>'
+        '<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">'
+        + "".join(
+            f'<Relationship Id="rId{i}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{i}.xml"/>'
+            for i in range(1, len(names) + 1)
+        )
+        + "</Relationships>"
+    )
+    with zipfile.ZipFile(path, "w", zipfile.ZIP_DEFLATED) as z:
+        def add(name, data):
+            info = zipfile.ZipInfo(name, date_time=(2025, 1, 1, 0, 0, 0))
+            info.compress_type = zipfile.ZIP_DEFLATED
+            z.writestr(info, data)
+
+        add("[Content_Types].xml", content_types)
+        add("_rels/.rels", root_rels)
+        add("xl/workbook.xml", workbook)
+        add("xl/_rels/workbook.xml.rels", workbook_rels)
+        for i, (_, xml) in enumerate(sheets, start=1):
+            add(f"xl/worksheets/sheet{i}.xml", xml)
+
+
+def ejecucion(partida_09_total, through_month=len(MONTHS)):
+    header_top = (
+        ["Partida", "Capítulo", "Programa", "Denominación", "Presupuesto Vigente", "Devengado"]
+        + [""] * 5
+        + ["Pagado"]
+        + [""] * 5
+    )
+    header_bottom = ["", "", "", "", ""] + MONTHS + MONTHS
+    rows = [
+        ["Ejecución Presupuestaria Mensual de Gastos - Gobierno Central"],
+        ["Año 2025 - Miles de $"],
+        [],
+        header_top,
+        header_bottom,
+        [9, "", "", "MINISTERIO DE EDUCACIÓN", 25000]
+        + partida_09_total
+        + [1300, 1000, 2100, 1450, 1550, 1400],
+        [9, 1, 1, "Subsecretaría de Educación", 20000, 1000, 1100, 1200, 1300, 1400, 1500]
+        + [900, 1000, 1100, 1200, 1300, 1400],
+        ["09", "01", "02", "Programa de Infraestructura", "5.000,0", 500, 0, "1.250,5", 250, 250, 250]
+        + [400, 0, "1.000,0", 250, 250, ""],
+        [16, "", "", "MINISTERIO DE SALUD", 15000, 2000, 2000, 2000, "", "", ""]
+        + [1800, 2000, 2000, "", "", ""],
+        [16, 1, 1, "Subsecretaría de Salud Pública", 15000, 2000, 2000, 2000, "", "", ""]
+        + [1800, 2000, 2000, "", "", ""],
+        [],
+        ["Fuente: DIPRES, Ejecución Presupuestaria"],
+        ["Nota: cifras en miles de pesos de cada año"],
+    ]
+    # An earlier issue of the cumulative workbook: later months not reported yet
+    for row in rows[5:10]:
+        for month in range(through_month, len(MONTHS)):
+            for column in (5 + month, 5 + len(MONTHS) + month):
+                if column < len(row):
+                    row[column] = ""
+    merges = ["A4:A5", "B4:B5", "C4:C5", "D4:D5", "E4:E5", "F4:K4", "L4:Q4"]
+    return [
+        ("Portada", sheet_xml([["Dirección de Presupuestos"], ["Informe de Ejecución Mensual"]])),
+        ("Ejecución Gastos", sheet_xml(rows, merges)),
+        (
+            "Notas",
+            sheet_xml(
+                [
+                    ["Presupuesto Vigente: ley inicial más modificaciones por decreto"],
+                    ["Devengado: gasto reconocido en el mes"],
+                    ["Pagado: gasto pagado en el mes"],
+                ]
+            ),
+        ),
+    ]
+
+
+def glyph_width(ch):
+    """Helvetica-like advance widths (1/1000 em), declared in the font's /Widths"""
+    base = ch.translate(str.maketrans("áéíóúÁÉÍÓÚñÑ", "aeiouAEIOUnN"))
+    if base in "0123456789$":
+        return 556
+    if base in " .,:;":
+        return 278
+    if base in "-()":
+        return 333
+    if base in "il":
+        return 222
+    if base in "ftIJ":
+        return 278
+    if base in "mM":
+        return 833
+    if base == "r":
+        return 333
+    if base.isupper():
+        return 667
+    return 556
+
+
+def text_width(text, size):
+    return sum(glyph_width(c) for c in text) * size / 1000
+
+
+def pdf_string(text):
+    raw = text.encode("cp1252")
+    return b"(" + raw.replace(b"\\", b"\\\\").replace(b"(", b"\\(").replace(b")", b"\\)") + b")"
+
+
+def page_content(items):
+    """items: (x, y, size, text, align) with align "l" or "r" (x is the right edge)"""
+    ops = []
+    for x, y, size, text, align in items:
+        if align == "r":
+            x -= text_width(text, size)
+        ops.append(
+            b"BT /F1 %d Tf 1 0 0 1 %.2f %.2f Tm " % (size, x, y) + pdf_string(text) + b" Tj ET"
+        )
+    return b"\n".join(ops) + b"\n"
+
+
+def write_pdf(path, pages):
+    """pages: list of (content bytes, compressed)"""
+    widths = " ".join(str(glyph_width(bytes([c]).decode("cp1252", "replace"))) for c in range(32, 256))
+    objects = [
+        b"<< /Type /Catalog /Pages 2 0 R >>",
+        b"<< /Type /Pages /Kids ["
+        + b" ".join(b"%d 0 R" % (4 + 2 * i) for i in range(len(pages)))
+        + b"] /Count %d >>" % len(pages),
+        (
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding "
+            f"/FirstChar 32 /LastChar 255 /Widths [{widths}] >>"
+        ).encode(),
+    ]
+    for i, (content, compressed) in enumerate(pages):
+        objects.append(
+            b"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] "
+            b"/Resources << /Font << /F1 3 0 R >> >> /Contents %d 0 R >>" % (5 + 2 * i)
+        )
+        if compressed:
+            data = zlib.compress(content, 9)
+            objects.append(b"<< /Length %d /Filter /FlateDecode >>\nstream\n" % len(data) + data + b"\nendstream")
+        else:
+            objects.append(b"<< /Length %d >>\nstream\n" % len(content) + content + b"\nendstream")
+
+    out = bytearray(b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n")
+    offsets = []
+    for n, obj in enumerate(objects, start=1):
+        offsets.append(len(out))
+        out += b"%d 0 obj\n" % n + obj + b"\nendobj\n"
+    xref = len(out)
+    out += b"xref\n0 %d\n0000000000 65535 f \n" % (len(objects) + 1)
+    for offset in offsets:
+        out += b"%010d 00000 n \n" % offset
+    out += b"trailer\n<< /Size %d /Root 1 0 R >>\nstartxref\n%d\n%%%%EOF\n" % (len(objects) + 1, xref)
+    with open(path, "wb") as f:
+        f.write(bytes(out))
+
+
+def contraloria_ejecucion(pagado_18_x=555):
+    title = [
+        (40, 800, 10, "CONTRALORÍA GENERAL DE LA REPÚBLICA", "l"),
+        (40, 786, 9, "Ejecución Presupuestaria del Sector Público por Partida - Año 2024", "l"),
+        (40, 774, 9, "(Miles de $)", "l"),
+    ]
+    header_y = 750
+    header = [
+        (40, header_y, 8, "Partida", "l"),
+        (90, header_y, 8, "Denominación", "l"),
+        (380, header_y, 8, "Presupuesto Vigente", "r"),
+        (470, header_y, 8, "Gasto Devengado", "r"),
+        (555, header_y, 8, "Gasto Pagado", "r"),
+    ]
+
+    def rows(lines):
+        items, y = [], header_y - 18
+        for code, name, vigente, devengado, pagado in lines:
+            if code:
+                items.append((48, y, 8, code, "l"))
+            if name:
+                items.append((90, y, 8, name, "l"))
+            pagado_x = pagado_18_x if code == "18" else 555
+            for x, value in ((380, vigente), (470, devengado), (pagado_x, pagado)):
+                if value:
+                    items.append((x, y, 8, value, "r"))
+            y -= 14
+        return items, y
+
+    page2, _ = rows(
+        [
+            ("09", "Ministerio de Educación", "15.234.567", "14.100.250", "13.950.100"),
+            ("10", "Ministerio de Justicia y", "1.845.300", "1.700.120", "1.650.000"),
+            ("", "Derechos Humanos", "", "", ""),
+            ("12", "Ministerio de Obras Públicas", "4.120.800", "3.900.450", "3.700.000"),
+        ]
+    )
+    page3, y = rows(
+        [
+            ("16", "Ministerio de Salud", "12.500.000", "12.100.900", "11.800.700"),
+            ("18", "Ministerio de Vivienda y Urbanismo", "3.300.250", "2.950.000", "-"),
+            ("", "Total", "37.000.917", "34.751.720", "31.100.800"),
+        ]
+    )
+    footnote = [(40, y - 10, 7, "Fuente: Contraloría General de la República, Balance de Ejecución Presupuestaria", "l")]
+    cover = [
+        (40, 700, 14, "CONTRALORÍA GENERAL DE LA REPÚBLICA", "l"),
+        (40, 680, 12, "Informe de Ejecución Presupuestaria del Sector Público", "l"),
+        (40, 664, 12, "Año 2024", "l"),
+    ]
+    return [
+        (page_content(cover), False),
+        (page_content(title + header + page2), False),
+        (page_content(title + header + page3 + footnote), True),
+    ]
+
+
+def write_csv(path, rows, encoding="latin-1", sep=";"):
+    text = "".join(sep.join(str(v) for v in row) + "\r\n" for row in rows)
+    with open(path, "wb") as f:
+        f.write(text.encode(encoding))
+
+
+def licitaciones():
+    header = [
+        "Codigo", "CodigoExterno", "Nombre", "Estado", "CodigoOrganismo", "NombreOrganismo",
+        "sector", "FechaAdjudicacion", "Codigoitem", "RutProveedor", "NombreProveedor",
+        "Moneda de la Oferta", "CantidadAdjudicada", "MontoLineaAdjudica", "Oferta seleccionada",
+    ]
+    ssmn = ["7248", "Servicio de Salud Metropolitano Norte", "Salud"]
+    providencia = ["6945", "Municipalidad de Providencia", "Municipalidades"]
+    return [
+        header,
+        ["101", "2401-5-LE25", "Insumos clínicos", "Adjudicada"] + ssmn
+        + ["2025-01-20 12:00:00", "1", "76.086.428-5", "Comercial Andes Ltda.", "Peso Chileno", "1", "12500000", "Seleccionada"],
+        ["101", "2401-5-LE25", "Insumos clínicos", "Adjudicada"] + ssmn
+        + ["2025-01-20 12:00:00", "1", "77.123.456-9", "Constructora Ñuble SpA", "Peso Chileno", "0", "13100000", "No Seleccionada"],
+        ["101", "2401-5-LE25", "Insumos clínicos", "Adjudicada"] + ssmn
+        + ["2025-01-20 12:00:00", "2", "77.123.456-9", "Constructora Ñuble SpA", "Peso Chileno", "1", "3400000,5", "Seleccionada"],
+        ["102", "2401-7-L125", "Mantención de áreas verdes", "Adjudicada"] + providencia
+        + ["28-01-2025", "1", "76.086.428-5", "Comercial Andes Ltda.", "CLP", "1", "850000", "Seleccionada"],
+        ["102", "2401-7-L125", "Mantención de áreas verdes", "Adjudicada"] + providencia
+        + ["28-01-2025", "1", "96.556.940-5", "Servicios Integrales del Sur S.A.", "CLP", "0", "910000", "No Seleccionada"],
+    ]
+
+
+def ordenes_compra():
+    header = [
+        "ID", "Codigo", "Nombre", "Estado", "CodigoOrganismoPublico", "OrganismoPublico",
+        "sector", "FechaCreacion", "FechaEnvio", "MontoTotalOC", "TipoMonedaOC",
+        "MontoTotalOC_PesosChilenos", "CodigoLicitacion", "NombreProveedor", "RutSucursal",
+        "IDItem", "cantidad", "totalLineaNeto",
+    ]
+    ssmn = ["7248", "Servicio de Salud Metropolitano Norte", "Salud"]
+    providencia = ["6945", "Municipalidad de Providencia", "Municipalidades"]
+    andes = ["Comercial Andes Ltda.", "76.086.428-5"]
+    return [
+        header,
+        ["1", "2401-120-SE25", "Insumos clínicos", "Aceptada"] + ssmn
+        + ["2025-01-21", "2025-01-22 09:15:00", "14875000", "CLP", "14875000", "2401-5-LE25"] + andes
+        + ["1", "100", "10000000"],
+        ["1", "2401-120-SE25", "Insumos clínicos", "Aceptada"] + ssmn
+        + ["2025-01-21", "2025-01-22 09:15:00", "14875000", "CLP", "14875000", "2401-5-LE25"] + andes
+        + ["2", "50", "2500000"],
+        ["2", "6945-33-AG25", "Asesoría jurídica", "Enviada a proveedor"] + providencia
+        + ["2025-01-14", "2025-01-15", "45,5", "CLF", "1750000", "", "Constructora Ñuble SpA", "77.123.456-9"]
+        + ["1", "1", "45,5"],
+        ["3", "6945-40-SE25", "Arriendo de vehículos", "Cancelada"] + providencia
+        + ["2025-01-16", "2025-01-17", "990000", "CLP", "990000", ""] + andes
+        + ["1", "1", "990000"],
+        ["4", "2401-130-CM25", "Compra ágil de resmas", "Recepción Conforme"] + ssmn
+        + ["2025-01-29", "2025-01-30", "480000", "CLP", "480000", "", "Servicios Integrales del Sur S.A.", "96.556.940-5"]
+        + ["1", "120", "403361"],
+    ]
+
+
+def dotacion_contrata():
+    header = [
+        "organismo_nombre", "organismo_codigo", "fecha_publicación", "anyo", "Mes",
+        "Tipo Estamento", "Nombres", "Paterno", "Materno", "grado_eus", "Tipo cargo", "region",
+        "tipo_unidad_monetaria", "remuneracionbruta_mensual", "remuliquida_mensual",
+        "fecha_ingreso", "fecha_termino",
+    ]
+    educacion = ["Subsecretaría de Educación", "AB001", "2025-02-10"]
+    patrimonio = ["Servicio Nacional del Patrimonio Cultural", "AD010", "2025-02-12"]
+    jardines = ["Junta Nacional de Jardines Infantiles", "AJ005", "2025-04-08"]
+    region = "Región Metropolitana de Santiago"
+    return [
+        header,
+        educacion + ["2025", "Enero", "Profesional", "Ana", "Rojas", "Soto", "8", "Analista", region,
+                     "Pesos", "2.100.000", "1.680.000", "01/03/2020", "31/12/2025"],
+        educacion + ["2025", "Enero", "Técnico", "Luis", "Muñoz", "Pérez", "14", "Técnico", region,
+                     "Pesos", "1.550.000,5", "1.240.000", "01/06/2022", "31/12/2025"],
+        educacion + ["2025", "Enero", "Administrativo", "Eva", "Díaz", "Vera", "18", "Secretaria", region,
+                     "Pesos", "1500000", "1200000", "01/01/2024", "31/12/2025"],
+        educacion + ["2025", "Febrero", "Profesional", "Ana", "Rojas", "Soto", "8", "Analista", region,
+                     "Pesos", "1.800.000", "1.440.000", "01/03/2020", "31/12/2025"],
+        educacion + ["2025", "Febrero", "Técnico", "Luis", "Muñoz", "Pérez", "14", "Técnico", region,
+                     "Pesos", "", "", "01/06/2022", "31/12/2025"],
+        patrimonio + ["2025", "Enero", "Profesional", "Juan", "Pino", "Lagos", "10", "Conservador",
+                      "Región de Valparaíso", "Pesos", "", "", "01/01/2025", "30/06/2025"],
+        jardines + ["2025", "Marzo", "Técnico", "Rosa", "Vidal", "Araya", "16", "Educadora",
+                    "Región del Biobío", "Pesos", "950000", "760000", "01/03/2025", "31/12/2025"],
+    ]
+
+
+def dipres_ley():
+    header = [
+        "Partida", "Capítulo", "Programa", "Subtítulo", "Ítem", "Asignación", "Denominación",
+        "Monto Pesos", "Monto Dólar",
+    ]
+    return [
+        header,
+        ["01", "01", "01", "21", "00", "000", "GASTOS EN PERSONAL", "100000", "0"],
+        ["01", "01", "01", "22", "00", "000", "BIENES Y SERVICIOS DE CONSUMO", "50000", "0"],
+        ["06", "01", "01", "09", "00", "000", "APORTE FISCAL", "10000", "5000"],
+        ["06", "01", "01", "22", "00", "000", "BIENES Y SERVICIOS DE CONSUMO", "10000", "5000"],
+        ["09", "01", "01", "05", "02", "001", "DEL TESORO PÚBLICO", "250000", "0"],
+        ["09", "01", "01", "21", "00", "000", "GASTOS EN PERSONAL", "300000", "0"],
+        ["50", "01", "05", "24", "02", "001", "A PARTIDA 09", "250000", "0"],
+    ]
+
+
+def dipres_ley_2020():
+    # Earlier files: headers without accents, Latin-1, no Partida 06 lines
+    header = [
+        "Partida", "Capitulo", "Programa", "Subtitulo", "Item", "Asignacion", "Denominacion",
+        "Monto Pesos", "Monto Dolar",
+    ]
+    return [
+        header,
+        ["01", "01", "01", "21", "00", "000", "GASTOS EN PERSONAL", "90000", "0"],
+        ["01", "01", "01", "22", "00", "000", "BIENES Y SERVICIOS DE CONSUMO", "45000", "0"],
+        ["09", "01", "01", "05", "02", "001", "DEL TESORO PÚBLICO", "200000", "0"],
+        ["09", "01", "01", "21", "00", "000", "GASTOS EN PERSONAL", "240000", "0"],
+        ["50", "01", "05", "24", "02", "001", "A PARTIDA 09", "200000", "0"],
+    ]
+
+
+def dipres_ley_2023():
+    # Accented headers as in 2026, but Latin-1; Partida 13 appears
+    header = [
+        "Partida", "Capítulo", "Programa", "Subtítulo", "Ítem", "Asignación", "Denominación",
+        "Monto Pesos", "Monto Dólar",
+    ]
+    return [
+        header,
+        ["01", "01", "01", "21", "00", "000", "GASTOS EN PERSONAL", "95000", "0"],
+        ["06", "01", "01", "09", "00", "000", "APORTE FISCAL", "8000", "4000"],
+        ["06", "01", "01", "22", "00", "000", "BIENES Y SERVICIOS DE CONSUMO", "8000", "4000"],
+        ["09", "01", "01", "21", "00", "000", "GASTOS EN PERSONAL", "280000", "0"],
+        ["13", "01", "01", "31", "02", "004", "OBRAS CIVILES", "60000", "0"],
+    ]
+
+
+def demo_presupuesto():
+    return [
+        ["entidad", "categoria", "anio", "monto"],
+        ["Ministerio de Educación", "Personal", "2024", "1250000000000"],
+        ["MINEDUC", "Operaciones", "2024", "$ 450.000.000"],
+        ["Servicio de Salud Aconcagua", "Personal", "2024", "1000000"],
+        ["Ministerio de Salud", "", "2024", "mil"],
+    ]
+
+
+def dipres_presupuesto():
+    rows = [
+        ["Organismo", "Categoria", "Monto"],
+        ["Ministerio de Educación", "Personal", 1250000000],
+        ["MINISTERIO DE SALUD", "Operaciones", 980000000.5],
+        ["Servicio de Salud Aconcagua", "Personal", 0],
+        ["Dirección de Presupuestos", "", "1.500.000"],
+    ]
+    return [("Presupuesto", sheet_xml(rows))]
+
+
+def main():
+    write_xlsx(
+        os.path.join(HERE, "dipres_ejecucion_2025.xlsx"),
+        ejecucion([1500, 1100, "2.450,5", 1550, 1650, 1750]),
+    )
+    # The same year as issued in February: January only
+    write_xlsx(
+        os.path.join(HERE, "dipres_ejecucion_2025_01.xlsx"),
+        ejecucion([1500, 1100, "2.450,5", 1550, 1650, 1750], through_month=1),
+    )
+    # Partida 09, March: 2450 instead of 2450.5
+    write_xlsx(
+        os.path.join(HERE, "dipres_ejecucion_2025_bad_total.xlsx"),
+        ejecucion([1500, 1100, 2450, 1550, 1650, 1750]),
+    )
+    write_csv(os.path.join(HERE, "mercadopublico_licitaciones_2025-01.csv"), licitaciones())
+    write_csv(os.path.join(HERE, "mercadopublico_ordenes_compra_2025-01.csv"), ordenes_compra())
+    write_csv(
+        os.path.join(HERE, "transparencia_dotacion_contrata_2025.csv"), dotacion_contrata(), "utf-8"
+    )
+    write_csv(os.path.join(HERE, "dipres_ley_presupuestos_2026.csv"), dipres_ley(), "utf-8-sig")
+    write_csv(os.path.join(HERE, "dipres_ley_presupuestos_2020.csv"), dipres_ley_2020())
+    write_csv(os.path.join(HERE, "dipres_ley_presupuestos_2023.csv"), dipres_ley_2023())
+    write_csv(os.path.join(HERE, "demo_presupuesto_2024.csv"), demo_presupuesto(), "utf-8", ",")
+    write_xlsx(os.path.join(HERE, "dipres_presupuesto_2024.xlsx"), dipres_presupuesto())
+    write_pdf(os.path.join(HERE, "contraloria_ejecucion_2024.pdf"), contraloria_ejecucion())
+    # Partida 18 pagado ("-") drawn in the gap between Devengado and Pagado
+    write_pdf(
+        os.path.join(HERE, "contraloria_ejecucion_2024_bad_layout.pdf"), contraloria_ejecucion(490)
+    )
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,176 @@
+//! Golden-file regression test: every case of tests/golden/cases.json must
+//! produce, byte for byte, its stored canonical JSON (PRINCIPLES.md #1)
+//!
+//! After an intentional output change, regenerate the goldens and review
+//! their diff before committing:
+//!
+//!     UPDATE_GOLDENS=1 cargo test -p parser --test golden
+
+use parser::golden::canonical_json;
+use parser::{ArtifactInput, Parser, ParserRegistry};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+const UPDATE_VAR: &str = "UPDATE_GOLDENS";
+
+#[derive(Debug, Deserialize)]
+struct CasesFile {
+    cases: Vec<GoldenCase>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoldenCase {
+    name: String,
+    source_id: String,
+    mime_type: String,
+    /// File under tests/fixtures
+    artifact: String,
+    /// Parser the registry must pick for the artifact
+    parser: String,
+    /// Use `parser` by id instead of letting the registry pick one
+    #[serde(default)]
+    forced: bool,
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+/// Canonical output of a case, checked to be stable within the run
+fn run_case(registry: &ParserRegistry, case: &GoldenCase) -> Result<String, String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(&case.artifact);
+    let bytes = std::fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let input = ArtifactInput {
+        source_id: &case.source_id,
+        mime_type: &case.mime_type,
+        storage_path: &case.artifact,
+        bytes: &bytes,
+    };
+
+    let parser: &dyn Parser = if case.forced {
+        registry.get(&case.parser)
+    } else {
+        registry.select(&input)
+    }
+    .ok_or_else(|| format!("no parser for the artifact (expected {})", case.parser))?;
+    if parser.id() != case.parser {
+        return Err(format!(
+            "registry picked {}, expected {}",
+            parser.id(),
+            case.parser
+        ));
+    }
+
+    let render = || -> Result<String, String> {
+        let fingerprint = parser
+            .fingerprint(&input)
+            .map_err(|e| format!("fingerprint failed: {:#}", e))?;
+        let output = parser
+            .parse(&input)
+            .map_err(|e| format!("parse failed: {:#}", e))?;
+        canonical_json(parser.id(), &output, fingerprint.as_ref()).map_err(|e| e.to_string())
+    };
+    let first = render()?;
+    if render()? != first {
+        return Err("two parses of the same bytes differ".to_string());
+    }
+    Ok(first)
+}
+
+/// First differing line, for a readable failure
+fn first_difference(expected: &str, actual: &str) -> String {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    for line in 1.. {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => continue,
+            (None, None) => break,
+            (e, a) => {
+                return format!(
+                    "line {}:\n  golden: {}\n  actual: {}",
+                    line,
+                    e.unwrap_or("<end of file>"),
+                    a.unwrap_or("<end of file>")
+                )
+            }
+        }
+    }
+    // Same lines: line endings or the trailing newline differ
+    "line endings or trailing newline differ".to_string()
+}
+
+#[test]
+fn golden_outputs_are_byte_identical() {
+    let dir = golden_dir();
+    let cases: CasesFile = serde_json::from_str(
+        &std::fs::read_to_string(dir.join("cases.json")).expect("tests/golden/cases.json"),
+    )
+    .expect("valid tests/golden/cases.json");
+    let update = std::env::var_os(UPDATE_VAR).is_some();
+    let registry = ParserRegistry::builtin();
+    let mut failures = Vec::new();
+
+    let mut names = BTreeSet::new();
+    for case in &cases.cases {
+        if !names.insert(format!("{}.json", case.name)) {
+            failures.push(format!("{}: duplicate case name", case.name));
+            continue;
+        }
+        let actual = match run_case(&registry, case) {
+            Ok(actual) => actual,
+            Err(e) => {
+                failures.push(format!("{}: {}", case.name, e));
+                continue;
+            }
+        };
+
+        let golden = dir.join(format!("{}.json", case.name));
+        let expected = std::fs::read(&golden).ok();
+        if update {
+            if expected.as_deref() != Some(actual.as_bytes()) {
+                std::fs::write(&golden, &actual).expect("write golden");
+                println!("updated {}", golden.display());
+            }
+        } else {
+            match expected {
+                None => failures.push(format!(
+                    "{}: no golden file; run `{}=1 cargo test -p parser --test golden`",
+                    case.name, UPDATE_VAR
+                )),
+                Some(expected) if expected != actual.as_bytes() => failures.push(format!(
+                    "{}: output differs from {}\n{}",
+                    case.name,
+                    golden.display(),
+                    first_difference(&String::from_utf8_lossy(&expected), &actual)
+                )),
+                Some(_) => {}
+            }
+        }
+    }
+
+    // A golden without a case would never be checked again
+    for entry in std::fs::read_dir(&dir).expect("tests/golden") {
+        let file = entry
+            .expect("golden entry")
+            .file_name()
+            .to_string_lossy()
+            .to_string();
+        if file != "cases.json" && file.ends_with(".json") && !names.contains(&file) {
+            failures.push(format!(
+                "{}: golden file without a case in cases.json",
+                file
+            ));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} golden case(s) failed (if the change is intended: {}=1 cargo test -p parser --test golden, then review the diff):\n\n{}",
+        failures.len(),
+        UPDATE_VAR,
+        failures.join("\n\n")
+    );
+}
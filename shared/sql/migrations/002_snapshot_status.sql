@@ -0,0 +1,47 @@
+-- 002_snapshot_status.sql — snapshot lifecycle (staging → published → superseded)
+--
+-- A parser run writes its facts into a 'staging' snapshot. Once every fact is
+-- inserted the snapshot is promoted to 'published' and the previously published
+-- snapshot for the same artifact + parser is marked 'superseded'.
+-- The API only reads facts from 'published' snapshots unless a snapshot_id is
+-- requested explicitly.
+
+ALTER TABLE snapshots ADD COLUMN IF NOT EXISTS artifact_id UUID REFERENCES artifacts(artifact_id) ON DELETE CASCADE;
+ALTER TABLE snapshots ADD COLUMN IF NOT EXISTS parser TEXT;
+ALTER TABLE snapshots ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'published'; -- staging|published|superseded
+ALTER TABLE snapshots ADD COLUMN IF NOT EXISTS published_at TIMESTAMPTZ;
+ALTER TABLE snapshots ADD COLUMN IF NOT EXISTS superseded_by UUID REFERENCES snapshots(snapshot_id);
+
+-- At most one published snapshot per artifact + parser
+CREATE UNIQUE INDEX IF NOT EXISTS idx_snapshots_published
+  ON snapshots(artifact_id, parser)
+  WHERE status = 'published';
+
+CREATE INDEX IF NOT EXISTS idx_snapshots_status ON snapshots(status);
+CREATE INDEX IF NOT EXISTS idx_facts_snapshot ON facts(snapshot_id);
+
+-- Backfill artifact_id for snapshots created before this migration
+UPDATE snapshots s
+SET artifact_id = sub.artifact_id
+FROM (
+  SELECT DISTINCT f.snapshot_id, p.artifact_id
+  FROM facts f
+  JOIN provenance p ON p.fact_id = f.fact_id
+) sub
+WHERE s.snapshot_id = sub.snapshot_id AND s.artifact_id IS NULL;
+
+-- Legacy duplicate runs: keep only the most recent snapshot per artifact +
+-- parser published. Parsers sharing an artifact (e.g. dipres_ley_csv_v1 and
+-- dipres_ley_csv_lines_v1) keep one each, so re-running this is a no-op
+UPDATE snapshots s
+SET status = 'superseded', superseded_by = latest.snapshot_id
+FROM (
+  SELECT DISTINCT ON (artifact_id, parser) artifact_id, parser, snapshot_id
+  FROM snapshots
+  WHERE artifact_id IS NOT NULL AND status = 'published'
+  ORDER BY artifact_id, parser, created_at DESC
+) latest
+WHERE s.artifact_id = latest.artifact_id
+  AND s.parser IS NOT DISTINCT FROM latest.parser
+  AND s.snapshot_id <> latest.snapshot_id
+  AND s.status = 'published';
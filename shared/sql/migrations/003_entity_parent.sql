@@ -0,0 +1,8 @@
+-- 003_entity_parent.sql — parent links between entities
+--
+-- DIPRES line-item facts hang from a Programa entity, whose parent is its
+-- Capitulo, whose parent is its Partida. Root entities have no parent.
+
+ALTER TABLE entities ADD COLUMN IF NOT EXISTS parent_entity_id UUID REFERENCES entities(entity_id);
+
+CREATE INDEX IF NOT EXISTS idx_entities_parent ON entities(parent_entity_id);
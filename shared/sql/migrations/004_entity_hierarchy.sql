@@ -0,0 +1,26 @@
+-- 004_entity_hierarchy.sql — entity levels (partida → capítulo → programa)
+--
+-- `entity_levels` declares which entity types form the institutional
+-- hierarchy and which type is allowed as parent of each one.
+-- `entities.level` is filled from it when the entity is created.
+
+CREATE TABLE IF NOT EXISTS entity_levels (
+  entity_type TEXT PRIMARY KEY,
+  level SMALLINT NOT NULL, -- 1 = root
+  parent_type TEXT REFERENCES entity_levels(entity_type),
+  description TEXT
+);
+
+INSERT INTO entity_levels (entity_type, level, parent_type, description) VALUES
+  ('partida', 1, NULL, 'Partida presupuestaria (ministerio o poder del Estado)'),
+  ('capitulo', 2, 'partida', 'Capítulo (servicio o institución dentro de la Partida)'),
+  ('programa', 3, 'capitulo', 'Programa presupuestario dentro del Capítulo')
+ON CONFLICT (entity_type) DO NOTHING;
+
+ALTER TABLE entities ADD COLUMN IF NOT EXISTS level SMALLINT;
+
+-- Backfill levels for entities created before this migration
+UPDATE entities e
+SET level = l.level
+FROM entity_levels l
+WHERE e.entity_type = l.entity_type AND e.level IS NULL;
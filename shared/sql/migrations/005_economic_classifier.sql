@@ -0,0 +1,17 @@
+-- 005_economic_classifier.sql — economic classifier catalog (subtítulo / ítem / asignación)
+--
+-- Seed: config/clasificador_economico.json (synced by the parser on each run).
+-- Facts reference it through dims.subtitulo_code / dims.item_code / dims.classifier_code.
+
+CREATE TABLE IF NOT EXISTS economic_classifier (
+  code TEXT NOT NULL,            -- "21", "21.01", "21.01.001"
+  level TEXT NOT NULL,           -- subtitulo|item|asignacion
+  name TEXT NOT NULL,
+  kind TEXT NOT NULL,            -- ingreso|gasto
+  valid_from_year INT NOT NULL,
+  valid_to_year INT,             -- NULL = still in force
+  catalog_version TEXT NOT NULL,
+  PRIMARY KEY (code, valid_from_year)
+);
+
+CREATE INDEX IF NOT EXISTS idx_facts_dims_subtitulo ON facts((dims->>'subtitulo_code'));
@@ -0,0 +1,13 @@
+-- 006_consolidation_rules.sql — netting rules for consolidated budget totals
+--
+-- Seed: config/consolidacion.json (synced by the parser on each run).
+-- Tagged line items carry dims.consolidation_rule; per-Partida runs emit the
+-- tagged amount as the metric presupuesto_ley_gasto_transferencias.
+-- Consolidated total = presupuesto_ley_gasto - presupuesto_ley_gasto_transferencias.
+
+CREATE TABLE IF NOT EXISTS consolidation_rules (
+  rule_id TEXT PRIMARY KEY,
+  classifier_prefix TEXT NOT NULL,  -- "24.02" matches 24.02 and 24.02.xxx
+  description TEXT NOT NULL,
+  rules_version TEXT NOT NULL
+);
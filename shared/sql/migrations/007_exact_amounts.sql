@@ -0,0 +1,16 @@
+-- 007_exact_amounts.sql — store fact amounts as exact decimals
+--
+-- DOUBLE PRECISION only keeps ~15-16 significant digits; national budget
+-- totals in CLP (tens of trillions, summed across thousands of lines) need
+-- lossless storage. Parser and API use a decimal type end to end and the API
+-- serializes amounts as JSON strings.
+-- Existing values are converted as-is (no precision can be recovered for them;
+-- re-parse with --reparse to get exact figures).
+
+DO $$
+BEGIN
+  IF (SELECT data_type FROM information_schema.columns
+      WHERE table_name = 'facts' AND column_name = 'value_num') = 'double precision' THEN
+    ALTER TABLE facts ALTER COLUMN value_num TYPE NUMERIC USING value_num::numeric;
+  END IF;
+END $$;
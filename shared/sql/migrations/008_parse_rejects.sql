@@ -0,0 +1,18 @@
+-- 008_parse_rejects.sql — row-level rejects from parser runs
+--
+-- Each parser run records a structured report in job_runs.detail.report
+-- (accepted / skipped / rejected counts, reject ratio, totals checks) and one
+-- row here per rejected input row, so a run can be audited without its stdout.
+
+CREATE TABLE IF NOT EXISTS parse_rejects (
+  reject_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  job_run_id UUID NOT NULL REFERENCES job_runs(job_run_id) ON DELETE CASCADE,
+  artifact_id UUID NOT NULL REFERENCES artifacts(artifact_id) ON DELETE CASCADE,
+  parser TEXT NOT NULL,
+  line INT NOT NULL,      -- CSV line or 1-based XLS row
+  reason TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_parse_rejects_artifact ON parse_rejects(artifact_id);
+CREATE INDEX IF NOT EXISTS idx_parse_rejects_job_run ON parse_rejects(job_run_id);
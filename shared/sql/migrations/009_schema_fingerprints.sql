@@ -0,0 +1,21 @@
+-- 009_schema_fingerprints.sql — structural fingerprint per artifact and drift between fiscal years
+--
+-- Before parsing, the parser records the layout of the artifact (headers,
+-- column types, partida / programa / subtítulo codes, row count) and compares
+-- it with the latest fingerprint of an earlier fiscal year of the same source
+-- family (source_id without the year). Drift is stored even if the parse halts.
+
+CREATE TABLE IF NOT EXISTS schema_fingerprints (
+  artifact_id UUID PRIMARY KEY REFERENCES artifacts(artifact_id) ON DELETE CASCADE,
+  source_id TEXT NOT NULL,
+  source_family TEXT NOT NULL,   -- e.g. dipres-ley-presupuestos
+  fiscal_year INT NOT NULL,
+  fingerprint JSONB NOT NULL,
+  compared_to UUID REFERENCES artifacts(artifact_id) ON DELETE SET NULL,
+  drift JSONB,                   -- null when there is no earlier year to compare with
+  has_drift BOOLEAN NOT NULL DEFAULT false,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_schema_fingerprints_family
+  ON schema_fingerprints(source_family, fiscal_year);
@@ -0,0 +1,14 @@
+-- 010_partida_catalog.sql — official Partida names per fiscal year
+--
+-- Seed: config/partidas.json (synced by the parser on each run).
+-- entities.display_name keeps the name seen when the entity was first created;
+-- the API resolves the name in force for the requested year from this table.
+
+CREATE TABLE IF NOT EXISTS partida_catalog (
+  code TEXT NOT NULL,            -- "01" .. "50"
+  name TEXT NOT NULL,
+  valid_from_year INT NOT NULL,
+  valid_to_year INT,             -- NULL = still in force
+  catalog_version TEXT NOT NULL,
+  PRIMARY KEY (code, valid_from_year)
+);
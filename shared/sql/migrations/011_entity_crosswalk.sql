@@ -0,0 +1,37 @@
+-- 011_entity_crosswalk.sql — per-source entity aliases and review queue
+--
+-- Canonical entities are the Partidas (partida_NN). The parser resolves each
+-- source entity name by code, by alias (config/entity_aliases.json) or by the
+-- official Partida name in force that year, and records the outcome here.
+-- Unmatched names keep a source-local entity and wait in the review queue;
+-- they are resolved by adding an alias to the seed file and re-parsing.
+
+CREATE TABLE IF NOT EXISTS entity_aliases (
+  source_id TEXT NOT NULL,
+  normalized_name TEXT NOT NULL,   -- lowercase, no accents or punctuation
+  source_name TEXT NOT NULL,       -- as written in the source
+  entity_key TEXT NOT NULL,        -- canonical entity, e.g. partida_09
+  match_rule TEXT NOT NULL,        -- code|alias|official_name
+  last_artifact_id UUID REFERENCES artifacts(artifact_id) ON DELETE SET NULL,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  PRIMARY KEY (source_id, normalized_name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_entity_aliases_entity ON entity_aliases(entity_key);
+
+CREATE TABLE IF NOT EXISTS entity_review_queue (
+  source_id TEXT NOT NULL,
+  normalized_name TEXT NOT NULL,
+  source_name TEXT NOT NULL,
+  local_entity_key TEXT NOT NULL,  -- source-local entity the facts were stored under
+  rows INT NOT NULL,               -- rows with this name in the last run
+  status TEXT NOT NULL DEFAULT 'pending', -- pending|resolved
+  resolved_entity_key TEXT,
+  first_artifact_id UUID REFERENCES artifacts(artifact_id) ON DELETE SET NULL,
+  last_artifact_id UUID REFERENCES artifacts(artifact_id) ON DELETE SET NULL,
+  first_seen_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  PRIMARY KEY (source_id, normalized_name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_entity_review_queue_status ON entity_review_queue(status);
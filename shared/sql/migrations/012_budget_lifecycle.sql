@@ -0,0 +1,19 @@
+-- 012_budget_lifecycle.sql — stages of the spending cycle and their metrics
+--
+-- Ley inicial, vigente (after decree modifications), devengado and pagado are
+-- distinct metrics from distinct sources. This table links each metric to its
+-- stage so they can be compared side by side and never summed together.
+-- Synced by the parser from config/ciclo_presupuestario.json.
+
+CREATE TABLE IF NOT EXISTS budget_lifecycle (
+  metric_key TEXT PRIMARY KEY,
+  stage TEXT NOT NULL,             -- ley|vigente|devengado|pagado
+  stage_order INT NOT NULL,        -- 1 = ley ... 4 = pagado
+  stage_name TEXT NOT NULL,
+  metric_priority INT NOT NULL,    -- 1 = preferred metric of the stage
+  period_kind TEXT NOT NULL,       -- year|month
+  description TEXT NOT NULL,
+  lifecycle_version TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_budget_lifecycle_stage ON budget_lifecycle(stage_order, metric_priority);
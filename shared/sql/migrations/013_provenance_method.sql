@@ -0,0 +1,15 @@
+-- 013_provenance_method.sql — provenance.method names the parser that read the fact
+--
+-- Before this migration every fact was recorded with the fixed method
+-- 'csv_parser_v1', whichever parser produced it. Backfill it from the parser
+-- of the fact's snapshot; snapshots without a parser (before migration 002)
+-- keep the old value. Re-running is a no-op.
+
+UPDATE provenance p
+SET method = s.parser
+FROM facts f
+JOIN snapshots s ON s.snapshot_id = f.snapshot_id
+WHERE p.fact_id = f.fact_id
+  AND p.method = 'csv_parser_v1'
+  AND s.parser IS NOT NULL
+  AND s.parser <> 'csv_parser_v1';
@@ -0,0 +1,13 @@
+-- 014_licitacion_item_dim.sql — licitación line numbers move to dims.licitacion_item
+--
+-- The Mercado Público licitaciones parser stored the line number of the
+-- licitación as dims.item_code, the key the economic classifier uses for its
+-- ítem, so /facts?item= and /facts/by-classifier could match award lines.
+-- Rename the key on the facts that parser produced. Re-running is a no-op.
+
+UPDATE facts f
+SET dims = (f.dims - 'item_code') || jsonb_build_object('licitacion_item', f.dims->'item_code')
+FROM snapshots s
+WHERE s.snapshot_id = f.snapshot_id
+  AND s.parser = 'mercadopublico_licitaciones_csv_v1'
+  AND f.dims ? 'item_code';